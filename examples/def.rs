@@ -0,0 +1,63 @@
+use reda_gds::{DefLayer, DefReader, GdsLibrary, RasterOptions};
+
+/// A placed and routed design using the sense amplifier and flip-flop cells
+const DESIGN: &str = r#"
+VERSION 5.8 ;
+DIVIDERCHAR "/" ;
+BUSBITCHARS "[]" ;
+DESIGN sense_top ;
+UNITS DISTANCE MICRONS 1000 ;
+DIEAREA ( 0 0 ) ( 8000 7000 ) ;
+ROW ROW_0 core 0 0 N DO 10 BY 1 STEP 380 0 ;
+COMPONENTS 4 ;
+- sa0 sense_amp + PLACED ( 0 0 ) N ;
+- sa1 sense_amp + PLACED ( 1410 0 ) FN ;
+- ff0 dff + FIXED ( 3000 0 ) N ;
+- ff1 dff + PLACED ( 3000 5000 ) FS ;
+END COMPONENTS
+PINS 1 ;
+- clk + NET clk + DIRECTION INPUT + USE SIGNAL
+  + LAYER metal2 ( -35 0 ) ( 35 70 ) + PLACED ( 4000 0 ) N ;
+END PINS
+SPECIALNETS 1 ;
+- vdd ( * vdd ) + USE POWER
+  + ROUTED metal1 130 + SHAPE STRIPE ( 0 6500 ) ( 8000 6500 )
+    NEW metal2 140 ( 7800 0 ) ( 7800 6500 ) via1
+  + RECT metal1 ( 7700 6400 ) ( 7900 6600 ) ;
+END SPECIALNETS
+NETS 1 ;
+- clk ( PIN clk ) ( ff0 clk ) ( ff1 clk )
+  + ROUTED metal2 ( 4000 35 ) ( * 3000 ) via1 ( 4800 * 50 )
+    NEW metal1 ( 4000 3000 ) ( 4500 3000 ) RECT ( -50 -50 50 50 ) ;
+END NETS
+END DESIGN
+"#;
+
+/// Place the cells of a DEF design into a library of the leaf cells
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all("./temp")?;
+
+    let mut lib = GdsLibrary::load_file("./data/cells/sense_amp.gds")?;
+    let dff = GdsLibrary::load_file("./data/cells/dff.gds")?;
+    for structure in dff.ordered_structures() {
+        lib.add_structure(structure.read().unwrap().clone());
+    }
+
+    let mut reader = DefReader::new(DESIGN.as_bytes())
+        .with_layer("metal1", DefLayer::new(11, 0, 0.065))
+        .with_layer("metal2", DefLayer::new(13, 0, 0.07))
+        .with_via_layers("via1", "metal1", "metal2")
+        .with_boundary_layer(239, 0)
+        .with_die_area_layer(235, 0);
+    let top = reader.read_into(&mut lib)?;
+
+    lib.save_gds_file("./temp/sense_top.gds")?;
+    lib.render_image(&top, &RasterOptions::new(800, 700))?.save_png("./temp/sense_top.png")?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
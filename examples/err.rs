@@ -1,8 +1,17 @@
-use reda_gds::GdsLibrary;
+use reda_gds::{GdsLibrary, ReadOptions};
 
 fn main_result() -> Result<(), Box<dyn std::error::Error>> {
-    let lib = GdsLibrary::load_file("./data/cell_err.gds")?;
-    eprintln!("{}", lib.name);
+    // A strict read stops at the first problem
+    if let Err(e) = GdsLibrary::load_file("./data/cell_err.gds") {
+        eprintln!("{}", e.report());
+    }
+
+    // Recovery mode keeps everything that could be read
+    let (lib, diagnostics) = GdsLibrary::load_file_with_options("./data/cell_err.gds", ReadOptions::recovery())?;
+    eprintln!("{}: {} structures", lib.name, lib.structures.len());
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic);
+    }
     Ok(())
 }
 
@@ -10,4 +19,4 @@ fn main() {
     if let Err(e) = main_result() {
         eprintln!("{}", e);
     }
-}
\ No newline at end of file
+}
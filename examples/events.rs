@@ -0,0 +1,28 @@
+use reda_gds::{GdsEvent, GdsReader};
+
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = GdsReader::open("./data/sram/sram_1rw0r0w_8_256_freepdk45.gds")?;
+
+    let mut structure = String::new();
+    let mut elements = 0;
+    for event in reader.events() {
+        match event? {
+            GdsEvent::BeginStructure { name, .. } => {
+                structure = name;
+                elements = 0;
+            }
+            GdsEvent::EndStructure => eprintln!("{}: {} elements", structure, elements),
+            GdsEvent::Boundary(_) | GdsEvent::Path(_) | GdsEvent::Sref(_) | GdsEvent::Aref(_) |
+            GdsEvent::Text(_) | GdsEvent::Node(_) | GdsEvent::Box(_) => elements += 1,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,20 @@
+use reda_gds::GdsIndexedReader;
+
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = GdsIndexedReader::open("./data/sram/sram_1rw0r0w_8_256_freepdk45.gds")?;
+    eprintln!("{} structures", reader.structures().len());
+
+    let bitcell = reader.load_structure("cell_1rw")?;
+    eprintln!("{}: {} boundaries", bitcell.name, bitcell.boundarys().count());
+
+    let bank = reader.load_with_dependencies("sram_1rw0r0w_8_256_freepdk45_bank")?;
+    eprintln!("bank and its dependencies: {} structures", bank.structures.len());
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
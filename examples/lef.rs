@@ -0,0 +1,23 @@
+use reda_gds::{GdsLibrary, LefOptions};
+
+/// Write a LEF macro of the sense amplifier, write driver and flip-flop cells
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all("./temp")?;
+
+    let options = LefOptions::new()
+        .with_layer(11, 0, "metal1")
+        .with_layer(13, 0, "metal2")
+        .with_boundary_layer(239, 0)
+        .with_symmetry("X Y");
+    for cell in ["sense_amp", "write_driver", "dff"] {
+        let lib = GdsLibrary::load_file(format!("./data/cells/{}.gds", cell))?;
+        lib.save_lef(format!("./temp/{}.lef", cell), &[cell], options.clone())?;
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
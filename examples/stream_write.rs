@@ -0,0 +1,44 @@
+use reda_gds::{GdsBoundary, GdsDateTime, GdsLibraryBuilder, GdsSref, GdsStreamWriter};
+
+/// Write a 256 x 256 array of bitcells one row structure at a time
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    let header = GdsLibraryBuilder::default()
+        .version(600)
+        .create_date(GdsDateTime::now())
+        .modify_date(GdsDateTime::now())
+        .name("ARRAY".to_string())
+        .usrunits_per_dbunit(0.001)
+        .meters_per_dbunit(1e-9)
+        .structures(Default::default())
+        .build()?;
+
+    std::fs::create_dir_all("./temp")?;
+    let mut writer = GdsStreamWriter::create("./temp/array.gds", &header)?;
+
+    writer.begin_structure("bitcell")?;
+    writer.write_boundary(&GdsBoundary::rect(1, (0, 0), (100, 200)))?;
+    writer.end_structure()?;
+
+    for row in 0..256 {
+        writer.begin_structure(format!("row_{row}"))?;
+        for col in 0..256 {
+            writer.write_sref(&GdsSref::new("bitcell", (col * 100, 0), None))?;
+        }
+        writer.end_structure()?;
+    }
+
+    writer.begin_structure("array")?;
+    for row in 0..256 {
+        writer.write_sref(&GdsSref::new(format!("row_{row}"), (0, row * 200), None))?;
+    }
+    writer.end_structure()?;
+
+    writer.finish()?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+use reda_gds::{GdsLibrary, RasterOptions};
+
+/// Draw a PNG thumbnail of every structure of the leafcells and the sram array
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all("./temp/thumbnails")?;
+
+    let mut paths: Vec<PathBuf> = vec![];
+    for dir in ["./data/leafcell", "./data/sram"] {
+        for entry in std::fs::read_dir(dir)? {
+            paths.push(entry?.path());
+        }
+    }
+    paths.sort();
+
+    let options = RasterOptions::thumbnail(256);
+    for path in paths {
+        let lib = GdsLibrary::load_file(&path)?;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        for structure in lib.ordered_structures() {
+            let name = structure.read().unwrap().name.clone();
+            let output = format!("./temp/thumbnails/{}-{}.png", stem, name);
+            match lib.render_image(&name, &options) {
+                Ok(image) => image.save_png(&output)?,
+                Err(e) => eprintln!("{}: {}", name, e),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
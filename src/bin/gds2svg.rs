@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use reda_gds::{GdsLibrary, SvgOptions};
+use clap::Parser;
+
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Input GDS file path (optionally gzip-compressed)
+    input_path: PathBuf,
+
+    /// Output SVG file path
+    output_path: PathBuf,
+
+    /// Cell to draw, the first top-level cell by default
+    #[arg(short, long)]
+    cell: Option<String>,
+
+    /// Levels of instances to expand, all by default
+    #[arg(short, long)]
+    depth: Option<usize>,
+
+    /// Image width in pixels
+    #[arg(short, long, default_value_t = 800)]
+    width: u32,
+
+    /// Leave out TEXT labels
+    #[arg(long)]
+    no_texts: bool,
+}
+
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let library = GdsLibrary::load_file(cli.input_path)?;
+
+    let cell = match cli.cell {
+        Some(cell) => cell,
+        None => top_cell(&library).ok_or("Library has no structure")?,
+    };
+
+    let mut options = SvgOptions::new().with_width(cli.width);
+    if let Some(depth) = cli.depth {
+        options = options.with_depth(depth);
+    }
+    options.texts = !cli.no_texts;
+
+    let svg = library.render_svg(&cell, &options)?;
+    std::fs::write(cli.output_path, svg)?;
+    Ok(())
+}
+
+/// First structure, in file order, that no other structure references.
+fn top_cell(library: &GdsLibrary) -> Option<String> {
+    let structures = library.ordered_structures();
+    let mut referenced = HashSet::new();
+    for structure in &structures {
+        let structure = structure.read().unwrap();
+        referenced.extend(structure.srefs().map(|sref| sref.s_name.clone()));
+        referenced.extend(structure.arefs().map(|aref| aref.s_name.clone()));
+    }
+    structures
+        .iter()
+        .map(|structure| structure.read().unwrap().name.clone())
+        .find(|name| !referenced.contains(name))
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
@@ -6,7 +6,7 @@ use clap::Parser;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Input GDS file path
+    /// Input GDS file path (optionally gzip-compressed), `-` to read from standard input
     input_path: PathBuf,
 
     /// Output text file path
@@ -15,7 +15,11 @@ struct Cli {
 
 fn main_result() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
-    let library = GdsLibrary::load_file(cli.input_path)?;
+    let library = if cli.input_path.as_os_str() == "-" {
+        GdsLibrary::read_auto(std::io::stdin().lock())?
+    } else {
+        GdsLibrary::load_file(cli.input_path)?
+    };
     library.save_text_file(cli.output_path)?;
     Ok(())
 }
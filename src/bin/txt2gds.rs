@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+use reda_gds::GdsLibrary;
+use clap::Parser;
+
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Input text file path, as written by gds2txt, `-` to read from standard input
+    input_path: PathBuf,
+
+    /// Output GDS file path, gzip-compressed if it ends with `.gz`
+    output_path: PathBuf,
+}
+
+fn main_result() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let library = if cli.input_path.as_os_str() == "-" {
+        GdsLibrary::read_text(std::io::stdin().lock())?
+    } else {
+        GdsLibrary::load_text_file(cli.input_path)?
+    };
+    library.save_gds_file(cli.output_path)?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = main_result() {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,48 @@
+use std::string::FromUtf8Error;
+use crate::GdsLibraryBuilderError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CifReadError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Parse utf8 failed '{0}'")]
+    Utf8(#[from] FromUtf8Error),
+
+    #[error("Comment is not closed")]
+    UnterminatedComment,
+
+    #[error("Invalid command '{0}'")]
+    InvalidCommand(String),
+
+    #[error("Command '{0}' is not expected here")]
+    UnexpectCommand(&'static str),
+
+    #[error("Geometry before any layer command")]
+    UndefinedLayer,
+
+    #[error("Call of undefined symbol '{0}'")]
+    UnknownSymbol(u64),
+
+    #[error("Value '{0}' does not fit in database units")]
+    OutOfRange(f64),
+
+    #[error("Build library failed for '{0}'")]
+    BuildLibrary(#[from] GdsLibraryBuilderError),
+}
+
+pub type CifReadResult<T> = Result<T, CifReadError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CifWriteError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Instance of '{0}' is magnified, which CIF can not express")]
+    UnsupportMagnification(String),
+
+    #[error("Structure name '{0}' can not be written in a CIF '9' extension")]
+    InvalidName(String),
+}
+
+pub type CifWriteResult<T> = Result<T, CifWriteError>;
@@ -0,0 +1,7 @@
+mod error;
+mod read;
+mod write;
+
+pub use error::*;
+pub use read::*;
+pub use write::*;
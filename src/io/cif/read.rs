@@ -0,0 +1,557 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    GdsBoundary, GdsCoord, GdsDateTime, GdsLibrary, GdsLibraryBuilder, GdsPath, GdsPathType, GdsStructure, GdsText, GdsTransform, GdsTransformFlag
+};
+use super::{CifReadError, CifReadResult};
+
+/// Name given to libraries read from a stream, CIF files have none
+const DEFAULT_NAME: &str = "LIB";
+
+/// Length of a CIF unit, the centimicron, in meters
+pub(super) const CIF_UNIT: f64 = 1e-8;
+
+/// Number of vertices of the polygon approximating a round flash
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// Reads a CIF file into the GDSII object model.
+///
+/// Every symbol becomes a structure, named by the `9` extension or `S<number>`
+/// otherwise, and geometry outside symbols goes to a structure named after the
+/// library. Boxes, polygons and round flashes become boundaries, wires become
+/// round-ended paths, calls become srefs and `94` / `95` labels become texts.
+/// Layers are numbered by the names given with `with_layers`, names of the form
+/// `L<layer>D<datatype>` or `L<layer>`, and in order of appearance otherwise.
+pub struct CifReader<R> {
+    reader: R,
+    name: String,
+    /// Chosen from the symbol scales when `None`
+    meters_per_dbunit: Option<f64>,
+    layers: HashMap<String, (i16, i16)>,
+}
+
+impl CifReader<File> {
+    /// The library is named after the file.
+    pub fn open<P: AsRef<Path>>(path: P) -> CifReadResult<Self> {
+        let path = path.as_ref();
+        let reader = Self::new(File::open(path)?);
+        Ok(match path.file_stem() {
+            Some(stem) => reader.with_name(stem.to_string_lossy()),
+            None => reader,
+        })
+    }
+}
+
+impl<R: Read> CifReader<R> {
+    /// Read into a library with a database unit of one nanometer,
+    /// or finer if the scale of a symbol needs it.
+    pub fn new(reader: R) -> Self {
+        Self { reader, name: DEFAULT_NAME.to_string(), meters_per_dbunit: None, layers: HashMap::new() }
+    }
+
+    /// Name of the library to read into.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Database unit of the library to read into.
+    pub fn with_meters_per_dbunit(mut self, meters_per_dbunit: f64) -> Self {
+        self.meters_per_dbunit = Some(meters_per_dbunit);
+        self
+    }
+
+    /// Layer and datatype of CIF layer names.
+    pub fn with_layers(mut self, layers: HashMap<String, (i16, i16)>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    pub fn read(&mut self) -> CifReadResult<GdsLibrary> {
+        let mut data = vec![];
+        self.reader.read_to_end(&mut data)?;
+        let text = String::from_utf8(data)?;
+        let commands = commands(&text)?;
+        let meters_per_dbunit = self.meters_per_dbunit.unwrap_or_else(|| finest_unit(&commands));
+
+        let unit = CIF_UNIT / meters_per_dbunit;
+        let mut parser = Parser {
+            unit,
+            layers: self.layers.clone(),
+            layer: None,
+            symbols: vec![],
+            current: None,
+            top: Symbol::new(0, unit),
+        };
+        for command in commands {
+            if !parser.command(&command)? {
+                break;
+            }
+        }
+        parser.build(self.name.clone(), meters_per_dbunit)
+    }
+}
+
+/// Split CIF text into commands, without comments.
+fn commands(text: &str) -> CifReadResult<Vec<String>> {
+    let mut commands = vec![];
+    let mut command = String::new();
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            ';' => commands.push(std::mem::take(&mut command)),
+            c => command.push(c),
+        }
+    }
+    if depth > 0 {
+        return Err(CifReadError::UnterminatedComment);
+    }
+    commands.push(command);
+    Ok(commands
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect())
+}
+
+/// One nanometer, or the unit of the symbol with the finest scale if smaller.
+fn finest_unit(commands: &[String]) -> f64 {
+    let mut unit: f64 = 1e-9;
+    for command in commands {
+        let Some(rest) = command.strip_prefix('D').map(str::trim_start) else {
+            continue;
+        };
+        if let Some(values) = rest.strip_prefix('S').map(integers) {
+            if let [_, a, b] = values[..] {
+                if a > 0 && b > 0 {
+                    unit = unit.min(CIF_UNIT * a as f64 / b as f64);
+                }
+            }
+        }
+    }
+    unit
+}
+
+/// Integers of a command, anything else between them is taken as blank.
+fn integers(text: &str) -> Vec<i64> {
+    let mut values = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let negative = c == '-' && chars.peek().is_some_and(|(_, d)| d.is_ascii_digit());
+        if !negative && !c.is_ascii_digit() {
+            continue;
+        }
+        let mut end = start + 1;
+        while let Some(&(i, d)) = chars.peek() {
+            if !d.is_ascii_digit() {
+                break;
+            }
+            end = i + 1;
+            chars.next();
+        }
+        if let Ok(value) = text[start..end].parse() {
+            values.push(value);
+        }
+    }
+    values
+}
+
+/// A symbol being read, its calls still referring to symbol numbers.
+struct Symbol {
+    number: u64,
+    name: Option<String>,
+    /// Database units per unit of the symbol
+    scale: f64,
+    structure: GdsStructure,
+    /// Symbol number called by each sref
+    calls: Vec<u64>,
+}
+
+impl Symbol {
+    fn new(number: u64, scale: f64) -> Self {
+        Self { number, name: None, scale, structure: GdsStructure::default(), calls: vec![] }
+    }
+
+    fn coord(&self, (x, y): (f64, f64)) -> CifReadResult<GdsCoord> {
+        Ok(GdsCoord::new(self.length(x)?, self.length(y)?))
+    }
+
+    fn length(&self, value: f64) -> CifReadResult<i32> {
+        let value = (value * self.scale).round();
+        if value < i32::MIN as f64 || value > i32::MAX as f64 {
+            return Err(CifReadError::OutOfRange(value));
+        }
+        Ok(value as i32)
+    }
+}
+
+struct Parser {
+    /// Database units per CIF unit
+    unit: f64,
+    layers: HashMap<String, (i16, i16)>,
+    layer: Option<(i16, i16)>,
+    symbols: Vec<Symbol>,
+    /// The symbol being defined, geometry goes to `top` outside of one
+    current: Option<Symbol>,
+    top: Symbol,
+}
+
+impl Parser {
+    /// Handle one command, `false` at the end command.
+    fn command(&mut self, command: &str) -> CifReadResult<bool> {
+        let invalid = || CifReadError::InvalidCommand(command.to_string());
+        let mut chars = command.chars();
+        let kind = chars.next().ok_or_else(invalid)?;
+        let rest = chars.as_str();
+
+        match kind {
+            'D' => {
+                let rest = rest.trim_start();
+                let mut chars = rest.chars();
+                let sub = chars.next().ok_or_else(invalid)?;
+                let values = integers(chars.as_str());
+                match sub {
+                    'S' => {
+                        if self.current.is_some() {
+                            return Err(CifReadError::UnexpectCommand("DS"));
+                        }
+                        let (number, a, b) = match values[..] {
+                            [number] => (number, 1, 1),
+                            [number, a, b] if b != 0 => (number, a, b),
+                            _ => return Err(invalid()),
+                        };
+                        let scale = self.unit * a as f64 / b as f64;
+                        self.current = Some(Symbol::new(number.unsigned_abs(), scale));
+                    }
+                    'F' => {
+                        let symbol = self.current.take().ok_or(CifReadError::UnexpectCommand("DF"))?;
+                        self.symbols.retain(|s| s.number != symbol.number);
+                        self.symbols.push(symbol);
+                    }
+                    'D' => {
+                        let [number] = values[..] else {
+                            return Err(invalid());
+                        };
+                        self.symbols.retain(|s| s.number < number.unsigned_abs());
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+            'L' => {
+                let name = rest.trim();
+                if name.is_empty() {
+                    return Err(invalid());
+                }
+                self.layer = Some(self.layer_number(name));
+            }
+            'B' => {
+                let values = integers(rest);
+                let (length, width, cx, cy, dx, dy) = match values[..] {
+                    [length, width, cx, cy] => (length, width, cx, cy, 1, 0),
+                    [length, width, cx, cy, dx, dy] if (dx, dy) != (0, 0) => (length, width, cx, cy, dx, dy),
+                    _ => return Err(invalid()),
+                };
+                let (ux, uy) = {
+                    let len = (dx as f64).hypot(dy as f64);
+                    (dx as f64 / len, dy as f64 / len)
+                };
+                let (hl, hw) = (length as f64 / 2.0, width as f64 / 2.0);
+                let (cx, cy) = (cx as f64, cy as f64);
+                let corners = [(-hl, -hw), (hl, -hw), (hl, hw), (-hl, hw)]
+                    .map(|(l, w)| (cx + ux * l - uy * w, cy + uy * l + ux * w));
+                self.add_boundary(&corners)?;
+            }
+            'P' => {
+                let values = integers(rest);
+                if values.len() < 6 || !values.len().is_multiple_of(2) {
+                    return Err(invalid());
+                }
+                let points: Vec<(f64, f64)> = values.chunks(2).map(|p| (p[0] as f64, p[1] as f64)).collect();
+                self.add_boundary(&points)?;
+            }
+            'W' => {
+                let values = integers(rest);
+                if values.len() < 3 || values.len().is_multiple_of(2) {
+                    return Err(invalid());
+                }
+                let width = values[0];
+                let points: Vec<(f64, f64)> = values[1..].chunks(2).map(|p| (p[0] as f64, p[1] as f64)).collect();
+                if points.iter().all(|&p| p == points[0]) {
+                    self.add_circle(width as f64, points[0])?;
+                } else {
+                    self.add_wire(width, &points)?;
+                }
+            }
+            'R' => {
+                let [diameter, cx, cy] = integers(rest)[..] else {
+                    return Err(invalid());
+                };
+                self.add_circle(diameter as f64, (cx as f64, cy as f64))?;
+            }
+            'C' => self.add_call(rest).ok_or_else(invalid)??,
+            'E' => return Ok(false),
+            c if c.is_ascii_digit() => self.extension(command)?,
+            _ => return Err(invalid()),
+        }
+        Ok(true)
+    }
+
+    /// User extensions: `9` names the symbol, `94` and `95` are labels, others are skipped.
+    fn extension(&mut self, command: &str) -> CifReadResult<()> {
+        let code: String = command.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let rest = command[code.len()..].trim();
+        let invalid = || CifReadError::InvalidCommand(command.to_string());
+        match code.as_str() {
+            "9" => {
+                if let Some(symbol) = &mut self.current {
+                    symbol.name = Some(rest.to_string()).filter(|n| !n.is_empty());
+                }
+            }
+            "94" | "95" => {
+                // The label, its box for 95, the position and an optional layer
+                let fields: Vec<&str> = rest.split_whitespace().collect();
+                let position = if code == "94" { 1 } else { 3 };
+                if fields.len() < position + 2 {
+                    return Err(invalid());
+                }
+                let coordinate = |field: &str| field.parse::<i64>().map_err(|_| invalid());
+                let (x, y) = (coordinate(fields[position])?, coordinate(fields[position + 1])?);
+                let (layer, text_type) = match fields.get(position + 2) {
+                    Some(name) => self.layer_number(name),
+                    None => self.layer.ok_or(CifReadError::UndefinedLayer)?,
+                };
+                let symbol = self.symbol();
+                let mut text = GdsText::new(layer, symbol.coord((x as f64, y as f64))?, fields[0]);
+                text.text_type = text_type;
+                symbol.structure.push(text);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn add_boundary(&mut self, points: &[(f64, f64)]) -> CifReadResult<()> {
+        let (layer, data_type) = self.layer.ok_or(CifReadError::UndefinedLayer)?;
+        let symbol = self.symbol();
+        let mut xy = points.iter().map(|&p| symbol.coord(p)).collect::<CifReadResult<Vec<_>>>()?;
+        if let (Some(&first), Some(last)) = (xy.first(), xy.last()) {
+            if (first.x, first.y) != (last.x, last.y) {
+                xy.push(first);
+            }
+        }
+        symbol.structure.push(GdsBoundary {
+            elf_flags: None,
+            plex: None,
+            layer,
+            data_type,
+            xy,
+            properties: vec![],
+            raw_records: vec![],
+        });
+        Ok(())
+    }
+
+    fn add_circle(&mut self, diameter: f64, (cx, cy): (f64, f64)) -> CifReadResult<()> {
+        let radius = diameter / 2.0;
+        let points: Vec<(f64, f64)> = (0..CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / CIRCLE_SEGMENTS as f64;
+                (cx + radius * angle.cos(), cy + radius * angle.sin())
+            })
+            .collect();
+        self.add_boundary(&points)
+    }
+
+    fn add_wire(&mut self, width: i64, points: &[(f64, f64)]) -> CifReadResult<()> {
+        let (layer, data_type) = self.layer.ok_or(CifReadError::UndefinedLayer)?;
+        let symbol = self.symbol();
+        let xy = points.iter().map(|&p| symbol.coord(p)).collect::<CifReadResult<Vec<_>>>()?;
+        let width = symbol.length(width as f64)?;
+        symbol.structure.push(GdsPath {
+            layer,
+            data_type,
+            path_type: Some(GdsPathType::RoundEnd),
+            width: Some(width),
+            xy,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// `C number` followed by translations `T x y`, mirrors `M X` / `M Y`
+    /// and rotations `R a b`, applied in order. `None` if malformed.
+    fn add_call(&mut self, text: &str) -> Option<CifReadResult<()>> {
+        enum Token {
+            Letter(char),
+            Number(i64),
+        }
+        let mut tokens = vec![];
+        let mut chars = text.char_indices().peekable();
+        while let Some(&(start, c)) = chars.peek() {
+            if c.is_ascii_uppercase() {
+                tokens.push(Token::Letter(c));
+                chars.next();
+            } else if c.is_ascii_digit() || c == '-' {
+                chars.next();
+                let mut end = start + 1;
+                while let Some(&(i, d)) = chars.peek() {
+                    if !d.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                tokens.push(Token::Number(text[start..end].parse().ok()?));
+            } else {
+                chars.next();
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        let Some(Token::Number(number)) = tokens.next() else {
+            return None;
+        };
+        let number_after = |tokens: &mut std::vec::IntoIter<Token>| match tokens.next() {
+            Some(Token::Number(n)) => Some(n as f64),
+            _ => None,
+        };
+
+        // Applied to a point p as m * p + t
+        let mut m = [[1.0, 0.0], [0.0, 1.0]];
+        let mut t = (0.0, 0.0);
+        while let Some(token) = tokens.next() {
+            let op = match token {
+                Token::Letter('T') => {
+                    t.0 += number_after(&mut tokens)?;
+                    t.1 += number_after(&mut tokens)?;
+                    continue;
+                }
+                Token::Letter('M') => match tokens.next() {
+                    Some(Token::Letter('X')) => [[-1.0, 0.0], [0.0, 1.0]],
+                    Some(Token::Letter('Y')) => [[1.0, 0.0], [0.0, -1.0]],
+                    _ => return None,
+                },
+                Token::Letter('R') => {
+                    let (a, b) = (number_after(&mut tokens)?, number_after(&mut tokens)?);
+                    let len = a.hypot(b);
+                    if len == 0.0 {
+                        return None;
+                    }
+                    let (cos, sin) = (a / len, b / len);
+                    [[cos, -sin], [sin, cos]]
+                }
+                _ => return None,
+            };
+            m = [
+                [op[0][0] * m[0][0] + op[0][1] * m[1][0], op[0][0] * m[0][1] + op[0][1] * m[1][1]],
+                [op[1][0] * m[0][0] + op[1][1] * m[1][0], op[1][0] * m[0][1] + op[1][1] * m[1][1]],
+            ];
+            t = (op[0][0] * t.0 + op[0][1] * t.1, op[1][0] * t.0 + op[1][1] * t.1);
+        }
+
+        // A reflection about the X-axis, if any, comes before the rotation
+        let reflect = m[0][0] * m[1][1] - m[0][1] * m[1][0] < 0.0;
+        let mut angle = m[1][0].atan2(m[0][0]).to_degrees().rem_euclid(360.0);
+        if (angle - angle.round()).abs() < 1e-9 {
+            angle = angle.round() % 360.0;
+        }
+        let transform = (reflect || angle != 0.0).then(|| GdsTransform {
+            flag: GdsTransformFlag::new(reflect, false, false),
+            magnification: None,
+            angle: (angle != 0.0).then_some(angle),
+        });
+
+        let symbol = self.symbol();
+        let position = match symbol.coord(t) {
+            Ok(position) => position,
+            Err(e) => return Some(Err(e)),
+        };
+        symbol.structure.add_sref(String::new(), position, transform);
+        symbol.calls.push(number.unsigned_abs());
+        Some(Ok(()))
+    }
+
+    fn layer_number(&mut self, name: &str) -> (i16, i16) {
+        if let Some(&layer) = self.layers.get(name) {
+            return layer;
+        }
+        let layer = parse_layer_name(name).unwrap_or_else(|| {
+            let next = self.layers.values().map(|&(layer, _)| layer).max().unwrap_or(0);
+            (next.saturating_add(1), 0)
+        });
+        self.layers.insert(name.to_string(), layer);
+        layer
+    }
+
+    fn symbol(&mut self) -> &mut Symbol {
+        self.current.as_mut().unwrap_or(&mut self.top)
+    }
+
+    fn build(self, name: String, meters_per_dbunit: f64) -> CifReadResult<GdsLibrary> {
+        let mut names = HashMap::new();
+        let mut taken = HashSet::new();
+        for symbol in &self.symbols {
+            let mut symbol_name = symbol.name.clone().unwrap_or_else(|| format!("S{}", symbol.number));
+            if !taken.insert(symbol_name.clone()) {
+                symbol_name = format!("{}_{}", symbol_name, symbol.number);
+                taken.insert(symbol_name.clone());
+            }
+            names.insert(symbol.number, symbol_name);
+        }
+
+        let mut symbols: Vec<(String, Symbol)> = self.symbols
+            .into_iter()
+            .map(|symbol| (names[&symbol.number].clone(), symbol))
+            .collect();
+        if self.top.structure.element_count() != 0 {
+            let top_name = if taken.contains(&name) { format!("{}_top", name) } else { name.clone() };
+            symbols.push((top_name, self.top));
+        }
+
+        let mut structures = HashMap::new();
+        let mut structure_order = vec![];
+        for (structure_name, symbol) in symbols {
+            let mut structure = symbol.structure;
+            structure.name = structure_name;
+            for (sref, number) in structure.srefs_mut().zip(symbol.calls) {
+                sref.s_name = names.get(&number).cloned().ok_or(CifReadError::UnknownSymbol(number))?;
+            }
+            structure_order.push(structure.name.clone());
+            structures.insert(structure.name.clone(), Arc::new(RwLock::new(structure)));
+        }
+
+        let now = GdsDateTime::now();
+        let library = GdsLibraryBuilder::default()
+            .version(600)
+            .name(name)
+            .create_date(now.clone())
+            .modify_date(now)
+            .usrunits_per_dbunit(meters_per_dbunit / 1e-6)
+            .meters_per_dbunit(meters_per_dbunit)
+            .structures(structures)
+            .structure_order(structure_order)
+            .build()?;
+        Ok(library)
+    }
+}
+
+/// Layer and datatype of names written by `CifWriter` without a layer map.
+fn parse_layer_name(name: &str) -> Option<(i16, i16)> {
+    let rest = name.strip_prefix('L')?;
+    let (layer, data_type) = match rest.split_once('D') {
+        Some((layer, data_type)) => (layer, data_type),
+        None => (rest, "0"),
+    };
+    let all_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if !all_digits(layer) || !all_digits(data_type) {
+        return None;
+    }
+    Some((layer.parse().ok()?, data_type.parse().ok()?))
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::{GdsCoord, GdsElementRef, GdsLibrary, GdsPathType, GdsStructure, GdsTransform};
+use super::read::CIF_UNIT;
+use super::{CifWriteError, CifWriteResult};
+
+/// Writes a library as CIF.
+///
+/// Every structure becomes a symbol numbered in write order and named by the `9`
+/// extension, with a scale that makes its coordinates the database units of the
+/// library. Names holding `;` or parentheses can not be written there and are
+/// refused. Elements are written in the order of the structure. Axis-aligned rectangles become boxes and other boundaries polygons,
+/// round-ended paths become wires and other paths the polygon of their outline.
+/// Srefs become calls, arefs one call per instance, and texts `94` labels with
+/// blanks, `;` and parentheses replaced by `_`. Nodes, properties and instances of structures missing
+/// from the library are left out. Layers are named by the map given with
+/// `with_layer_names`, `L<layer>D<datatype>` otherwise.
+pub struct CifWriter<W> {
+    writer: W,
+    layer_names: HashMap<(i16, i16), String>,
+}
+
+impl CifWriter<BufWriter<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> CifWriteResult<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> CifWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, layer_names: HashMap::new() }
+    }
+
+    /// CIF layer names by (layer, datatype), texts use their (layer, texttype).
+    pub fn with_layer_names(mut self, layer_names: HashMap<(i16, i16), String>) -> Self {
+        self.layer_names = layer_names;
+        self
+    }
+
+    pub fn write(&mut self, library: &GdsLibrary) -> CifWriteResult<()> {
+        let structures = library.ordered_structures();
+        let mut numbers = HashMap::new();
+        for structure in &structures {
+            let number = numbers.len() + 1;
+            numbers.insert(structure.read().unwrap().name.clone(), number);
+        }
+        for structure in &structures {
+            let name = &structure.read().unwrap().name;
+            if name.contains([';', '(', ')']) {
+                return Err(CifWriteError::InvalidName(name.clone()));
+            }
+        }
+        let (a, b) = scale(library.meters_per_dbunit / CIF_UNIT);
+
+        writeln!(self.writer, "({});", comment(&library.name))?;
+        for structure in &structures {
+            let structure = structure.read().unwrap();
+            writeln!(self.writer, "DS {} {} {};", numbers[&structure.name], a, b)?;
+            writeln!(self.writer, "9 {};", structure.name)?;
+            self.write_structure(&structure, &numbers)?;
+            writeln!(self.writer, "DF;")?;
+        }
+        writeln!(self.writer, "E")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_structure(&mut self, structure: &GdsStructure, numbers: &HashMap<String, usize>) -> CifWriteResult<()> {
+        let mut layer = None;
+        for element in structure.elements() {
+            match element {
+                GdsElementRef::Boundary(boundary) => {
+                    self.set_layer(&mut layer, boundary.layer, boundary.data_type)?;
+                    self.write_polygon(&boundary.xy)?;
+                }
+                GdsElementRef::Path(path) => {
+                    self.set_layer(&mut layer, path.layer, path.data_type)?;
+                    if path.path_type() == GdsPathType::RoundEnd && path.xy.len() > 1 {
+                        write!(self.writer, "W {}", path.width().abs())?;
+                        for c in &path.xy {
+                            write!(self.writer, " {} {}", c.x, c.y)?;
+                        }
+                        writeln!(self.writer, ";")?;
+                    } else {
+                        let outline = path.outline();
+                        if !outline.is_empty() {
+                            self.write_polygon(&outline)?;
+                        }
+                    }
+                }
+                GdsElementRef::Box(boxx) => {
+                    self.set_layer(&mut layer, boxx.layer, boxx.box_type)?;
+                    self.write_polygon(&boxx.xy)?;
+                }
+                GdsElementRef::Text(text) => {
+                    let label: String = text.string
+                        .chars()
+                        .map(|c| if c.is_whitespace() || matches!(c, ';' | '(' | ')') { '_' } else { c })
+                        .collect();
+                    let name = self.layer_name(text.layer, text.text_type);
+                    writeln!(self.writer, "94 {} {} {} {};", label, text.position.x, text.position.y, name)?;
+                }
+                GdsElementRef::Sref(sref) => {
+                    if let Some(number) = numbers.get(&sref.s_name) {
+                        self.write_call(*number, &sref.s_name, sref.position, sref.transform)?;
+                    }
+                }
+                GdsElementRef::Aref(aref) => {
+                    if let Some(number) = numbers.get(&aref.s_name) {
+                        for position in aref.positions() {
+                            self.write_call(*number, &aref.s_name, position, aref.transform)?;
+                        }
+                    }
+                }
+                GdsElementRef::Node(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// A box when the points are an axis-aligned rectangle with a center on the grid.
+    fn write_polygon(&mut self, xy: &[GdsCoord]) -> CifWriteResult<()> {
+        let mut points: Vec<(i64, i64)> = xy.iter().map(|c| (c.x as i64, c.y as i64)).collect();
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 3 {
+            return Ok(());
+        }
+        if let Some((min, max)) = rectangle(&points) {
+            let (sum_x, sum_y) = (min.0 + max.0, min.1 + max.1);
+            if sum_x % 2 == 0 && sum_y % 2 == 0 {
+                writeln!(self.writer, "B {} {} {} {};", max.0 - min.0, max.1 - min.1, sum_x / 2, sum_y / 2)?;
+                return Ok(());
+            }
+        }
+        write!(self.writer, "P")?;
+        for (x, y) in points {
+            write!(self.writer, " {} {}", x, y)?;
+        }
+        writeln!(self.writer, ";")?;
+        Ok(())
+    }
+
+    /// A call reflecting about the X-axis, rotating and then moving the symbol.
+    fn write_call(&mut self, number: usize, name: &str, position: GdsCoord, transform: Option<GdsTransform>) -> CifWriteResult<()> {
+        write!(self.writer, "C {}", number)?;
+        if let Some(transform) = transform {
+            if transform.magnification() != 1.0 {
+                return Err(CifWriteError::UnsupportMagnification(name.to_string()));
+            }
+            if transform.flag.reflect {
+                write!(self.writer, " M Y")?;
+            }
+            let angle = transform.angle().rem_euclid(360.0);
+            if angle != 0.0 {
+                let (a, b) = match angle {
+                    90.0 => (0, 1),
+                    180.0 => (-1, 0),
+                    270.0 => (0, -1),
+                    angle => {
+                        let (sin, cos) = angle.to_radians().sin_cos();
+                        ((cos * 1e6).round() as i64, (sin * 1e6).round() as i64)
+                    }
+                };
+                write!(self.writer, " R {} {}", a, b)?;
+            }
+        }
+        writeln!(self.writer, " T {} {};", position.x, position.y)?;
+        Ok(())
+    }
+
+    fn set_layer(&mut self, current: &mut Option<(i16, i16)>, layer: i16, data_type: i16) -> CifWriteResult<()> {
+        if *current != Some((layer, data_type)) {
+            writeln!(self.writer, "L {};", self.layer_name(layer, data_type))?;
+            *current = Some((layer, data_type));
+        }
+        Ok(())
+    }
+
+    fn layer_name(&self, layer: i16, data_type: i16) -> String {
+        match self.layer_names.get(&(layer, data_type)) {
+            Some(name) => name.clone(),
+            None => format!("L{}D{}", layer, data_type),
+        }
+    }
+}
+
+/// Lower left and upper right corner of points forming an axis-aligned rectangle.
+fn rectangle(points: &[(i64, i64)]) -> Option<((i64, i64), (i64, i64))> {
+    if points.len() != 4 {
+        return None;
+    }
+    let axis_aligned = (0..4).all(|i| {
+        let (a, b) = (points[i], points[(i + 1) % 4]);
+        (a.0 == b.0) != (a.1 == b.1)
+    });
+    if !axis_aligned {
+        return None;
+    }
+    let min = (points.iter().map(|p| p.0).min()?, points.iter().map(|p| p.1).min()?);
+    let max = (points.iter().map(|p| p.0).max()?, points.iter().map(|p| p.1).max()?);
+    Some((min, max))
+}
+
+/// `value` as a fraction `a / b` with a power of ten below it, as small as it takes.
+fn scale(value: f64) -> (i64, i64) {
+    let mut b = 1i64;
+    while b < 1_000_000_000 {
+        let a = value * b as f64;
+        if (a - a.round()).abs() < 1e-6 * a.abs().max(1.0) {
+            let a = a.round() as i64;
+            let divisor = gcd(a.abs(), b);
+            return (a / divisor, b / divisor);
+        }
+        b *= 10;
+    }
+    ((value * b as f64).round() as i64, b)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.max(1) } else { gcd(b, a % b) }
+}
+
+/// Parentheses must balance inside a comment.
+fn comment(text: &str) -> String {
+    text.chars().map(|c| if c == '(' || c == ')' { '_' } else { c }).collect()
+}
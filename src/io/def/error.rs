@@ -0,0 +1,43 @@
+use std::string::FromUtf8Error;
+use crate::{GdsLibraryBuilderError, GdsRenderError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DefReadError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Parse utf8 failed '{0}'")]
+    Utf8(#[from] FromUtf8Error),
+
+    #[error("Statement is not terminated by ';'")]
+    UnterminatedStatement,
+
+    #[error("Expect '{expect}' but found '{found}'")]
+    UnexpectToken { expect: &'static str, found: String },
+
+    #[error("Invalid number '{0}'")]
+    InvalidNumber(String),
+
+    #[error("Invalid orientation '{0}'")]
+    InvalidOrientation(String),
+
+    #[error("Value '{0}' does not fit in database units")]
+    OutOfRange(f64),
+
+    #[error("Wiring goes through via '{0}' whose layers are unknown")]
+    UnknownVia(String),
+
+    #[error("Via '{0}' does not connect to layer '{1}'")]
+    ViaLayer(String, String),
+
+    #[error("Design '{0}' has the name of a structure of the library")]
+    DuplicateStructure(String),
+
+    #[error("Expand hierarchy failed '{0}'")]
+    Hierarchy(#[from] GdsRenderError),
+
+    #[error("Build library failed for '{0}'")]
+    BuildLibrary(#[from] GdsLibraryBuilderError),
+}
+
+pub type DefReadResult<T> = Result<T, DefReadError>;
@@ -0,0 +1,5 @@
+mod error;
+mod read;
+
+pub use error::*;
+pub use read::*;
@@ -0,0 +1,829 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use crate::models::{footprint, Affine, Bounds, Flattener, Point};
+use crate::{
+    GdsBoundary, GdsCoord, GdsDateTime, GdsLibrary, GdsLibraryBuilder, GdsPath, GdsPathType, GdsSref, GdsStructure, GdsText, GdsTransform, GdsTransformFlag
+};
+use super::{DefReadError, DefReadResult};
+
+/// Name of the design when the file has no DESIGN statement
+const DEFAULT_NAME: &str = "LIB";
+
+/// DEF units per micron when the file has no UNITS statement
+const DEFAULT_UNITS: f64 = 1000.0;
+
+/// Sections skipped as a whole, up to their `END` statement
+const SKIPPED_SECTIONS: [&str; 10] = [
+    "PROPERTYDEFINITIONS", "PINPROPERTIES", "BLOCKAGES", "REGIONS", "GROUPS",
+    "NONDEFAULTRULES", "SCANCHAINS", "FILLS", "SLOTS", "STYLES",
+];
+
+/// Orientation of a component, pin or via.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefOrient {
+    #[default]
+    N,
+    S,
+    E,
+    W,
+    FN,
+    FS,
+    FE,
+    FW,
+}
+
+impl DefOrient {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "N" => Some(Self::N),
+            "S" => Some(Self::S),
+            "E" => Some(Self::E),
+            "W" => Some(Self::W),
+            "FN" => Some(Self::FN),
+            "FS" => Some(Self::FS),
+            "FE" => Some(Self::FE),
+            "FW" => Some(Self::FW),
+            _ => None,
+        }
+    }
+
+    /// Transform of an instance, `None` for `N`. `W` turns counterclockwise by 90°,
+    /// `FN` mirrors about the Y-axis, which is a reflection about the X-axis and a half turn.
+    pub fn transform(self) -> Option<GdsTransform> {
+        let (reflect, angle) = match self {
+            Self::N => return None,
+            Self::W => (false, 90.0),
+            Self::S => (false, 180.0),
+            Self::E => (false, 270.0),
+            Self::FS => (true, 0.0),
+            Self::FW => (true, 90.0),
+            Self::FN => (true, 180.0),
+            Self::FE => (true, 270.0),
+        };
+        Some(GdsTransform {
+            flag: GdsTransformFlag::new(reflect, false, false),
+            magnification: None,
+            angle: if angle == 0.0 { None } else { Some(angle) },
+        })
+    }
+}
+
+/// Layer, datatype and wire width of a DEF routing layer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DefLayer {
+    pub layer: i16,
+    pub data_type: i16,
+    /// Width of regular wiring in microns, special wiring gives its own
+    pub width: f64,
+}
+
+impl DefLayer {
+    pub fn new(layer: i16, data_type: i16, width: f64) -> Self {
+        Self { layer, data_type, width }
+    }
+}
+
+/// How the names of components are kept in the structure read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefInstanceNames {
+    Omit,
+    /// A property of the sref with this attribute number
+    Property(i16),
+    /// A text at the placement point on (layer, texttype)
+    Text(i16, i16),
+}
+
+impl Default for DefInstanceNames {
+    fn default() -> Self {
+        Self::Property(1)
+    }
+}
+
+/// Reads a placed and routed DEF design into a structure named after it.
+///
+/// Placed components become srefs of their model, turned as their orientation asks
+/// and moved so that the bounding box of the model, or of its shapes on the layer
+/// given with `with_boundary_layer`, has its lower left corner on the placement
+/// point. Models missing from the library are placed with their origin there.
+/// Wiring of special and regular nets becomes paths on the layers given with
+/// `with_layers`, regular wires extended by half their width, and RECT and POLYGON
+/// shapes become boundaries. A wire continuing past a via goes on on the other layer
+/// of the via, at the width of that layer. Vias of the VIAS section connect the layers
+/// of their `+ LAYERS`, or else of their first and last shape, other vias need
+/// `with_via_layers`. Vias become srefs, those defined by shapes in the VIAS section
+/// become structures.
+/// Placed pins become boundaries with a text of their name. Wiring on unmapped
+/// layers, blockages, rows, tracks and the like are left out.
+pub struct DefReader<R> {
+    reader: R,
+    layers: HashMap<String, DefLayer>,
+    boundary_layer: Option<(i16, i16)>,
+    die_area_layer: Option<(i16, i16)>,
+    instance_names: DefInstanceNames,
+    via_layers: HashMap<String, (String, String)>,
+}
+
+impl DefReader<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> DefReadResult<Self> {
+        Ok(Self::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> DefReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            layers: HashMap::new(),
+            boundary_layer: None,
+            die_area_layer: None,
+            instance_names: DefInstanceNames::default(),
+            via_layers: HashMap::new(),
+        }
+    }
+
+    /// Where the wiring on DEF layers goes, by layer name.
+    pub fn with_layers(mut self, layers: HashMap<String, DefLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    pub fn with_layer(mut self, name: impl Into<String>, layer: DefLayer) -> Self {
+        self.layers.insert(name.into(), layer);
+        self
+    }
+
+    /// Layer of the shapes whose bounding box is placed, e.g. a placement boundary.
+    pub fn with_boundary_layer(mut self, layer: i16, data_type: i16) -> Self {
+        self.boundary_layer = Some((layer, data_type));
+        self
+    }
+
+    /// Draw the DIEAREA as a boundary on (layer, datatype).
+    pub fn with_die_area_layer(mut self, layer: i16, data_type: i16) -> Self {
+        self.die_area_layer = Some((layer, data_type));
+        self
+    }
+
+    pub fn with_instance_names(mut self, instance_names: DefInstanceNames) -> Self {
+        self.instance_names = instance_names;
+        self
+    }
+
+    /// Bottom and top layer of a via, by DEF layer name. Needed for the vias wiring
+    /// goes through that the VIAS section does not define, and wins over it.
+    pub fn with_via_layers(mut self, via: impl Into<String>, bottom: impl Into<String>, top: impl Into<String>) -> Self {
+        self.via_layers.insert(via.into(), (bottom.into(), top.into()));
+        self
+    }
+
+    /// Read into a new library with the database unit of the DEF file,
+    /// holding the design and the vias it defines.
+    pub fn read(&mut self) -> DefReadResult<GdsLibrary> {
+        let design = self.parse(None)?;
+        let mut structures = HashMap::new();
+        let mut structure_order = vec![];
+        for structure in design.vias.into_iter().chain([design.top]) {
+            structure_order.push(structure.name.clone());
+            structures.insert(structure.name.clone(), Arc::new(RwLock::new(structure)));
+        }
+
+        let now = GdsDateTime::now();
+        let library = GdsLibraryBuilder::default()
+            .version(600)
+            .name(design.name)
+            .create_date(now.clone())
+            .modify_date(now)
+            .usrunits_per_dbunit(design.meters_per_dbunit / 1e-6)
+            .meters_per_dbunit(design.meters_per_dbunit)
+            .structures(structures)
+            .structure_order(structure_order)
+            .build()?;
+        Ok(library)
+    }
+
+    /// Read into a library holding the cells the design places, in its database unit.
+    /// Vias the library already defines are kept. Returns the name of the structure read.
+    pub fn read_into(&mut self, library: &mut GdsLibrary) -> DefReadResult<String> {
+        let design = self.parse(Some(library))?;
+        if library.structures.contains_key(&design.top.name) {
+            return Err(DefReadError::DuplicateStructure(design.top.name));
+        }
+        for via in design.vias {
+            if !library.structures.contains_key(&via.name) {
+                library.add_structure(via);
+            }
+        }
+        let name = design.top.name.clone();
+        library.add_structure(design.top);
+        Ok(name)
+    }
+
+    fn parse(&mut self, library: Option<&GdsLibrary>) -> DefReadResult<Design> {
+        let mut data = vec![];
+        self.reader.read_to_end(&mut data)?;
+        let text = String::from_utf8(data)?;
+        let mut tokens = Tokens::new(&text);
+
+        let mut parser = Parser {
+            layers: &self.layers,
+            boundary_layer: self.boundary_layer,
+            die_area_layer: self.die_area_layer,
+            instance_names: self.instance_names,
+            via_layers: self.via_layers.clone(),
+            library_unit: library.map(|library| library.meters_per_dbunit),
+            flattener: library.map(Flattener::new),
+            meters_per_dbunit: 0.0,
+            scale: 0.0,
+            top: GdsStructure::new(DEFAULT_NAME),
+            vias: vec![],
+            footprints: HashMap::new(),
+        };
+        parser.set_units(DEFAULT_UNITS);
+        parser.parse(&mut tokens)?;
+
+        Ok(Design {
+            name: parser.top.name.clone(),
+            meters_per_dbunit: parser.meters_per_dbunit,
+            top: parser.top,
+            vias: parser.vias,
+        })
+    }
+}
+
+/// What a DEF file is read into.
+struct Design {
+    name: String,
+    meters_per_dbunit: f64,
+    top: GdsStructure,
+    vias: Vec<GdsStructure>,
+}
+
+/// Whitespace separated words of DEF text, or of one statement of it.
+/// Quoted strings are one token without their quotes, `;` is always a token of its own.
+struct Tokens {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Tokens {
+    fn new(text: &str) -> Self {
+        let mut tokens = vec![];
+        let mut token = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '#' if token.is_empty() => {
+                    chars.by_ref().find(|&c| c == '\n');
+                }
+                '"' if token.is_empty() => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => token.extend(chars.next()),
+                            c => token.push(c),
+                        }
+                    }
+                    tokens.push(std::mem::take(&mut token));
+                }
+                ';' => {
+                    if !token.is_empty() {
+                        tokens.push(std::mem::take(&mut token));
+                    }
+                    tokens.push(";".to_string());
+                }
+                c if c.is_whitespace() => {
+                    if !token.is_empty() {
+                        tokens.push(std::mem::take(&mut token));
+                    }
+                }
+                c => token.push(c),
+            }
+        }
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&str> {
+        self.tokens.get(self.pos + offset).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get_mut(self.pos).map(std::mem::take);
+        self.pos += 1;
+        token
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.pos += count;
+    }
+
+    fn require(&mut self, expect: &'static str) -> DefReadResult<String> {
+        self.next().ok_or(DefReadError::UnexpectToken { expect, found: String::new() })
+    }
+
+    fn expect(&mut self, expect: &'static str) -> DefReadResult<()> {
+        let found = self.require(expect)?;
+        if found != expect {
+            return Err(DefReadError::UnexpectToken { expect, found });
+        }
+        Ok(())
+    }
+
+    fn number(&mut self) -> DefReadResult<f64> {
+        let token = self.require("number")?;
+        token.parse().map_err(|_| DefReadError::InvalidNumber(token))
+    }
+
+    fn orient(&mut self) -> DefReadResult<DefOrient> {
+        let token = self.require("orientation")?;
+        DefOrient::parse(&token).ok_or(DefReadError::InvalidOrientation(token))
+    }
+
+    /// Tokens up to the next `;`, which is consumed.
+    fn statement(&mut self) -> DefReadResult<Tokens> {
+        let end = self.tokens[self.pos.min(self.tokens.len())..]
+            .iter()
+            .position(|t| t == ";")
+            .ok_or(DefReadError::UnterminatedStatement)?;
+        let tokens = self.tokens[self.pos..self.pos + end].to_vec();
+        self.pos += end + 1;
+        Ok(Tokens { tokens, pos: 0 })
+    }
+
+    /// `( x y [ext] )`, `*` repeating the coordinate of `last`.
+    fn point(&mut self, last: Option<Point>) -> DefReadResult<(Point, Option<f64>)> {
+        self.expect("(")?;
+        let mut coordinate = |last: Option<f64>| match (self.peek(), last) {
+            (Some("*"), Some(last)) => {
+                self.skip(1);
+                Ok(last)
+            }
+            _ => self.number(),
+        };
+        let x = coordinate(last.map(|p| p.0))?;
+        let y = coordinate(last.map(|p| p.1))?;
+        let extension = if self.peek() == Some(")") { None } else { Some(self.number()?) };
+        self.expect(")")?;
+        Ok(((x, y), extension))
+    }
+
+    /// Points as long as they follow each other.
+    fn points(&mut self) -> DefReadResult<Vec<Point>> {
+        let mut points: Vec<Point> = vec![];
+        while self.peek() == Some("(") {
+            let (point, _) = self.point(points.last().copied())?;
+            points.push(point);
+        }
+        Ok(points)
+    }
+
+    /// Skip options such as `MASK n` or `SPACING d` up to the first point.
+    fn skip_to_points(&mut self) {
+        while self.peek().is_some_and(|t| t != "(") {
+            self.skip(1);
+        }
+    }
+}
+
+/// Shapes of a pin by DEF layer name, relative to its placement.
+#[derive(Default)]
+struct Port {
+    shapes: Vec<(String, Vec<Point>)>,
+    placement: Option<(Point, DefOrient)>,
+}
+
+struct Parser<'a> {
+    layers: &'a HashMap<String, DefLayer>,
+    boundary_layer: Option<(i16, i16)>,
+    die_area_layer: Option<(i16, i16)>,
+    instance_names: DefInstanceNames,
+    /// Bottom and top layer name of each via
+    via_layers: HashMap<String, (String, String)>,
+    /// Database unit of the library read into, that of the file if `None`
+    library_unit: Option<f64>,
+    flattener: Option<Flattener<'a>>,
+    meters_per_dbunit: f64,
+    /// Database units per DEF unit
+    scale: f64,
+    top: GdsStructure,
+    vias: Vec<GdsStructure>,
+    /// Bounds of placed models, `None` if empty or missing from the library
+    footprints: HashMap<String, Option<Bounds>>,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self, tokens: &mut Tokens) -> DefReadResult<()> {
+        while let Some(keyword) = tokens.next() {
+            match keyword.as_str() {
+                "DESIGN" => {
+                    let mut statement = tokens.statement()?;
+                    self.top.name = statement.require("design name")?;
+                }
+                "UNITS" => {
+                    let mut statement = tokens.statement()?;
+                    statement.expect("DISTANCE")?;
+                    statement.expect("MICRONS")?;
+                    let units = statement.number()?;
+                    self.set_units(units);
+                }
+                "DIEAREA" => {
+                    let points = tokens.statement()?.points()?;
+                    if let Some(layer) = self.die_area_layer {
+                        let points: Vec<Point> = points.iter().map(|&p| self.dbu(p)).collect();
+                        self.add_polygon(layer, &points)?;
+                    }
+                }
+                "COMPONENTS" | "PINS" | "VIAS" | "SPECIALNETS" | "NETS" => {
+                    tokens.statement()?;
+                    while tokens.peek() == Some("-") {
+                        let mut item = tokens.statement()?;
+                        item.skip(1);
+                        match keyword.as_str() {
+                            "COMPONENTS" => self.component(&mut item)?,
+                            "PINS" => self.pin(&mut item)?,
+                            "VIAS" => self.via(&mut item)?,
+                            _ => self.net(&mut item, keyword == "SPECIALNETS")?,
+                        }
+                    }
+                    tokens.expect("END")?;
+                    tokens.skip(1);
+                }
+                "BEGINEXT" => while tokens.next().is_some_and(|t| t != "ENDEXT") {},
+                "END" => {
+                    if tokens.next().as_deref() == Some("DESIGN") {
+                        break;
+                    }
+                }
+                section if SKIPPED_SECTIONS.contains(&section) => {
+                    while let Some(token) = tokens.next() {
+                        if token == "END" && tokens.peek() == Some(section) {
+                            tokens.skip(1);
+                            break;
+                        }
+                    }
+                }
+                _ => {
+                    tokens.statement()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_units(&mut self, units_per_micron: f64) {
+        let def_unit = 1e-6 / units_per_micron;
+        self.meters_per_dbunit = self.library_unit.unwrap_or(def_unit);
+        self.scale = def_unit / self.meters_per_dbunit;
+    }
+
+    /// `- name model + PLACED ( x y ) orient ...`, unplaced components are left out.
+    fn component(&mut self, item: &mut Tokens) -> DefReadResult<()> {
+        let name = item.require("component name")?;
+        let model = item.require("model name")?;
+        let mut placement = None;
+        while let Some(token) = item.next() {
+            if token == "+" && matches!(item.peek(), Some("PLACED" | "FIXED" | "COVER")) {
+                item.skip(1);
+                let (point, _) = item.point(None)?;
+                placement = Some((point, item.orient()?));
+            }
+        }
+        let Some((point, orient)) = placement else {
+            return Ok(());
+        };
+
+        let transform = orient.transform();
+        let (x, y) = self.dbu(point);
+        let (dx, dy) = match self.footprint(&model)? {
+            Some(bounds) => {
+                let turn = Affine::placement(GdsCoord::new(0, 0), transform);
+                let turned = Bounds::of(&bounds.corners().map(|(x, y)| turn.apply(x, y))).unwrap();
+                (turned.min_x, turned.min_y)
+            }
+            None => (0.0, 0.0),
+        };
+        let mut sref = GdsSref::new(model, self.coord((x - dx, y - dy))?, transform);
+        match self.instance_names {
+            DefInstanceNames::Omit => {}
+            DefInstanceNames::Property(attr) => sref.set_property(attr, name),
+            DefInstanceNames::Text(layer, text_type) => {
+                let mut text = GdsText::new(layer, self.coord((x, y))?, name);
+                text.text_type = text_type;
+                self.top.push(text);
+            }
+        }
+        self.top.push(sref);
+        Ok(())
+    }
+
+    fn footprint(&mut self, model: &str) -> DefReadResult<Option<Bounds>> {
+        if let Some(bounds) = self.footprints.get(model) {
+            return Ok(*bounds);
+        }
+        let bounds = match &mut self.flattener {
+            Some(flattener) if flattener.contains(model) => {
+                footprint(&flattener.flatten(model, usize::MAX)?, self.boundary_layer)
+            }
+            _ => None,
+        };
+        self.footprints.insert(model.to_string(), bounds);
+        Ok(bounds)
+    }
+
+    /// `- name + NET net + LAYER layer ( x y ) ( x y ) + PLACED ( x y ) orient ...`,
+    /// with one placement for each `+ PORT`.
+    fn pin(&mut self, item: &mut Tokens) -> DefReadResult<()> {
+        let name = item.require("pin name")?;
+        let mut ports = vec![Port::default()];
+        while let Some(token) = item.next() {
+            if token != "+" {
+                continue;
+            }
+            match item.next().as_deref() {
+                Some("PORT") if ports.last().is_some_and(|port| !port.shapes.is_empty() || port.placement.is_some()) => {
+                    ports.push(Port::default());
+                }
+                Some("LAYER") => {
+                    let layer = item.require("layer name")?;
+                    item.skip_to_points();
+                    let (a, _) = item.point(None)?;
+                    let (b, _) = item.point(None)?;
+                    ports.last_mut().unwrap().shapes.push((layer, vec![a, (a.0, b.1), b, (b.0, a.1)]));
+                }
+                Some("POLYGON") => {
+                    let layer = item.require("layer name")?;
+                    item.skip_to_points();
+                    let points = item.points()?;
+                    ports.last_mut().unwrap().shapes.push((layer, points));
+                }
+                Some("PLACED" | "FIXED" | "COVER") => {
+                    let (point, _) = item.point(None)?;
+                    ports.last_mut().unwrap().placement = Some((point, item.orient()?));
+                }
+                _ => {}
+            }
+        }
+
+        for Port { shapes, placement } in ports {
+            let Some((point, orient)) = placement else {
+                continue;
+            };
+            let at = self.coord(self.dbu(point))?;
+            let placement = Affine::placement(at, orient.transform());
+            let mut label = None;
+            for (layer, points) in shapes {
+                let Some(&layer) = self.layers.get(&layer) else {
+                    continue;
+                };
+                let points: Vec<Point> = points.iter().map(|&p| self.dbu(p)).map(|(x, y)| placement.apply(x, y)).collect();
+                self.add_polygon((layer.layer, layer.data_type), &points)?;
+                label.get_or_insert((layer.layer, layer.data_type));
+            }
+            if let Some((layer, data_type)) = label {
+                let mut text = GdsText::new(layer, at, name.clone());
+                text.text_type = data_type;
+                self.top.push(text);
+            }
+        }
+        Ok(())
+    }
+
+    /// `- name + RECT layer ( x y ) ( x y ) + POLYGON layer ( x y ) ...`,
+    /// vias generated from a rule `+ VIARULE rule ... + LAYERS bottom cut top`
+    /// are left to the library.
+    fn via(&mut self, item: &mut Tokens) -> DefReadResult<()> {
+        let name = item.require("via name")?;
+        let top = std::mem::replace(&mut self.top, GdsStructure::new(name.clone()));
+        let mut shape_layers: Vec<String> = vec![];
+        let mut rule_layers = None;
+        while let Some(token) = item.next() {
+            if token != "+" {
+                continue;
+            }
+            match item.next().as_deref() {
+                Some("LAYERS") => {
+                    let bottom = item.require("layer name")?;
+                    item.skip(1);
+                    rule_layers = Some((bottom, item.require("layer name")?));
+                }
+                Some("RECT" | "POLYGON") => {
+                    let layer = item.require("layer name")?;
+                    item.skip_to_points();
+                    let points = item.points()?;
+                    if let Some(&layer) = self.layers.get(&layer) {
+                        let points: Vec<Point> = points.iter().map(|&p| self.dbu(p)).collect();
+                        self.add_polygon((layer.layer, layer.data_type), &points)?;
+                    }
+                    if !shape_layers.contains(&layer) {
+                        shape_layers.push(layer);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Shapes are listed from the bottom layer of the via up to its top layer
+        let layers = rule_layers.or_else(|| Some((shape_layers.first()?.clone(), shape_layers.last()?.clone())));
+        if let Some(layers) = layers {
+            self.via_layers.entry(name).or_insert(layers);
+        }
+        let via = std::mem::replace(&mut self.top, top);
+        if via.element_count() != 0 {
+            self.vias.push(via);
+        }
+        Ok(())
+    }
+
+    /// `- name ( component pin ) ... + ROUTED layer ... NEW layer ...`,
+    /// special nets may also have `+ RECT`, `+ POLYGON` and `+ VIA` shapes.
+    fn net(&mut self, item: &mut Tokens, special: bool) -> DefReadResult<()> {
+        item.require("net name")?;
+        while let Some(token) = item.next() {
+            if token != "+" {
+                continue;
+            }
+            match item.next().as_deref() {
+                Some("ROUTED" | "FIXED" | "COVER" | "NOSHIELD") => self.wiring(item, special)?,
+                Some("RECT" | "POLYGON") if special => {
+                    let layer = item.require("layer name")?;
+                    item.skip_to_points();
+                    let points = item.points()?;
+                    if let Some(&layer) = self.layers.get(&layer) {
+                        let points: Vec<Point> = points.iter().map(|&p| self.dbu(p)).collect();
+                        self.add_polygon((layer.layer, layer.data_type), &points)?;
+                    }
+                }
+                Some("VIA") if special => {
+                    let via = item.require("via name")?;
+                    let orient = item.peek().and_then(DefOrient::parse);
+                    if orient.is_some() {
+                        item.skip(1);
+                    }
+                    for point in item.points()? {
+                        self.add_via(via.clone(), point, orient)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Wires up to the next `+`, the first layer follows `ROUTED`, the others `NEW`.
+    fn wiring(&mut self, item: &mut Tokens, special: bool) -> DefReadResult<()> {
+        loop {
+            let mut name = item.require("layer name")?;
+            let mut layer = self.layers.get(&name).copied();
+            let mut width = if special { Some(item.number()? * self.scale) } else { None };
+            loop {
+                match (item.peek(), item.peek_at(1)) {
+                    (Some("+"), Some("SHAPE" | "STYLE" | "MASK")) => item.skip(3),
+                    (Some("TAPER"), _) => item.skip(1),
+                    (Some("TAPERRULE" | "STYLE"), _) => item.skip(2),
+                    _ => break,
+                }
+            }
+
+            let mut wire: Vec<(Point, Option<f64>)> = vec![];
+            let mut last = None;
+            loop {
+                match item.peek() {
+                    None | Some("+" | "NEW") => break,
+                    Some("(") => {
+                        let (point, extension) = item.point(last)?;
+                        last = Some(point);
+                        wire.push((point, extension));
+                    }
+                    Some("MASK") => item.skip(2),
+                    Some("VIRTUAL") => {
+                        item.skip(1);
+                        self.add_wire(layer, width, special, &std::mem::take(&mut wire))?;
+                        let (point, _) = item.point(last)?;
+                        last = Some(point);
+                        wire.push((point, None));
+                    }
+                    Some("RECT") => {
+                        item.skip(1);
+                        item.expect("(")?;
+                        let mut offsets = [0.0; 4];
+                        for offset in &mut offsets {
+                            *offset = item.number()?;
+                        }
+                        item.expect(")")?;
+                        if let (Some(layer), Some((x, y))) = (layer, last) {
+                            let [x0, y0, x1, y1] = offsets.map(|o| o * self.scale);
+                            let (x, y) = self.dbu((x, y));
+                            let points = [(x + x0, y + y0), (x + x0, y + y1), (x + x1, y + y1), (x + x1, y + y0)];
+                            self.add_polygon((layer.layer, layer.data_type), &points)?;
+                        }
+                    }
+                    Some(_) => {
+                        let via = item.require("via name")?;
+                        let next = match self.via_layers.get(&via) {
+                            Some((bottom, top)) if *bottom == name => top.clone(),
+                            Some((bottom, top)) if *top == name => bottom.clone(),
+                            Some(_) => return Err(DefReadError::ViaLayer(via, name)),
+                            None => return Err(DefReadError::UnknownVia(via)),
+                        };
+                        let orient = item.peek().and_then(DefOrient::parse);
+                        if orient.is_some() {
+                            item.skip(1);
+                        }
+                        if let Some(point) = last {
+                            self.add_via(via, point, orient)?;
+                        }
+                        self.add_wire(layer, width, special, &std::mem::take(&mut wire))?;
+                        wire.extend(last.map(|point| (point, None)));
+                        layer = self.layers.get(&next).copied();
+                        name = next;
+                        width = None;
+                    }
+                }
+            }
+            self.add_wire(layer, width, special, &wire)?;
+
+            if item.peek() != Some("NEW") {
+                return Ok(());
+            }
+            item.skip(1);
+        }
+    }
+
+    /// A path through the points of a wire, `width` in database units.
+    fn add_wire(&mut self, layer: Option<DefLayer>, width: Option<f64>, special: bool, wire: &[(Point, Option<f64>)]) -> DefReadResult<()> {
+        let (Some(layer), [(_, begin), .., (_, end)]) = (layer, wire) else {
+            return Ok(());
+        };
+        let width = self.length(width.unwrap_or(layer.width * 1e-6 / self.meters_per_dbunit))?;
+        let path_type = match (begin, end) {
+            (None, None) if special => GdsPathType::SquareEnd,
+            (None, None) => GdsPathType::SquareEndExtend,
+            _ => {
+                let default = if special { 0 } else { width / 2 };
+                let extension = |e: &Option<f64>| e.map_or(Ok(default), |e| self.length(e * self.scale));
+                GdsPathType::custom_extend(extension(begin)?, extension(end)?)
+            }
+        };
+        let xy = wire.iter().map(|&(p, _)| self.coord(self.dbu(p))).collect::<DefReadResult<Vec<_>>>()?;
+        self.top.push(GdsPath {
+            layer: layer.layer,
+            data_type: layer.data_type,
+            path_type: Some(path_type),
+            width: Some(width),
+            xy,
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    fn add_via(&mut self, name: String, point: Point, orient: Option<DefOrient>) -> DefReadResult<()> {
+        let position = self.coord(self.dbu(point))?;
+        let transform = orient.and_then(DefOrient::transform);
+        self.top.push(GdsSref::new(name, position, transform));
+        Ok(())
+    }
+
+    /// A boundary through points in database units, a rectangle if there are two.
+    fn add_polygon(&mut self, (layer, data_type): (i16, i16), points: &[Point]) -> DefReadResult<()> {
+        let points = match *points {
+            [a, b] => vec![a, (a.0, b.1), b, (b.0, a.1)],
+            _ => points.to_vec(),
+        };
+        if points.len() < 3 {
+            return Ok(());
+        }
+        let mut xy = points.into_iter().map(|p| self.coord(p)).collect::<DefReadResult<Vec<_>>>()?;
+        xy.push(xy[0]);
+        let mut boundary = GdsBoundary::new(layer);
+        boundary.data_type = data_type;
+        boundary.xy = xy;
+        self.top.push(boundary);
+        Ok(())
+    }
+
+    /// A point in DEF units in database units.
+    fn dbu(&self, (x, y): Point) -> Point {
+        (x * self.scale, y * self.scale)
+    }
+
+    fn coord(&self, (x, y): Point) -> DefReadResult<GdsCoord> {
+        Ok(GdsCoord::new(self.length(x)?, self.length(y)?))
+    }
+
+    fn length(&self, value: f64) -> DefReadResult<i32> {
+        let value = value.round();
+        if value < i32::MIN as f64 || value > i32::MAX as f64 {
+            return Err(DefReadError::OutOfRange(value));
+        }
+        Ok(value as i32)
+    }
+}
@@ -0,0 +1,13 @@
+#[derive(Debug, thiserror::Error)]
+pub enum DxfWriteError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("No structure named '{0}'")]
+    UnknownStructure(String),
+
+    #[error("Structure '{0}' references itself")]
+    RecursiveStructure(String),
+}
+
+pub type DxfWriteResult<T> = Result<T, DxfWriteError>;
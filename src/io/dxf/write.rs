@@ -0,0 +1,622 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Write as _};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::models::{Affine, Point};
+use crate::{GdsCoord, GdsLibrary, GdsPath, GdsPathType, GdsStructure};
+use super::{DxfWriteError, DxfWriteResult};
+
+/// Length unit of a DXF drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DxfUnit {
+    #[default]
+    Millimeter,
+    Micrometer,
+    Nanometer,
+}
+
+impl DxfUnit {
+    pub fn meters(self) -> f64 {
+        match self {
+            Self::Millimeter => 1e-3,
+            Self::Micrometer => 1e-6,
+            Self::Nanometer => 1e-9,
+        }
+    }
+
+    /// Code of the unit in the `$INSUNITS` header variable
+    fn insunits(self) -> i32 {
+        match self {
+            Self::Millimeter => 4,
+            Self::Micrometer => 13,
+            Self::Nanometer => 12,
+        }
+    }
+}
+
+/// What `DxfWriter` writes and how.
+#[derive(Debug, Clone)]
+pub struct DxfOptions {
+    /// Expand every instance into the cell instead of writing BLOCKs and INSERTs
+    pub flatten: bool,
+    /// Unit of the drawing, coordinates are converted with `GdsLibrary::meters_per_dbunit`
+    pub unit: DxfUnit,
+    /// DXF layer names by (layer, datatype), texts use their (layer, texttype)
+    pub layer_names: HashMap<(i16, i16), String>,
+    /// Also write the layers missing from `layer_names`, named `L<layer>D<datatype>`
+    pub unmapped_layers: bool,
+    /// Write paths as closed outlines instead of polylines with a width
+    pub path_outlines: bool,
+    /// Height of TEXT entities in database units, texts are left out if `None`
+    pub text_height: Option<f64>,
+}
+
+impl Default for DxfOptions {
+    fn default() -> Self {
+        Self {
+            flatten: false,
+            unit: DxfUnit::default(),
+            layer_names: HashMap::new(),
+            unmapped_layers: true,
+            path_outlines: false,
+            text_height: None,
+        }
+    }
+}
+
+impl DxfOptions {
+    /// Keep the hierarchy, every layer in millimeters, no texts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_flatten(mut self, flatten: bool) -> Self {
+        self.flatten = flatten;
+        self
+    }
+
+    pub fn with_unit(mut self, unit: DxfUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn with_layer_name(mut self, layer: i16, data_type: i16, name: impl Into<String>) -> Self {
+        self.layer_names.insert((layer, data_type), name.into());
+        self
+    }
+
+    /// Only write the layers given a name.
+    pub fn only_named_layers(mut self) -> Self {
+        self.unmapped_layers = false;
+        self
+    }
+
+    pub fn with_path_outlines(mut self, path_outlines: bool) -> Self {
+        self.path_outlines = path_outlines;
+        self
+    }
+
+    pub fn with_text_height(mut self, text_height: f64) -> Self {
+        self.text_height = Some(text_height);
+        self
+    }
+
+    /// DXF layer of (layer, datatype), `None` if it is not written.
+    fn layer_name(&self, layer: i16, data_type: i16) -> Option<String> {
+        match self.layer_names.get(&(layer, data_type)) {
+            Some(name) => Some(name.clone()),
+            None if self.unmapped_layers => Some(format!("L{}D{}", layer, data_type)),
+            None => None,
+        }
+    }
+}
+
+/// A DXF entity, coordinates already in drawing units.
+#[derive(Debug, Clone)]
+enum Entity {
+    Polyline { layer: String, points: Vec<Point>, closed: bool, width: f64 },
+    Text { layer: String, position: Point, height: f64, string: String },
+    Insert { block: String, position: Point, magnification: f64, reflect: bool, angle: f64 },
+}
+
+/// Writes one cell of a library as an AutoCAD R2000 (AC1015) DXF drawing.
+///
+/// Boundaries and boxes become closed LWPOLYLINEs, paths become LWPOLYLINEs with a
+/// constant width, their ends extended as the path type asks. Round-ended paths are
+/// written as outlines, as are all paths with `DxfOptions::path_outlines`. Unless
+/// flattened, every structure the cell uses becomes a BLOCK placed by INSERTs, one per
+/// instance of an aref. Instances of structures missing from the library are left out.
+/// Blocks are named after their structure, with the characters DXF does not allow in
+/// names replaced by `_` and a numeric suffix when two structures would share a name.
+pub struct DxfWriter<W> {
+    writer: W,
+    options: DxfOptions,
+}
+
+impl DxfWriter<BufWriter<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> DxfWriteResult<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> DxfWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, options: DxfOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: DxfOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn write(&mut self, library: &GdsLibrary, cell: &str) -> DxfWriteResult<()> {
+        let Some(top) = library.structures.get(cell) else {
+            return Err(DxfWriteError::UnknownStructure(cell.to_string()));
+        };
+        let mut collector = Collector {
+            library,
+            options: &self.options,
+            scale: library.meters_per_dbunit / self.options.unit.meters(),
+            block_names: HashMap::new(),
+        };
+
+        // Blocks in the order they are first used, each after the blocks it inserts
+        let mut blocks: Vec<(String, Vec<Entity>)> = vec![];
+        if !self.options.flatten {
+            let mut names = vec![];
+            collector.block_order(&top.read().unwrap(), &mut vec![cell.to_string()], &mut names)?;
+            collector.block_names = block_names(&names);
+            for name in names {
+                let structure = library.structures[&name].read().unwrap();
+                let mut entities = vec![];
+                collector.collect(&structure, &Affine::identity(), &mut vec![], &mut entities)?;
+                blocks.push((collector.block_names[&name].clone(), entities));
+            }
+        }
+        let mut entities = vec![];
+        collector.collect(&top.read().unwrap(), &Affine::identity(), &mut vec![cell.to_string()], &mut entities)?;
+
+        let mut layers = BTreeSet::new();
+        for entity in blocks.iter().flat_map(|(_, e)| e).chain(&entities) {
+            match entity {
+                Entity::Polyline { layer, .. } | Entity::Text { layer, .. } => {
+                    layers.insert(layer.clone());
+                }
+                Entity::Insert { .. } => {}
+            }
+        }
+        layers.remove("0");
+
+        let dxf = Dxf::document(self.options.unit, &layers, &blocks, &entities);
+        self.writer.write_all(dxf.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Turns the elements of structures into entities.
+struct Collector<'a> {
+    library: &'a GdsLibrary,
+    options: &'a DxfOptions,
+    /// Drawing units per database unit
+    scale: f64,
+    /// Block of each structure placed by INSERTs
+    block_names: HashMap<String, String>,
+}
+
+impl Collector<'_> {
+    /// Defined structures below `structure`, children before parents.
+    fn block_order(&self, structure: &GdsStructure, stack: &mut Vec<String>, names: &mut Vec<String>) -> DxfWriteResult<()> {
+        let children = structure.srefs().map(|s| &s.s_name).chain(structure.arefs().map(|a| &a.s_name));
+        for child in children {
+            if stack.contains(child) {
+                return Err(DxfWriteError::RecursiveStructure(child.clone()));
+            }
+            if names.contains(child) {
+                continue;
+            }
+            if let Some(sub) = self.library.structures.get(child) {
+                stack.push(child.clone());
+                self.block_order(&sub.read().unwrap(), stack, names)?;
+                stack.pop();
+                names.push(child.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Entities of `structure` placed by `affine`, instances expanded when flattening.
+    fn collect(&self, structure: &GdsStructure, affine: &Affine, stack: &mut Vec<String>, entities: &mut Vec<Entity>) -> DxfWriteResult<()> {
+        let points = |xy: &[GdsCoord]| -> Vec<Point> {
+            let mut xy = xy;
+            if let [first, .., last] = xy {
+                if (first.x, first.y) == (last.x, last.y) {
+                    xy = &xy[..xy.len() - 1];
+                }
+            }
+            xy.iter().map(|&c| self.to_units(affine.apply_coord(c))).collect()
+        };
+
+        for boundary in structure.boundarys() {
+            if let Some(layer) = self.options.layer_name(boundary.layer, boundary.data_type) {
+                entities.push(Entity::Polyline { layer, points: points(&boundary.xy), closed: true, width: 0.0 });
+            }
+        }
+        for path in structure.paths() {
+            if let Some(layer) = self.options.layer_name(path.layer, path.data_type) {
+                entities.extend(self.path(path, affine, layer));
+            }
+        }
+        for boxx in structure.boxes() {
+            if let Some(layer) = self.options.layer_name(boxx.layer, boxx.box_type) {
+                entities.push(Entity::Polyline { layer, points: points(&boxx.xy), closed: true, width: 0.0 });
+            }
+        }
+        if let Some(height) = self.options.text_height {
+            for text in structure.texts() {
+                if let Some(layer) = self.options.layer_name(text.layer, text.text_type) {
+                    entities.push(Entity::Text {
+                        layer,
+                        position: self.to_units(affine.apply_coord(text.position)),
+                        height: height * affine.magnification() * self.scale,
+                        string: text.string.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut instances = vec![];
+        for sref in structure.srefs() {
+            instances.push((&sref.s_name, sref.position, sref.transform));
+        }
+        for aref in structure.arefs() {
+            instances.extend(aref.positions().into_iter().map(|p| (&aref.s_name, p, aref.transform)));
+        }
+        for (name, position, transform) in instances {
+            let Some(sub) = self.library.structures.get(name) else {
+                continue;
+            };
+            if self.options.flatten {
+                if stack.contains(name) {
+                    return Err(DxfWriteError::RecursiveStructure(name.clone()));
+                }
+                stack.push(name.clone());
+                let placement = affine.then(&Affine::placement(position, transform));
+                self.collect(&sub.read().unwrap(), &placement, stack, entities)?;
+                stack.pop();
+            } else {
+                entities.push(Entity::Insert {
+                    block: self.block_names[name].clone(),
+                    position: self.to_units((position.x as f64, position.y as f64)),
+                    magnification: transform.map(|t| t.magnification()).unwrap_or(1.0),
+                    reflect: transform.is_some_and(|t| t.flag.reflect),
+                    angle: transform.map(|t| t.angle()).unwrap_or(0.0),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// A path as a polyline with width along its center line, or as its outline.
+    fn path(&self, path: &GdsPath, affine: &Affine, layer: String) -> Option<Entity> {
+        if self.options.path_outlines || path.path_type() == GdsPathType::RoundEnd {
+            let outline = path.outline();
+            if outline.is_empty() {
+                return None;
+            }
+            let points = outline.iter().map(|&c| self.to_units(affine.apply_coord(c))).collect();
+            return Some(Entity::Polyline { layer, points, closed: true, width: 0.0 });
+        }
+
+        let mut center: Vec<Point> = vec![];
+        for c in &path.xy {
+            let p = (c.x as f64, c.y as f64);
+            if center.last() != Some(&p) {
+                center.push(p);
+            }
+        }
+        if center.is_empty() {
+            return None;
+        }
+        if center.len() > 1 {
+            // Extensions move the end points along the first and last segment
+            let (begin, end) = path.extensions();
+            let extend = |point: Point, from: Point, by: i32| {
+                let (dx, dy) = (point.0 - from.0, point.1 - from.1);
+                let len = dx.hypot(dy);
+                (point.0 + dx / len * by as f64, point.1 + dy / len * by as f64)
+            };
+            let last = center.len() - 1;
+            center[0] = extend(center[0], center[1], begin);
+            center[last] = extend(center[last], center[last - 1], end);
+        }
+        let points = center.into_iter().map(|(x, y)| self.to_units(affine.apply(x, y))).collect();
+        let width = path.width().abs() as f64 * affine.magnification() * self.scale;
+        Some(Entity::Polyline { layer, points, closed: false, width })
+    }
+
+    fn to_units(&self, (x, y): Point) -> Point {
+        (x * self.scale, y * self.scale)
+    }
+}
+
+/// Block names of structures, in the order of `names`. Block names may not hold some
+/// characters that structure names may, and AutoCAD compares them ignoring case,
+/// so names that end up the same get a numeric suffix.
+fn block_names(names: &[String]) -> HashMap<String, String> {
+    let mut taken = HashSet::new();
+    let mut blocks = HashMap::new();
+    for name in names {
+        let base: String = name.chars()
+            .map(|c| if "<>/\\\":;?*|=`".contains(c) { '_' } else { c })
+            .collect();
+        let mut block = base.clone();
+        let mut suffix = 1;
+        while !taken.insert(block.to_uppercase()) {
+            block = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        blocks.insert(name.clone(), block);
+    }
+    blocks
+}
+
+/// Text of a DXF document, with the handles given out so far.
+#[derive(Default)]
+struct Dxf {
+    out: String,
+    handles: u64,
+}
+
+impl Dxf {
+    fn pair(&mut self, code: i32, value: impl Display) {
+        let _ = write!(self.out, "{:>3}\n{}\n", code, value);
+    }
+
+    fn handle(&mut self) -> String {
+        self.handles += 1;
+        format!("{:X}", self.handles)
+    }
+
+    /// Start a record owned by `owner`, returning its handle.
+    fn record(&mut self, kind: &str, owner: &str) -> String {
+        let handle = self.handle();
+        self.pair(0, kind);
+        self.pair(if kind == "DIMSTYLE" { 105 } else { 5 }, &handle);
+        self.pair(330, owner);
+        handle
+    }
+
+    fn document(unit: DxfUnit, layers: &BTreeSet<String>, blocks: &[(String, Vec<Entity>)], entities: &[Entity]) -> String {
+        // Every handle is given out before the header is written, which holds the next free one
+        let mut body = Dxf::default();
+        body.tables_and_blocks(layers, blocks, entities);
+
+        let mut dxf = Dxf::default();
+        dxf.pair(0, "SECTION");
+        dxf.pair(2, "HEADER");
+        dxf.pair(9, "$ACADVER");
+        dxf.pair(1, "AC1015");
+        dxf.pair(9, "$HANDSEED");
+        dxf.pair(5, format!("{:X}", body.handles + 1));
+        dxf.pair(9, "$INSUNITS");
+        dxf.pair(70, unit.insunits());
+        dxf.pair(9, "$MEASUREMENT");
+        dxf.pair(70, 1);
+        dxf.pair(0, "ENDSEC");
+        dxf.pair(0, "SECTION");
+        dxf.pair(2, "CLASSES");
+        dxf.pair(0, "ENDSEC");
+        dxf.out.push_str(&body.out);
+        dxf.pair(0, "EOF");
+        dxf.out
+    }
+
+    fn tables_and_blocks(&mut self, layers: &BTreeSet<String>, blocks: &[(String, Vec<Entity>)], entities: &[Entity]) {
+        self.pair(0, "SECTION");
+        self.pair(2, "TABLES");
+
+        for name in ["VPORT", "VIEW", "UCS"] {
+            self.table(name, 0);
+            self.pair(0, "ENDTAB");
+        }
+
+        let table = self.table("LTYPE", 3);
+        for (name, description) in [("ByBlock", ""), ("ByLayer", ""), ("Continuous", "Solid line")] {
+            self.record("LTYPE", &table);
+            self.pair(100, "AcDbSymbolTableRecord");
+            self.pair(100, "AcDbLinetypeTableRecord");
+            self.pair(2, name);
+            self.pair(70, 0);
+            self.pair(3, description);
+            self.pair(72, 65);
+            self.pair(73, 0);
+            self.pair(40, 0.0);
+        }
+        self.pair(0, "ENDTAB");
+
+        let table = self.table("LAYER", layers.len() + 1);
+        for (index, name) in std::iter::once("0").chain(layers.iter().map(String::as_str)).enumerate() {
+            self.record("LAYER", &table);
+            self.pair(100, "AcDbSymbolTableRecord");
+            self.pair(100, "AcDbLayerTableRecord");
+            self.pair(2, name);
+            self.pair(70, 0);
+            // The first colors of the AutoCAD color index, white for layer 0
+            self.pair(62, if index == 0 { 7 } else { 1 + (index - 1) % 6 });
+            self.pair(6, "Continuous");
+        }
+        self.pair(0, "ENDTAB");
+
+        let table = self.table("STYLE", 1);
+        self.record("STYLE", &table);
+        self.pair(100, "AcDbSymbolTableRecord");
+        self.pair(100, "AcDbTextStyleTableRecord");
+        self.pair(2, "Standard");
+        self.pair(70, 0);
+        self.pair(40, 0.0);
+        self.pair(41, 1.0);
+        self.pair(50, 0.0);
+        self.pair(71, 0);
+        self.pair(42, 2.5);
+        self.pair(3, "txt");
+        self.pair(4, "");
+        self.pair(0, "ENDTAB");
+
+        let table = self.table("APPID", 1);
+        self.record("APPID", &table);
+        self.pair(100, "AcDbSymbolTableRecord");
+        self.pair(100, "AcDbRegAppTableRecord");
+        self.pair(2, "ACAD");
+        self.pair(70, 0);
+        self.pair(0, "ENDTAB");
+
+        let table = self.table("DIMSTYLE", 1);
+        self.record("DIMSTYLE", &table);
+        self.pair(100, "AcDbSymbolTableRecord");
+        self.pair(100, "AcDbDimStyleTableRecord");
+        self.pair(2, "Standard");
+        self.pair(70, 0);
+        self.pair(0, "ENDTAB");
+
+        let names: Vec<&str> = ["*Model_Space", "*Paper_Space"]
+            .into_iter()
+            .chain(blocks.iter().map(|(name, _)| name.as_str()))
+            .collect();
+        let table = self.table("BLOCK_RECORD", names.len());
+        let mut records = BTreeMap::new();
+        for name in &names {
+            let handle = self.record("BLOCK_RECORD", &table);
+            self.pair(100, "AcDbSymbolTableRecord");
+            self.pair(100, "AcDbBlockTableRecord");
+            self.pair(2, name);
+            records.insert(*name, handle);
+        }
+        self.pair(0, "ENDTAB");
+        self.pair(0, "ENDSEC");
+
+        self.pair(0, "SECTION");
+        self.pair(2, "BLOCKS");
+        self.block("*Model_Space", &records["*Model_Space"], &[]);
+        self.block("*Paper_Space", &records["*Paper_Space"], &[]);
+        for (name, block_entities) in blocks {
+            self.block(name, &records[name.as_str()], block_entities);
+        }
+        self.pair(0, "ENDSEC");
+
+        self.pair(0, "SECTION");
+        self.pair(2, "ENTITIES");
+        for entity in entities {
+            self.entity(entity, &records["*Model_Space"], false);
+        }
+        self.pair(0, "ENDSEC");
+
+        self.pair(0, "SECTION");
+        self.pair(2, "OBJECTS");
+        let root = self.record("DICTIONARY", "0");
+        self.pair(100, "AcDbDictionary");
+        self.pair(281, 1);
+        self.pair(3, "ACAD_GROUP");
+        let group = format!("{:X}", self.handles + 1);
+        self.pair(350, &group);
+        self.record("DICTIONARY", &root);
+        self.pair(100, "AcDbDictionary");
+        self.pair(281, 1);
+        self.pair(0, "ENDSEC");
+    }
+
+    /// Start a symbol table, returning its handle.
+    fn table(&mut self, name: &str, count: usize) -> String {
+        let handle = self.handle();
+        self.pair(0, "TABLE");
+        self.pair(2, name);
+        self.pair(5, &handle);
+        self.pair(330, 0);
+        self.pair(100, "AcDbSymbolTable");
+        self.pair(70, count);
+        if name == "DIMSTYLE" {
+            self.pair(100, "AcDbDimStyleTable");
+        }
+        handle
+    }
+
+    fn block(&mut self, name: &str, record: &str, entities: &[Entity]) {
+        let paper = name == "*Paper_Space";
+        self.record("BLOCK", record);
+        self.pair(100, "AcDbEntity");
+        if paper {
+            self.pair(67, 1);
+        }
+        self.pair(8, "0");
+        self.pair(100, "AcDbBlockBegin");
+        self.pair(2, name);
+        self.pair(70, 0);
+        self.point(10, (0.0, 0.0));
+        self.pair(3, name);
+        self.pair(1, "");
+        for entity in entities {
+            self.entity(entity, record, paper);
+        }
+        self.record("ENDBLK", record);
+        self.pair(100, "AcDbEntity");
+        if paper {
+            self.pair(67, 1);
+        }
+        self.pair(8, "0");
+        self.pair(100, "AcDbBlockEnd");
+    }
+
+    fn entity(&mut self, entity: &Entity, owner: &str, paper: bool) {
+        let kind = match entity {
+            Entity::Polyline { .. } => "LWPOLYLINE",
+            Entity::Text { .. } => "TEXT",
+            Entity::Insert { .. } => "INSERT",
+        };
+        self.record(kind, owner);
+        self.pair(100, "AcDbEntity");
+        if paper {
+            self.pair(67, 1);
+        }
+        match entity {
+            Entity::Polyline { layer, points, closed, width } => {
+                self.pair(8, layer);
+                self.pair(100, "AcDbPolyline");
+                self.pair(90, points.len());
+                self.pair(70, if *closed { 1 } else { 0 });
+                self.pair(43, width);
+                for &(x, y) in points {
+                    self.pair(10, x);
+                    self.pair(20, y);
+                }
+            }
+            Entity::Text { layer, position, height, string } => {
+                self.pair(8, layer);
+                self.pair(100, "AcDbText");
+                self.point(10, *position);
+                self.pair(40, height);
+                self.pair(1, string);
+                self.pair(100, "AcDbText");
+            }
+            Entity::Insert { block, position, magnification, reflect, angle } => {
+                self.pair(8, "0");
+                self.pair(100, "AcDbBlockReference");
+                self.pair(2, block);
+                self.point(10, *position);
+                // Reflecting about the X-axis before rotating is a negative Y scale
+                self.pair(41, magnification);
+                self.pair(42, if *reflect { -magnification } else { *magnification });
+                self.pair(43, magnification);
+                self.pair(50, angle);
+            }
+        }
+    }
+
+    fn point(&mut self, code: i32, (x, y): Point) {
+        self.pair(code, x);
+        self.pair(code + 10, y);
+        self.pair(code + 20, 0.0);
+    }
+}
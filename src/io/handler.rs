@@ -0,0 +1,33 @@
+use crate::{GdsElement, GdsElementKind, GdsRawRecord};
+use super::{GdsReadResult, GdsWriteResult};
+
+/// Where a raw record sits in the library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GdsRecordScope {
+    Library,
+    /// Structure name, empty if the record comes before STRNAME
+    Structure(String),
+    /// Name of the structure holding the element, and the element kind
+    Element(String, GdsElementKind),
+}
+
+/// What a `GdsReadHandler` made of a record.
+#[derive(Debug, Clone)]
+pub enum GdsHandled {
+    /// Add the element to the structure, where the record was.
+    /// Only records between the elements of a structure can become elements.
+    Element(GdsElement),
+    /// Keep this record raw in place of the one handled
+    Raw(GdsRawRecord),
+    /// Leave the record out
+    Drop,
+    /// Go on as if no handler were registered, see `ReadOptions::unknown_records`
+    NotHandled,
+}
+
+/// Called by `GdsReader` on each unknown or obsolete record of the codes it is registered for.
+pub type GdsReadHandler = Box<dyn FnMut(&GdsRawRecord, &GdsRecordScope) -> GdsReadResult<GdsHandled>>;
+
+/// Called by `GdsWriter` on each raw record of the codes it is registered for.
+/// Returns the record to write, or `None` to leave it out.
+pub type GdsWriteHandler = Box<dyn FnMut(&GdsRawRecord, &GdsRecordScope) -> GdsWriteResult<Option<GdsRawRecord>>>;
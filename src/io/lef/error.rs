@@ -0,0 +1,12 @@
+use crate::GdsRenderError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LefWriteError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Expand hierarchy failed '{0}'")]
+    Hierarchy(#[from] GdsRenderError),
+}
+
+pub type LefWriteResult<T> = Result<T, LefWriteError>;
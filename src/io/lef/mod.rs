@@ -0,0 +1,5 @@
+mod error;
+mod write;
+
+pub use error::*;
+pub use write::*;
@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::models::{footprint, Bounds, Flattener, Point, Shape};
+use crate::GdsLibrary;
+use super::LefWriteResult;
+
+/// USE of a pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LefPinUse {
+    Signal,
+    Power,
+    Ground,
+}
+
+impl LefPinUse {
+    /// Guess from the usual names of supply nets, e.g. `vdd` or `gnd!`.
+    pub fn of(name: &str) -> Self {
+        let name = name.trim_end_matches('!').to_ascii_lowercase();
+        match name.as_str() {
+            "vdd" | "vcc" | "vpwr" | "vdda" | "vddd" => Self::Power,
+            "gnd" | "vss" | "vgnd" | "vssa" | "vssd" => Self::Ground,
+            _ => Self::Signal,
+        }
+    }
+
+    fn keyword(self) -> &'static str {
+        match self {
+            Self::Signal => "SIGNAL",
+            Self::Power => "POWER",
+            Self::Ground => "GROUND",
+        }
+    }
+}
+
+/// What `LefWriter` writes and how.
+#[derive(Debug, Clone)]
+pub struct LefOptions {
+    /// LEF names of the routing layers by (layer, datatype), other layers are left out
+    pub layers: HashMap<(i16, i16), String>,
+    /// Routing layer named by the texts on a (layer, texttype), texts on a routing layer name that one
+    pub label_layers: HashMap<(i16, i16), (i16, i16)>,
+    /// Shapes whose bounding box is the SIZE of a macro, e.g. a placement boundary, all shapes if `None`
+    pub boundary_layer: Option<(i16, i16)>,
+    /// CLASS of the macros
+    pub class: String,
+    pub site: Option<String>,
+    /// SYMMETRY of the macros, e.g. `X Y`
+    pub symmetry: Option<String>,
+}
+
+impl Default for LefOptions {
+    fn default() -> Self {
+        Self {
+            layers: HashMap::new(),
+            label_layers: HashMap::new(),
+            boundary_layer: None,
+            class: "CORE".to_string(),
+            site: None,
+            symmetry: None,
+        }
+    }
+}
+
+impl LefOptions {
+    /// CORE macros without routing layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layer(mut self, layer: i16, data_type: i16, name: impl Into<String>) -> Self {
+        self.layers.insert((layer, data_type), name.into());
+        self
+    }
+
+    /// Texts on (`text_layer`, `text_type`) name pins on the routing layer (`layer`, `data_type`).
+    pub fn with_label_layer(mut self, text_layer: i16, text_type: i16, layer: i16, data_type: i16) -> Self {
+        self.label_layers.insert((text_layer, text_type), (layer, data_type));
+        self
+    }
+
+    pub fn with_boundary_layer(mut self, layer: i16, data_type: i16) -> Self {
+        self.boundary_layer = Some((layer, data_type));
+        self
+    }
+
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = class.into();
+        self
+    }
+
+    pub fn with_site(mut self, site: impl Into<String>) -> Self {
+        self.site = Some(site.into());
+        self
+    }
+
+    pub fn with_symmetry(mut self, symmetry: impl Into<String>) -> Self {
+        self.symmetry = Some(symmetry.into());
+        self
+    }
+}
+
+/// A polygon on a routing layer of the flattened cell.
+struct Routing<'a> {
+    layer: (i16, i16),
+    points: &'a [Point],
+    bounds: Bounds,
+}
+
+/// Shapes by LEF layer name, in the coordinates of the cell.
+type Geometry<'a> = BTreeMap<String, Vec<&'a [Point]>>;
+
+struct Pin<'a> {
+    name: String,
+    usage: LefPinUse,
+    geometry: Geometry<'a>,
+}
+
+/// Writes cells of a library as LEF macros.
+///
+/// The SIZE of a macro is the bounding box of the cell, or of the shapes on
+/// `LefOptions::boundary_layer`, with an ORIGIN moving its lower left corner to `0 0`,
+/// so coordinates stay those of the cell. Every text of the cell itself on a routing
+/// layer makes a pin of the shapes under it, together with the shapes on that layer
+/// touching them; texts with the same string make one pin. All other shapes on
+/// routing layers become OBS. Instances are expanded, shapes are written as RECT when
+/// they are axis-aligned rectangles, POLYGON otherwise.
+pub struct LefWriter<W> {
+    writer: W,
+    options: LefOptions,
+}
+
+impl LefWriter<BufWriter<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> LefWriteResult<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> LefWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, options: LefOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: LefOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn write(&mut self, library: &GdsLibrary, cells: &[&str]) -> LefWriteResult<()> {
+        let microns = library.meters_per_dbunit * 1e6;
+        let decimals = decimals(microns);
+        let um = |v: f64| micron(v * microns, decimals);
+
+        writeln!(self.writer, "VERSION 5.8 ;")?;
+        writeln!(self.writer, "BUSBITCHARS \"[]\" ;")?;
+        writeln!(self.writer, "DIVIDERCHAR \"/\" ;")?;
+        for &cell in cells {
+            let shapes = Flattener::new(library).flatten(cell, usize::MAX)?;
+            let texts: Vec<_> = match library.structures.get(cell) {
+                Some(structure) => structure.read().unwrap().texts().cloned().collect(),
+                None => vec![],
+            };
+
+            let polygons = shapes.iter().filter_map(|shape| match shape {
+                Shape::Polygon { layer, data_type, points } if points.len() > 2 => Some(((*layer, *data_type), points)),
+                _ => None,
+            });
+            let size = footprint(&shapes, self.options.boundary_layer)
+                .unwrap_or(Bounds { min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+
+            let routing: Vec<Routing> = polygons
+                .filter(|(layer, points)| self.options.layers.contains_key(layer) && area(points) != 0.0)
+                .filter_map(|(layer, points)| Some(Routing { layer, points, bounds: Bounds::of(points)? }))
+                .collect();
+            let mut used = vec![false; routing.len()];
+
+            let mut pins: Vec<Pin> = vec![];
+            for text in &texts {
+                let label = (text.layer, text.text_type);
+                let layer = self.options.label_layers.get(&label).copied().unwrap_or(label);
+                let Some(layer_name) = self.options.layers.get(&layer) else {
+                    continue;
+                };
+                let position = (text.position.x as f64, text.position.y as f64);
+                let mut stack: Vec<usize> = (0..routing.len())
+                    .filter(|&i| !used[i] && routing[i].layer == layer && contains(routing[i].points, position))
+                    .collect();
+                if stack.is_empty() {
+                    continue;
+                }
+
+                let index = match pins.iter().position(|pin| pin.name == text.string) {
+                    Some(index) => index,
+                    None => {
+                        let usage = LefPinUse::of(&text.string);
+                        pins.push(Pin { name: text.string.clone(), usage, geometry: BTreeMap::new() });
+                        pins.len() - 1
+                    }
+                };
+                stack.iter().for_each(|&i| used[i] = true);
+                while let Some(i) = stack.pop() {
+                    pins[index].geometry.entry(layer_name.clone()).or_default().push(routing[i].points);
+                    for j in 0..routing.len() {
+                        if !used[j] && routing[j].layer == layer && routing[i].bounds.intersects(&routing[j].bounds)
+                            && touch(routing[i].points, routing[j].points)
+                        {
+                            used[j] = true;
+                            stack.push(j);
+                        }
+                    }
+                }
+            }
+
+            let mut obstructions: Geometry = BTreeMap::new();
+            for (shape, _) in routing.iter().zip(&used).filter(|(_, used)| !**used) {
+                obstructions.entry(self.options.layers[&shape.layer].clone()).or_default().push(shape.points);
+            }
+
+            writeln!(self.writer)?;
+            writeln!(self.writer, "MACRO {}", cell)?;
+            writeln!(self.writer, "  CLASS {} ;", self.options.class)?;
+            writeln!(self.writer, "  ORIGIN {} {} ;", um(-size.min_x), um(-size.min_y))?;
+            writeln!(self.writer, "  FOREIGN {} 0 0 ;", cell)?;
+            writeln!(self.writer, "  SIZE {} BY {} ;", um(size.width()), um(size.height()))?;
+            if let Some(symmetry) = &self.options.symmetry {
+                writeln!(self.writer, "  SYMMETRY {} ;", symmetry)?;
+            }
+            if let Some(site) = &self.options.site {
+                writeln!(self.writer, "  SITE {} ;", site)?;
+            }
+            for pin in &pins {
+                writeln!(self.writer, "  PIN {}", pin.name)?;
+                writeln!(self.writer, "    DIRECTION INOUT ;")?;
+                writeln!(self.writer, "    USE {} ;", pin.usage.keyword())?;
+                writeln!(self.writer, "    PORT")?;
+                self.write_geometry(&pin.geometry, "      ", &um)?;
+                writeln!(self.writer, "    END")?;
+                writeln!(self.writer, "  END {}", pin.name)?;
+            }
+            if !obstructions.is_empty() {
+                writeln!(self.writer, "  OBS")?;
+                self.write_geometry(&obstructions, "    ", &um)?;
+                writeln!(self.writer, "  END")?;
+            }
+            writeln!(self.writer, "END {}", cell)?;
+        }
+        writeln!(self.writer)?;
+        writeln!(self.writer, "END LIBRARY")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_geometry(&mut self, geometry: &Geometry, indent: &str, um: &impl Fn(f64) -> String) -> LefWriteResult<()> {
+        for (layer, polygons) in geometry {
+            writeln!(self.writer, "{}LAYER {} ;", indent, layer)?;
+            for points in polygons {
+                match rectangle(points) {
+                    Some(b) => writeln!(
+                        self.writer,
+                        "{}  RECT {} {} {} {} ;",
+                        indent, um(b.min_x), um(b.min_y), um(b.max_x), um(b.max_y)
+                    )?,
+                    None => {
+                        write!(self.writer, "{}  POLYGON", indent)?;
+                        for &(x, y) in points.iter() {
+                            write!(self.writer, " {} {}", um(x), um(y))?;
+                        }
+                        writeln!(self.writer, " ;")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn area(points: &[Point]) -> f64 {
+    points.iter().zip(points.iter().cycle().skip(1)).map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum::<f64>() / 2.0
+}
+
+/// Bounds of points forming an axis-aligned rectangle.
+fn rectangle(points: &[Point]) -> Option<Bounds> {
+    if points.len() != 4 {
+        return None;
+    }
+    let axis_aligned = (0..4).all(|i| {
+        let (a, b) = (points[i], points[(i + 1) % 4]);
+        (a.0 == b.0) != (a.1 == b.1)
+    });
+    if axis_aligned { Bounds::of(points) } else { None }
+}
+
+/// Whether `point` is inside `polygon` or on its outline.
+fn contains(polygon: &[Point], point: Point) -> bool {
+    let edges = || polygon.iter().zip(polygon.iter().cycle().skip(1));
+    if edges().any(|(&a, &b)| on_segment(a, b, point)) {
+        return true;
+    }
+    let (x, y) = point;
+    edges()
+        .filter(|(a, b)| (a.1 > y) != (b.1 > y))
+        .filter(|(a, b)| x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1))
+        .count()
+        % 2
+        == 1
+}
+
+/// Whether two polygons overlap or share a point of their outlines.
+fn touch(a: &[Point], b: &[Point]) -> bool {
+    let edges = |p: &[Point]| -> Vec<(Point, Point)> { p.iter().copied().zip(p.iter().copied().cycle().skip(1)).collect() };
+    let (edges_a, edges_b) = (edges(a), edges(b));
+    edges_a.iter().any(|&(p, q)| edges_b.iter().any(|&(r, s)| segments_touch(p, q, r, s)))
+        || a.first().is_some_and(|&p| contains(b, p))
+        || b.first().is_some_and(|&p| contains(a, p))
+}
+
+fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    cross(a, b, p) == 0.0
+        && p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0)
+        && p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
+
+fn segments_touch(p: Point, q: Point, r: Point, s: Point) -> bool {
+    let (d1, d2) = (cross(r, s, p), cross(r, s, q));
+    let (d3, d4) = (cross(p, q, r), cross(p, q, s));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0)) && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0)) {
+        return true;
+    }
+    on_segment(r, s, p) || on_segment(r, s, q) || on_segment(p, q, r) || on_segment(p, q, s)
+}
+
+/// Decimals it takes to write a multiple of `microns` exactly, at most nine.
+fn decimals(microns: f64) -> usize {
+    (0..9)
+        .find(|&d| {
+            let scaled = microns * 10f64.powi(d as i32);
+            (scaled - scaled.round()).abs() < 1e-6 * scaled.abs().max(1.0)
+        })
+        .unwrap_or(9)
+}
+
+/// A length in microns without trailing zeros.
+fn micron(value: f64, decimals: usize) -> String {
+    let mut text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    if text == "-0" { "0".to_string() } else { text }
+}
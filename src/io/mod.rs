@@ -1,6 +1,19 @@
 mod read;
 mod write;
 mod record;
+mod handler;
+mod oasis;
+mod dxf;
+mod cif;
+mod lef;
+mod def;
 
 pub use read::*;
-pub use write::*;
\ No newline at end of file
+pub use write::*;
+pub use record::*;
+pub use handler::*;
+pub use oasis::*;
+pub use dxf::*;
+pub use cif::*;
+pub use lef::*;
+pub use def::*;
\ No newline at end of file
@@ -0,0 +1,60 @@
+use std::string::FromUtf8Error;
+use crate::GdsLibraryBuilderError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OasisReadError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Parse utf8 failed '{0}'")]
+    Utf8(#[from] FromUtf8Error),
+
+    #[error("Missing the '%SEMI-OASIS' magic bytes")]
+    InvalidMagic,
+
+    #[error("Unexpected end of data at byte {0}")]
+    UnexpectEof(usize),
+
+    #[error("Unknown record id '{0}' at byte {1}")]
+    UnknownRecord(u64, usize),
+
+    #[error("Unsupport record '{0}'")]
+    UnsupportRecord(&'static str),
+
+    #[error("Record '{0}' is not expected here")]
+    UnexpectRecord(&'static str),
+
+    #[error("Invalid {0} type '{1}'")]
+    InvalidType(&'static str, u64),
+
+    #[error("Invalid unit '{0}'")]
+    InvalidUnit(f64),
+
+    #[error("Modal variable '{0}' is used before it is set")]
+    UndefinedModal(&'static str),
+
+    #[error("No {0} with reference number '{1}'")]
+    UnknownReference(&'static str, u64),
+
+    #[error("Value '{0}' does not fit in {1}")]
+    OutOfRange(i128, &'static str),
+
+    #[error("Validation failed, expect signature {0:#010x}, but got {1:#010x}")]
+    Validation(u32, u32),
+
+    #[error("Build library failed for '{0}'")]
+    BuildLibrary(#[from] GdsLibraryBuilderError),
+}
+
+pub type OasisReadResult<T> = Result<T, OasisReadError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OasisWriteError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Value '{0}' can not be stored as {1}")]
+    OutOfRange(i64, &'static str),
+}
+
+pub type OasisWriteResult<T> = Result<T, OasisWriteError>;
@@ -0,0 +1,8 @@
+mod error;
+mod record;
+mod read;
+mod write;
+
+pub use error::*;
+pub use read::*;
+pub use write::*;
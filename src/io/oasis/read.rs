@@ -0,0 +1,1071 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use flate2::Crc;
+use flate2::read::DeflateDecoder;
+
+use crate::{
+    GdsAref, GdsBoundary, GdsCoord, GdsDateTime, GdsElementMut, GdsLibrary, GdsLibraryBuilder, GdsPath, GdsPathType, GdsProperty, GdsSref, GdsStructure, GdsText, GdsTransform, GdsTransformFlag
+};
+use super::record::*;
+use super::{OasisReadError, OasisReadResult};
+
+/// Name given to libraries read from a stream, OASIS files have none
+const DEFAULT_NAME: &str = "LIB";
+
+/// Number of vertices of the polygon approximating a CIRCLE
+const CIRCLE_SEGMENTS: usize = 64;
+
+/// Reads an OASIS file into the GDSII object model.
+///
+/// Rectangles, polygons, trapezoids, circles and paths become boundaries and paths, placements
+/// become srefs, or arefs for repetitions on a lattice, and `S_GDS_PROPERTY` properties
+/// become element properties. Repeated geometry and irregular placement repetitions
+/// are expanded into one element per copy. Other properties, layer names and
+/// extension records are skipped.
+pub struct OasisReader<R> {
+    reader: R,
+    name: String,
+}
+
+impl OasisReader<File> {
+    /// The library is named after the file.
+    pub fn open<P: AsRef<Path>>(path: P) -> OasisReadResult<Self> {
+        let path = path.as_ref();
+        let reader = Self::new(File::open(path)?);
+        Ok(match path.file_stem() {
+            Some(stem) => reader.with_name(stem.to_string_lossy()),
+            None => reader,
+        })
+    }
+}
+
+impl<R: Read> OasisReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, name: DEFAULT_NAME.to_string() }
+    }
+
+    /// Name of the library to read into.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn read(&mut self) -> OasisReadResult<GdsLibrary> {
+        let mut data = vec![];
+        self.reader.read_to_end(&mut data)?;
+        Parser::new(data).parse(self.name.clone())
+    }
+}
+
+/// A cell, text string or property name, given inline or by reference number
+#[derive(Debug, Clone)]
+enum NameRef {
+    Name(String),
+    Number(u64),
+}
+
+#[derive(Debug, Clone)]
+enum PropertyValue {
+    Real(f64),
+    Unsigned(u64),
+    Signed(i64),
+    String(Vec<u8>),
+    Reference(u64),
+}
+
+#[derive(Debug, Clone)]
+struct Property {
+    name: NameRef,
+    values: Vec<PropertyValue>,
+}
+
+/// Names of one kind by reference number, numbered implicitly from zero
+/// unless the record gives a number
+#[derive(Default)]
+struct NameTable {
+    names: HashMap<u64, Vec<u8>>,
+    next: u64,
+}
+
+impl NameTable {
+    fn insert(&mut self, name: Vec<u8>, number: Option<u64>) {
+        let number = number.unwrap_or_else(|| {
+            self.next += 1;
+            self.next - 1
+        });
+        self.names.insert(number, name);
+    }
+
+    fn get(&self, what: &'static str, number: u64) -> OasisReadResult<String> {
+        let name = self.names.get(&number).ok_or(OasisReadError::UnknownReference(what, number))?;
+        Ok(String::from_utf8(name.clone())?)
+    }
+
+    fn resolve(&self, what: &'static str, name: NameRef) -> OasisReadResult<String> {
+        match name {
+            NameRef::Name(name) => Ok(name),
+            NameRef::Number(number) => self.get(what, number),
+        }
+    }
+}
+
+struct Cell {
+    name: NameRef,
+    structure: GdsStructure,
+    /// Placements whose cell is given by reference number
+    placement_refs: Vec<(usize, u64)>,
+    /// Texts whose string is given by reference number
+    text_refs: Vec<(usize, u64)>,
+    properties: Vec<(usize, Property)>,
+}
+
+/// Modal variables, reset at each CELL
+#[derive(Default)]
+struct Modal {
+    relative: bool,
+    placement_xy: (i64, i64),
+    text_xy: (i64, i64),
+    geometry_xy: (i64, i64),
+    placement_cell: Option<NameRef>,
+    layer: Option<u64>,
+    datatype: Option<u64>,
+    textlayer: Option<u64>,
+    texttype: Option<u64>,
+    text_string: Option<NameRef>,
+    geometry_w: Option<u64>,
+    geometry_h: Option<u64>,
+    polygon_points: Option<Vec<(i64, i64)>>,
+    path_half_width: Option<u64>,
+    path_points: Option<Vec<(i64, i64)>>,
+    path_start_extension: Option<OasisExtension>,
+    path_end_extension: Option<OasisExtension>,
+    ctrapezoid_type: Option<u64>,
+    circle_radius: Option<u64>,
+    repetition: Option<OasisRepetition>,
+    property_name: Option<NameRef>,
+    property_values: Option<Vec<PropertyValue>>,
+}
+
+#[derive(Clone, Copy)]
+enum Position {
+    Placement,
+    Text,
+    Geometry,
+}
+
+struct Parser {
+    data: Vec<u8>,
+    offset: usize,
+    /// Decompressed content of the CBLOCK being read, and the offset in it
+    block: Option<(Vec<u8>, usize)>,
+    /// Whether the table offsets are in END rather than START
+    tables_at_end: bool,
+    unit: f64,
+    modal: Modal,
+    cell_names: NameTable,
+    text_strings: NameTable,
+    property_names: NameTable,
+    property_strings: NameTable,
+    cells: Vec<Cell>,
+    /// Indices of the elements made by the last element record, where following properties go
+    targets: Vec<usize>,
+}
+
+impl Parser {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            offset: 0,
+            block: None,
+            tables_at_end: false,
+            unit: 1000.0,
+            modal: Modal::default(),
+            cell_names: NameTable::default(),
+            text_strings: NameTable::default(),
+            property_names: NameTable::default(),
+            property_strings: NameTable::default(),
+            cells: vec![],
+            targets: vec![],
+        }
+    }
+
+    fn parse(mut self, name: String) -> OasisReadResult<GdsLibrary> {
+        if !self.data.starts_with(MAGIC) {
+            return Err(OasisReadError::InvalidMagic);
+        }
+        self.offset = MAGIC.len();
+
+        if self.uint()? != START {
+            return Err(OasisReadError::UnexpectRecord("expect START"));
+        }
+        self.read_start()?;
+
+        loop {
+            let record_offset = self.offset;
+            let id = self.uint()?;
+            // Properties go to the last element, cell or name record,
+            // the other records are transparent to them
+            if !matches!(id, PAD | XYABSOLUTE | XYRELATIVE | PROPERTY | PROPERTY_REPEAT | CBLOCK) {
+                self.targets.clear();
+            }
+            match id {
+                PAD => {}
+                START => return Err(OasisReadError::UnexpectRecord("START")),
+                END => {
+                    if self.block.is_some() {
+                        return Err(OasisReadError::UnexpectRecord("END"));
+                    }
+                    self.read_end()?;
+                    break;
+                }
+                CELLNAME | CELLNAME_REF => {
+                    let name = self.string()?;
+                    let number = if id == CELLNAME_REF { Some(self.uint()?) } else { None };
+                    self.cell_names.insert(name, number);
+                }
+                TEXTSTRING | TEXTSTRING_REF => {
+                    let name = self.string()?;
+                    let number = if id == TEXTSTRING_REF { Some(self.uint()?) } else { None };
+                    self.text_strings.insert(name, number);
+                }
+                PROPNAME | PROPNAME_REF => {
+                    let name = self.string()?;
+                    let number = if id == PROPNAME_REF { Some(self.uint()?) } else { None };
+                    self.property_names.insert(name, number);
+                }
+                PROPSTRING | PROPSTRING_REF => {
+                    let name = self.string()?;
+                    let number = if id == PROPSTRING_REF { Some(self.uint()?) } else { None };
+                    self.property_strings.insert(name, number);
+                }
+                LAYERNAME | LAYERNAME_TEXT => {
+                    self.string()?;
+                    self.interval()?;
+                    self.interval()?;
+                }
+                CELL_REF | CELL => {
+                    let name = match id {
+                        CELL_REF => NameRef::Number(self.uint()?),
+                        _ => NameRef::Name(self.name()?),
+                    };
+                    self.modal = Modal::default();
+                    self.cells.push(Cell {
+                        name,
+                        structure: GdsStructure::default(),
+                        placement_refs: vec![],
+                        text_refs: vec![],
+                        properties: vec![],
+                    });
+                }
+                XYABSOLUTE => self.modal.relative = false,
+                XYRELATIVE => self.modal.relative = true,
+                PLACEMENT | PLACEMENT_TRANSFORM => self.read_placement(id == PLACEMENT_TRANSFORM)?,
+                TEXT => self.read_text()?,
+                RECTANGLE => self.read_rectangle()?,
+                POLYGON => self.read_polygon()?,
+                PATH => self.read_path()?,
+                TRAPEZOID | TRAPEZOID_A | TRAPEZOID_B => self.read_trapezoid(id)?,
+                CTRAPEZOID => self.read_ctrapezoid()?,
+                CIRCLE => self.read_circle()?,
+                PROPERTY => self.read_property()?,
+                PROPERTY_REPEAT => {
+                    let name = self.modal.property_name.clone().ok_or(OasisReadError::UndefinedModal("last-property-name"))?;
+                    let values = self.modal.property_values.clone().ok_or(OasisReadError::UndefinedModal("last-value-list"))?;
+                    self.attach_property(Property { name, values });
+                }
+                XNAME | XNAME_REF => {
+                    self.uint()?;
+                    self.string()?;
+                    if id == XNAME_REF {
+                        self.uint()?;
+                    }
+                }
+                XELEMENT => {
+                    self.uint()?;
+                    self.string()?;
+                }
+                XGEOMETRY => {
+                    let info = self.byte()?;
+                    self.uint()?;
+                    self.read_layer(info)?;
+                    self.string()?;
+                    self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+                    self.read_repetition(info & 0x04 != 0)?;
+                }
+                CBLOCK => self.read_cblock()?,
+                _ => return Err(OasisReadError::UnknownRecord(id, record_offset)),
+            }
+        }
+
+        self.build(name)
+    }
+
+    fn read_start(&mut self) -> OasisReadResult<()> {
+        self.string()?;
+        self.unit = self.real()?;
+        if self.unit.is_nan() || self.unit <= 0.0 {
+            return Err(OasisReadError::InvalidUnit(self.unit));
+        }
+        self.tables_at_end = self.uint()? == 1;
+        if !self.tables_at_end {
+            self.table_offsets()?;
+        }
+        Ok(())
+    }
+
+    fn read_end(&mut self) -> OasisReadResult<()> {
+        if self.tables_at_end {
+            self.table_offsets()?;
+        }
+        self.string()?;
+        let scheme = self.uint()?;
+        let covered = &self.data[..self.offset];
+        let expected = match scheme {
+            0 => return Ok(()),
+            1 => {
+                let mut crc = Crc::new();
+                crc.update(covered);
+                crc.sum()
+            }
+            2 => covered.iter().fold(0u32, |sum, &b| sum.wrapping_add(b as u32)),
+            _ => return Err(OasisReadError::InvalidType("validation scheme", scheme)),
+        };
+        let signature = u32::from_le_bytes(self.array()?);
+        if signature != expected {
+            return Err(OasisReadError::Validation(expected, signature));
+        }
+        Ok(())
+    }
+
+    fn table_offsets(&mut self) -> OasisReadResult<()> {
+        for _ in 0..12 {
+            self.uint()?;
+        }
+        Ok(())
+    }
+
+    fn read_cblock(&mut self) -> OasisReadResult<()> {
+        if self.block.is_some() {
+            return Err(OasisReadError::UnexpectRecord("CBLOCK in CBLOCK"));
+        }
+        let compression = self.uint()?;
+        if compression != 0 {
+            return Err(OasisReadError::InvalidType("compression", compression));
+        }
+        let uncompressed_size = self.uint()? as usize;
+        let compressed_size = self.uint()? as usize;
+        let compressed = self.data
+            .get(self.offset..self.offset.saturating_add(compressed_size))
+            .ok_or(OasisReadError::UnexpectEof(self.data.len()))?;
+        let mut block = Vec::with_capacity(uncompressed_size.min(1 << 24));
+        DeflateDecoder::new(compressed).read_to_end(&mut block)?;
+        self.offset += compressed_size;
+        self.block = Some((block, 0));
+        Ok(())
+    }
+
+    fn read_placement(&mut self, transformed: bool) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        if info & 0x80 != 0 {
+            let cell = match info & 0x40 != 0 {
+                true => NameRef::Number(self.uint()?),
+                false => NameRef::Name(self.name()?),
+            };
+            self.modal.placement_cell = Some(cell);
+        }
+        let cell = self.modal.placement_cell.clone().ok_or(OasisReadError::UndefinedModal("placement-cell"))?;
+
+        let (magnification, angle) = if transformed {
+            let magnification = if info & 0x04 != 0 { self.real()? } else { 1.0 };
+            let angle = if info & 0x02 != 0 { self.real()? } else { 0.0 };
+            (magnification, angle)
+        } else {
+            (1.0, ((info >> 1) & 0x03) as f64 * 90.0)
+        };
+        let reflect = info & 0x01 != 0;
+        let transform = (reflect || magnification != 1.0 || angle != 0.0).then(|| GdsTransform {
+            flag: GdsTransformFlag { reflect, ..Default::default() },
+            magnification: (magnification != 1.0).then_some(magnification),
+            angle: (angle != 0.0).then_some(angle),
+        });
+
+        let (x, y) = self.read_xy(info & 0x20 != 0, info & 0x10 != 0, Position::Placement)?;
+        let repetition = self.read_repetition(info & 0x08 != 0)?;
+
+        let (name, number) = match cell {
+            NameRef::Name(name) => (name, None),
+            NameRef::Number(number) => (String::new(), Some(number)),
+        };
+        let cell = self.cell("PLACEMENT")?;
+        let mut targets = vec![];
+
+        let lattice = match &repetition {
+            Some(OasisRepetition::Lattice { cols, rows, col_pitch, row_pitch }) => {
+                match (i16::try_from(*cols), i16::try_from(*rows)) {
+                    (Ok(cols), Ok(rows)) => Some((cols, rows, *col_pitch, *row_pitch)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some((cols, rows, col_pitch, row_pitch)) = lattice {
+            let aref = GdsAref::new(name, rows, cols, coord(x, y)?, coord(row_pitch.0, row_pitch.1)?, coord(col_pitch.0, col_pitch.1)?, transform);
+            targets.push(cell.structure.element_count());
+            cell.structure.push(aref);
+        } else {
+            for (dx, dy) in offsets(repetition.as_ref()) {
+                let sref = GdsSref::new(name.clone(), coord(x + dx, y + dy)?, transform);
+                targets.push(cell.structure.element_count());
+                cell.structure.push(sref);
+            }
+        }
+        if let Some(number) = number {
+            for &index in &targets {
+                cell.placement_refs.push((index, number));
+            }
+        }
+
+        self.targets = targets;
+        Ok(())
+    }
+
+    fn read_text(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        if info & 0x40 != 0 {
+            let string = match info & 0x20 != 0 {
+                true => NameRef::Number(self.uint()?),
+                false => NameRef::Name(self.name()?),
+            };
+            self.modal.text_string = Some(string);
+        }
+        let string = self.modal.text_string.clone().ok_or(OasisReadError::UndefinedModal("text-string"))?;
+        if info & 0x01 != 0 {
+            self.modal.textlayer = Some(self.uint()?);
+        }
+        if info & 0x02 != 0 {
+            self.modal.texttype = Some(self.uint()?);
+        }
+        let layer = to_i16(self.modal.textlayer.ok_or(OasisReadError::UndefinedModal("textlayer"))?)?;
+        let text_type = to_i16(self.modal.texttype.ok_or(OasisReadError::UndefinedModal("texttype"))?)?;
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Text)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let (string, number) = match string {
+            NameRef::Name(string) => (string, None),
+            NameRef::Number(number) => (String::new(), Some(number)),
+        };
+        let cell = self.cell("TEXT")?;
+        let mut targets = vec![];
+        for (dx, dy) in offsets(repetition.as_ref()) {
+            let mut text = GdsText::new(layer, coord(x + dx, y + dy)?, string.clone());
+            text.text_type = text_type;
+            let index = cell.structure.element_count();
+            cell.structure.push(text);
+            if let Some(number) = number {
+                cell.text_refs.push((index, number));
+            }
+            targets.push(index);
+        }
+
+        self.targets = targets;
+        Ok(())
+    }
+
+    fn read_rectangle(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x40 != 0 {
+            self.modal.geometry_w = Some(self.uint()?);
+        }
+        if info & 0x20 != 0 {
+            self.modal.geometry_h = Some(self.uint()?);
+        }
+        let width = self.modal.geometry_w.ok_or(OasisReadError::UndefinedModal("geometry-w"))?;
+        if info & 0x80 != 0 {
+            self.modal.geometry_h = Some(width);
+        }
+        let height = self.modal.geometry_h.ok_or(OasisReadError::UndefinedModal("geometry-h"))?;
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let (w, h) = (width as i64, height as i64);
+        let points = [(x, y), (x, y + h), (x + w, y + h), (x + w, y), (x, y)];
+        self.add_boundaries(layer, data_type, &points, repetition.as_ref())
+    }
+
+    fn read_polygon(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x20 != 0 {
+            self.modal.polygon_points = Some(self.point_list(true)?);
+        }
+        let deltas = self.modal.polygon_points.clone().ok_or(OasisReadError::UndefinedModal("polygon-point-list"))?;
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let mut points = Vec::with_capacity(deltas.len() + 2);
+        points.push((x, y));
+        points.extend(deltas.iter().map(|&(dx, dy)| (x + dx, y + dy)));
+        points.push((x, y));
+        self.add_boundaries(layer, data_type, &points, repetition.as_ref())
+    }
+
+    fn read_trapezoid(&mut self, id: u64) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x40 != 0 {
+            self.modal.geometry_w = Some(self.uint()?);
+        }
+        if info & 0x20 != 0 {
+            self.modal.geometry_h = Some(self.uint()?);
+        }
+        let w = self.modal.geometry_w.ok_or(OasisReadError::UndefinedModal("geometry-w"))? as i64;
+        let h = self.modal.geometry_h.ok_or(OasisReadError::UndefinedModal("geometry-h"))? as i64;
+        let a = if id != TRAPEZOID_B { self.sint()? } else { 0 };
+        let b = if id != TRAPEZOID_A { self.sint()? } else { 0 };
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        // `a` slants the left or bottom edge and `b` the right or top one,
+        // each by moving one end of the edge into the bounding box
+        let corners = if info & 0x80 != 0 {
+            [(0, a.max(0)), (0, h + b.min(0)), (w, h - b.max(0)), (w, -a.min(0))]
+        } else {
+            [(a.max(0), h), (w + b.min(0), h), (w - b.max(0), 0), (-a.min(0), 0)]
+        };
+        let points = polygon(x, y, corners);
+        self.add_boundaries(layer, data_type, &points, repetition.as_ref())
+    }
+
+    fn read_ctrapezoid(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x80 != 0 {
+            self.modal.ctrapezoid_type = Some(self.uint()?);
+        }
+        if info & 0x40 != 0 {
+            self.modal.geometry_w = Some(self.uint()?);
+        }
+        if info & 0x20 != 0 {
+            self.modal.geometry_h = Some(self.uint()?);
+        }
+        let tp = self.modal.ctrapezoid_type.ok_or(OasisReadError::UndefinedModal("ctrapezoid-type"))?;
+        let vertices = *CTRAPEZOIDS.get(tp as usize).ok_or(OasisReadError::InvalidType("ctrapezoid", tp))?;
+        // Some types are drawn from one dimension alone, the other one need not be set
+        let w = match vertices.iter().any(|v| v.0 != 0 || v.2 != 0) {
+            true => self.modal.geometry_w.ok_or(OasisReadError::UndefinedModal("geometry-w"))? as i64,
+            false => 0,
+        };
+        let h = match vertices.iter().any(|v| v.1 != 0 || v.3 != 0) {
+            true => self.modal.geometry_h.ok_or(OasisReadError::UndefinedModal("geometry-h"))? as i64,
+            false => 0,
+        };
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let corners = vertices.iter().map(|&(xw, xh, yw, yh)| (xw * w + xh * h, yw * w + yh * h));
+        let points = polygon(x, y, corners);
+        self.add_boundaries(layer, data_type, &points, repetition.as_ref())
+    }
+
+    fn read_circle(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x20 != 0 {
+            self.modal.circle_radius = Some(self.uint()?);
+        }
+        let radius = self.modal.circle_radius.ok_or(OasisReadError::UndefinedModal("circle-radius"))? as f64;
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let mut points: Vec<(i64, i64)> = (0..CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / CIRCLE_SEGMENTS as f64;
+                (x + (radius * angle.cos()).round() as i64, y + (radius * angle.sin()).round() as i64)
+            })
+            .collect();
+        points.push(points[0]);
+        self.add_boundaries(layer, data_type, &points, repetition.as_ref())
+    }
+
+    fn read_path(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        let (layer, data_type) = self.read_layer(info)?;
+        if info & 0x40 != 0 {
+            self.modal.path_half_width = Some(self.uint()?);
+        }
+        if info & 0x80 != 0 {
+            let scheme = self.uint()?;
+            if let Some(extension) = self.extension((scheme >> 2) & 0x03)? {
+                self.modal.path_start_extension = Some(extension);
+            }
+            if let Some(extension) = self.extension(scheme & 0x03)? {
+                self.modal.path_end_extension = Some(extension);
+            }
+        }
+        if info & 0x20 != 0 {
+            self.modal.path_points = Some(self.point_list(false)?);
+        }
+        let half_width = self.modal.path_half_width.ok_or(OasisReadError::UndefinedModal("path-halfwidth"))?;
+        let start = self.modal.path_start_extension.ok_or(OasisReadError::UndefinedModal("path-start-extension"))?;
+        let end = self.modal.path_end_extension.ok_or(OasisReadError::UndefinedModal("path-end-extension"))?;
+        let deltas = self.modal.path_points.clone().ok_or(OasisReadError::UndefinedModal("path-point-list"))?;
+        let (x, y) = self.read_xy(info & 0x10 != 0, info & 0x08 != 0, Position::Geometry)?;
+        let repetition = self.read_repetition(info & 0x04 != 0)?;
+
+        let width = i32::try_from(half_width * 2).map_err(|_| OasisReadError::OutOfRange(half_width as i128 * 2, "i32"))?;
+        let path_type = match (start, end) {
+            (OasisExtension::Flush, OasisExtension::Flush) => None,
+            (OasisExtension::HalfWidth, OasisExtension::HalfWidth) => Some(GdsPathType::SquareEndExtend),
+            _ => Some(GdsPathType::custom_extend(to_i32(start.value(half_width))?, to_i32(end.value(half_width))?)),
+        };
+
+        let cell = self.cell("PATH")?;
+        let mut targets = vec![];
+        for (dx, dy) in offsets(repetition.as_ref()) {
+            let mut xy = Vec::with_capacity(deltas.len() + 1);
+            xy.push(coord(x + dx, y + dy)?);
+            for &(px, py) in &deltas {
+                xy.push(coord(x + dx + px, y + dy + py)?);
+            }
+            targets.push(cell.structure.element_count());
+            cell.structure.push(GdsPath {
+                layer,
+                data_type,
+                path_type,
+                width: Some(width),
+                xy,
+                ..Default::default()
+            });
+        }
+
+        self.targets = targets;
+        Ok(())
+    }
+
+    fn extension(&mut self, scheme: u64) -> OasisReadResult<Option<OasisExtension>> {
+        Ok(match scheme {
+            1 => Some(OasisExtension::Flush),
+            2 => Some(OasisExtension::HalfWidth),
+            3 => Some(OasisExtension::Explicit(self.sint()?)),
+            _ => None,
+        })
+    }
+
+    fn read_property(&mut self) -> OasisReadResult<()> {
+        let info = self.byte()?;
+        if info & 0x04 != 0 {
+            let name = match info & 0x02 != 0 {
+                true => NameRef::Number(self.uint()?),
+                false => NameRef::Name(self.name()?),
+            };
+            self.modal.property_name = Some(name);
+        }
+        let name = self.modal.property_name.clone().ok_or(OasisReadError::UndefinedModal("last-property-name"))?;
+
+        let values = if info & 0x08 != 0 {
+            self.modal.property_values.clone().ok_or(OasisReadError::UndefinedModal("last-value-list"))?
+        } else {
+            let count = match (info >> 4) as u64 {
+                15 => self.uint()?,
+                count => count,
+            };
+            let values = (0..count).map(|_| self.property_value()).collect::<OasisReadResult<Vec<_>>>()?;
+            self.modal.property_values = Some(values.clone());
+            values
+        };
+
+        self.attach_property(Property { name, values });
+        Ok(())
+    }
+
+    fn property_value(&mut self) -> OasisReadResult<PropertyValue> {
+        let tp = self.uint()?;
+        Ok(match tp {
+            0..=7 => PropertyValue::Real(self.real_of(tp)?),
+            8 => PropertyValue::Unsigned(self.uint()?),
+            9 => PropertyValue::Signed(self.sint()?),
+            10..=12 => PropertyValue::String(self.string()?),
+            13..=15 => PropertyValue::Reference(self.uint()?),
+            _ => return Err(OasisReadError::InvalidType("property value", tp)),
+        })
+    }
+
+    fn attach_property(&mut self, property: Property) {
+        if let Some(cell) = self.cells.last_mut() {
+            for &index in &self.targets {
+                cell.properties.push((index, property.clone()));
+            }
+        }
+    }
+
+    fn add_boundaries(&mut self, layer: i16, data_type: i16, points: &[(i64, i64)], repetition: Option<&OasisRepetition>) -> OasisReadResult<()> {
+        let cell = self.cell("geometry")?;
+        let mut targets = vec![];
+        for (dx, dy) in offsets(repetition) {
+            let xy = points.iter().map(|&(x, y)| coord(x + dx, y + dy)).collect::<OasisReadResult<Vec<_>>>()?;
+            targets.push(cell.structure.element_count());
+            cell.structure.push(GdsBoundary {
+                elf_flags: None,
+                plex: None,
+                layer,
+                data_type,
+                xy,
+                properties: vec![],
+                raw_records: vec![],
+            });
+        }
+        self.targets = targets;
+        Ok(())
+    }
+
+    fn cell(&mut self, record: &'static str) -> OasisReadResult<&mut Cell> {
+        self.cells.last_mut().ok_or(OasisReadError::UnexpectRecord(record))
+    }
+
+    fn read_layer(&mut self, info: u8) -> OasisReadResult<(i16, i16)> {
+        if info & 0x01 != 0 {
+            self.modal.layer = Some(self.uint()?);
+        }
+        if info & 0x02 != 0 {
+            self.modal.datatype = Some(self.uint()?);
+        }
+        let layer = self.modal.layer.ok_or(OasisReadError::UndefinedModal("layer"))?;
+        let datatype = self.modal.datatype.ok_or(OasisReadError::UndefinedModal("datatype"))?;
+        Ok((to_i16(layer)?, to_i16(datatype)?))
+    }
+
+    fn read_xy(&mut self, has_x: bool, has_y: bool, position: Position) -> OasisReadResult<(i64, i64)> {
+        let x = if has_x { Some(self.sint()?) } else { None };
+        let y = if has_y { Some(self.sint()?) } else { None };
+        let relative = self.modal.relative;
+        let current = match position {
+            Position::Placement => &mut self.modal.placement_xy,
+            Position::Text => &mut self.modal.text_xy,
+            Position::Geometry => &mut self.modal.geometry_xy,
+        };
+        let update = |modal: &mut i64, value: Option<i64>| match value {
+            Some(value) if relative => *modal += value,
+            Some(value) => *modal = value,
+            None => {}
+        };
+        update(&mut current.0, x);
+        update(&mut current.1, y);
+        Ok(*current)
+    }
+
+    fn read_repetition(&mut self, present: bool) -> OasisReadResult<Option<OasisRepetition>> {
+        if !present {
+            return Ok(None);
+        }
+        let tp = self.uint()?;
+        let repetition = match tp {
+            0 => self.modal.repetition.clone().ok_or(OasisReadError::UndefinedModal("repetition"))?,
+            1 => {
+                let cols = self.uint()? + 2;
+                let rows = self.uint()? + 2;
+                let col_pitch = (self.uint()? as i64, 0);
+                let row_pitch = (0, self.uint()? as i64);
+                OasisRepetition::Lattice { cols, rows, col_pitch, row_pitch }
+            }
+            2 => {
+                let cols = self.uint()? + 2;
+                let col_pitch = (self.uint()? as i64, 0);
+                OasisRepetition::Lattice { cols, rows: 1, col_pitch, row_pitch: (0, 0) }
+            }
+            3 => {
+                let rows = self.uint()? + 2;
+                let row_pitch = (0, self.uint()? as i64);
+                OasisRepetition::Lattice { cols: 1, rows, col_pitch: (0, 0), row_pitch }
+            }
+            4..=7 => {
+                let count = self.uint()? + 2;
+                let grid = if tp == 5 || tp == 7 { self.uint()? as i64 } else { 1 };
+                let mut offsets = vec![(0, 0)];
+                let mut offset = 0;
+                for _ in 1..count {
+                    offset += self.uint()? as i64 * grid;
+                    offsets.push(if tp <= 5 { (offset, 0) } else { (0, offset) });
+                }
+                OasisRepetition::Offsets(offsets)
+            }
+            8 => {
+                let cols = self.uint()? + 2;
+                let rows = self.uint()? + 2;
+                let col_pitch = self.g_delta()?;
+                let row_pitch = self.g_delta()?;
+                OasisRepetition::Lattice { cols, rows, col_pitch, row_pitch }
+            }
+            9 => {
+                let cols = self.uint()? + 2;
+                let col_pitch = self.g_delta()?;
+                OasisRepetition::Lattice { cols, rows: 1, col_pitch, row_pitch: (0, 0) }
+            }
+            10 | 11 => {
+                let count = self.uint()? + 2;
+                let grid = if tp == 11 { self.uint()? as i64 } else { 1 };
+                let mut offsets = vec![(0, 0)];
+                let (mut x, mut y) = (0, 0);
+                for _ in 1..count {
+                    let (dx, dy) = self.g_delta()?;
+                    x += dx * grid;
+                    y += dy * grid;
+                    offsets.push((x, y));
+                }
+                OasisRepetition::Offsets(offsets)
+            }
+            _ => return Err(OasisReadError::InvalidType("repetition", tp)),
+        };
+        self.modal.repetition = Some(repetition.clone());
+        Ok(Some(repetition))
+    }
+
+    /// Points after the first one, relative to it. Polygon lists of
+    /// type 0 and 1 get their implicit last point.
+    fn point_list(&mut self, polygon: bool) -> OasisReadResult<Vec<(i64, i64)>> {
+        let tp = self.uint()?;
+        let count = self.uint()?;
+        let mut points = Vec::with_capacity(count.min(1 << 16) as usize + 1);
+        let (mut x, mut y) = (0i64, 0i64);
+        match tp {
+            0 | 1 => {
+                let mut horizontal = tp == 0;
+                for _ in 0..count {
+                    let delta = self.sint()?;
+                    if horizontal { x += delta } else { y += delta }
+                    points.push((x, y));
+                    horizontal = !horizontal;
+                }
+                if polygon {
+                    points.push(if horizontal { (0, y) } else { (x, 0) });
+                }
+            }
+            2 | 3 => {
+                let bits = if tp == 2 { 2 } else { 3 };
+                for _ in 0..count {
+                    let delta = self.uint()?;
+                    let (dx, dy) = DIRECTIONS[(delta & ((1 << bits) - 1)) as usize];
+                    let magnitude = (delta >> bits) as i64;
+                    x += dx * magnitude;
+                    y += dy * magnitude;
+                    points.push((x, y));
+                }
+            }
+            4 | 5 => {
+                let (mut dx, mut dy) = (0i64, 0i64);
+                for _ in 0..count {
+                    let delta = self.g_delta()?;
+                    if tp == 4 {
+                        (dx, dy) = delta;
+                    } else {
+                        dx += delta.0;
+                        dy += delta.1;
+                    }
+                    x += dx;
+                    y += dy;
+                    points.push((x, y));
+                }
+            }
+            _ => return Err(OasisReadError::InvalidType("point list", tp)),
+        }
+        Ok(points)
+    }
+
+    fn g_delta(&mut self) -> OasisReadResult<(i64, i64)> {
+        let value = self.uint()?;
+        if value & 0x01 == 0 {
+            let (dx, dy) = DIRECTIONS[((value >> 1) & 0x07) as usize];
+            let magnitude = (value >> 4) as i64;
+            Ok((dx * magnitude, dy * magnitude))
+        } else {
+            let magnitude = (value >> 2) as i64;
+            let x = if value & 0x02 != 0 { -magnitude } else { magnitude };
+            Ok((x, self.sint()?))
+        }
+    }
+
+    fn interval(&mut self) -> OasisReadResult<()> {
+        let tp = self.uint()?;
+        let bounds = match tp {
+            0 => 0,
+            1..=3 => 1,
+            4 => 2,
+            _ => return Err(OasisReadError::InvalidType("interval", tp)),
+        };
+        for _ in 0..bounds {
+            self.uint()?;
+        }
+        Ok(())
+    }
+
+    fn real(&mut self) -> OasisReadResult<f64> {
+        let tp = self.uint()?;
+        self.real_of(tp)
+    }
+
+    fn real_of(&mut self, tp: u64) -> OasisReadResult<f64> {
+        Ok(match tp {
+            0 => self.uint()? as f64,
+            1 => -(self.uint()? as f64),
+            2 => 1.0 / self.uint()? as f64,
+            3 => -1.0 / self.uint()? as f64,
+            4 => self.uint()? as f64 / self.uint()? as f64,
+            5 => -(self.uint()? as f64 / self.uint()? as f64),
+            6 => f32::from_le_bytes(self.array()?) as f64,
+            7 => f64::from_le_bytes(self.array()?),
+            _ => return Err(OasisReadError::InvalidType("real", tp)),
+        })
+    }
+
+    fn name(&mut self) -> OasisReadResult<String> {
+        Ok(String::from_utf8(self.string()?)?)
+    }
+
+    fn string(&mut self) -> OasisReadResult<Vec<u8>> {
+        let len = self.uint()?;
+        (0..len).map(|_| self.byte()).collect()
+    }
+
+    fn sint(&mut self) -> OasisReadResult<i64> {
+        let value = self.uint()?;
+        let magnitude = (value >> 1) as i64;
+        Ok(if value & 0x01 != 0 { -magnitude } else { magnitude })
+    }
+
+    fn uint(&mut self) -> OasisReadResult<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> OasisReadResult<[u8; N]> {
+        let mut bytes = [0u8; N];
+        for byte in bytes.iter_mut() {
+            *byte = self.byte()?;
+        }
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> OasisReadResult<u8> {
+        if let Some((block, offset)) = &mut self.block {
+            if let Some(&byte) = block.get(*offset) {
+                *offset += 1;
+                return Ok(byte);
+            }
+            self.block = None;
+        }
+        let byte = *self.data.get(self.offset).ok_or(OasisReadError::UnexpectEof(self.offset))?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn build(mut self, name: String) -> OasisReadResult<GdsLibrary> {
+        let mut structures = HashMap::new();
+        let mut structure_order = vec![];
+
+        for cell in std::mem::take(&mut self.cells) {
+            let mut structure = cell.structure;
+            structure.name = self.cell_names.resolve("cell name", cell.name)?;
+
+            for (index, number) in cell.placement_refs {
+                let s_name = self.cell_names.get("cell name", number)?;
+                match structure.element_mut(index) {
+                    Some(GdsElementMut::Sref(sref)) => sref.s_name = s_name,
+                    Some(GdsElementMut::Aref(aref)) => aref.s_name = s_name,
+                    _ => {}
+                }
+            }
+            for (index, number) in cell.text_refs {
+                if let Some(GdsElementMut::Text(text)) = structure.element_mut(index) {
+                    text.string = self.text_strings.get("text string", number)?;
+                }
+            }
+            for (index, property) in cell.properties {
+                if let Some(property) = self.gds_property(property)? {
+                    if let Some(mut element) = structure.element_mut(index) {
+                        element.properties_mut().push(property);
+                    }
+                }
+            }
+
+            let name = structure.name.clone();
+            if structures.insert(name.clone(), Arc::new(RwLock::new(structure))).is_none() {
+                structure_order.push(name);
+            }
+        }
+
+        let usrunits_per_dbunit = 1.0 / self.unit;
+        let now = GdsDateTime::now();
+        let library = GdsLibraryBuilder::default()
+            .version(600)
+            .name(name)
+            .create_date(now.clone())
+            .modify_date(now)
+            .usrunits_per_dbunit(usrunits_per_dbunit)
+            .meters_per_dbunit(usrunits_per_dbunit * 1e-6)
+            .structures(structures)
+            .structure_order(structure_order)
+            .build()?;
+        Ok(library)
+    }
+
+    /// The PROPATTR / PROPVALUE pair of an `S_GDS_PROPERTY`, `None` for other properties.
+    fn gds_property(&self, property: Property) -> OasisReadResult<Option<GdsProperty>> {
+        if self.property_names.resolve("property name", property.name)? != S_GDS_PROPERTY {
+            return Ok(None);
+        }
+        let attr = match property.values.first() {
+            Some(PropertyValue::Unsigned(attr)) => *attr as i128,
+            Some(PropertyValue::Signed(attr)) => *attr as i128,
+            Some(PropertyValue::Real(attr)) if attr.fract() == 0.0 => *attr as i128,
+            _ => return Ok(None),
+        };
+        let attr = i16::try_from(attr).map_err(|_| OasisReadError::OutOfRange(attr, "i16"))?;
+        let value = match property.values.get(1) {
+            Some(PropertyValue::String(value)) => String::from_utf8(value.clone())?,
+            Some(PropertyValue::Reference(number)) => self.property_strings.get("property string", *number)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(GdsProperty::new(attr, value)))
+    }
+}
+
+/// Closed outline of `corners` placed at `(x, y)`, without the vertices
+/// that coincide when a trapezoid degenerates into a triangle
+fn polygon(x: i64, y: i64, corners: impl IntoIterator<Item = (i64, i64)>) -> Vec<(i64, i64)> {
+    let mut points: Vec<_> = corners.into_iter().map(|(dx, dy)| (x + dx, y + dy)).collect();
+    points.dedup();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points.push(points[0]);
+    points
+}
+
+fn offsets(repetition: Option<&OasisRepetition>) -> Vec<(i64, i64)> {
+    repetition.map(|r| r.offsets()).unwrap_or_else(|| vec![(0, 0)])
+}
+
+fn coord(x: i64, y: i64) -> OasisReadResult<GdsCoord> {
+    Ok(GdsCoord::new(to_i32(x)?, to_i32(y)?))
+}
+
+fn to_i32(value: i64) -> OasisReadResult<i32> {
+    i32::try_from(value).map_err(|_| OasisReadError::OutOfRange(value as i128, "i32"))
+}
+
+fn to_i16(value: u64) -> OasisReadResult<i16> {
+    i16::try_from(value).map_err(|_| OasisReadError::OutOfRange(value as i128, "i16"))
+}
@@ -0,0 +1,136 @@
+//! Record ids and shared definitions of the OASIS stream format (SEMI P39).
+
+pub(super) const MAGIC: &[u8] = b"%SEMI-OASIS\r\n";
+pub(super) const VERSION: &str = "1.0";
+
+/// The END record, validation included, is always this long
+pub(super) const END_RECORD_SIZE: usize = 256;
+
+/// Standard property carrying a GDSII PROPATTR / PROPVALUE pair
+pub(super) const S_GDS_PROPERTY: &str = "S_GDS_PROPERTY";
+
+pub(super) const PAD: u64 = 0;
+pub(super) const START: u64 = 1;
+pub(super) const END: u64 = 2;
+pub(super) const CELLNAME: u64 = 3;
+pub(super) const CELLNAME_REF: u64 = 4;
+pub(super) const TEXTSTRING: u64 = 5;
+pub(super) const TEXTSTRING_REF: u64 = 6;
+pub(super) const PROPNAME: u64 = 7;
+pub(super) const PROPNAME_REF: u64 = 8;
+pub(super) const PROPSTRING: u64 = 9;
+pub(super) const PROPSTRING_REF: u64 = 10;
+pub(super) const LAYERNAME: u64 = 11;
+pub(super) const LAYERNAME_TEXT: u64 = 12;
+pub(super) const CELL_REF: u64 = 13;
+pub(super) const CELL: u64 = 14;
+pub(super) const XYABSOLUTE: u64 = 15;
+pub(super) const XYRELATIVE: u64 = 16;
+pub(super) const PLACEMENT: u64 = 17;
+pub(super) const PLACEMENT_TRANSFORM: u64 = 18;
+pub(super) const TEXT: u64 = 19;
+pub(super) const RECTANGLE: u64 = 20;
+pub(super) const POLYGON: u64 = 21;
+pub(super) const PATH: u64 = 22;
+pub(super) const TRAPEZOID: u64 = 23;
+pub(super) const TRAPEZOID_A: u64 = 24;
+pub(super) const TRAPEZOID_B: u64 = 25;
+pub(super) const CTRAPEZOID: u64 = 26;
+pub(super) const CIRCLE: u64 = 27;
+pub(super) const PROPERTY: u64 = 28;
+pub(super) const PROPERTY_REPEAT: u64 = 29;
+pub(super) const XNAME: u64 = 30;
+pub(super) const XNAME_REF: u64 = 31;
+pub(super) const XELEMENT: u64 = 32;
+pub(super) const XGEOMETRY: u64 = 33;
+pub(super) const CBLOCK: u64 = 34;
+
+/// Unit vectors of the octangular directions, in the order of their codes:
+/// east, north, west, south, northeast, northwest, southwest, southeast
+pub(super) const DIRECTIONS: [(i64, i64); 8] = [
+    (1, 0), (0, 1), (-1, 0), (0, -1), (1, 1), (-1, 1), (-1, -1), (1, -1),
+];
+
+/// Vertices of the CTRAPEZOID of each type, in the same turn as the vertices of a
+/// RECTANGLE. A vertex `(xw, xh, yw, yh)` lies at `(xw * w + xh * h, yw * w + yh * h)`.
+pub(super) const CTRAPEZOIDS: [&[(i64, i64, i64, i64)]; 26] = [
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, -1, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, -1, 0, 0)],
+    &[(0, 0, 0, 0), (0, 1, 0, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 1, 0, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 1, 0, 1), (1, -1, 0, 1), (1, 0, 0, 0)],
+    &[(0, 1, 0, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, -1, 0, 0)],
+    &[(0, 0, 0, 0), (0, 1, 0, 1), (1, 0, 0, 1), (1, -1, 0, 0)],
+    &[(0, 1, 0, 0), (0, 0, 0, 1), (1, -1, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, 0, -1, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, -1, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, 0, 1, 0)],
+    &[(0, 0, 1, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, 0, -1, 1), (1, 0, 1, 0)],
+    &[(0, 0, 1, 0), (0, 0, -1, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, -1, 1), (1, 0, 0, 1), (1, 0, 1, 0)],
+    &[(0, 0, 1, 0), (0, 0, 0, 1), (1, 0, -1, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 1, 0), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 1, 0), (1, 0, 1, 0)],
+    &[(0, 0, 0, 0), (1, 0, 1, 0), (1, 0, 0, 0)],
+    &[(0, 0, 1, 0), (1, 0, 1, 0), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 1, 0, 1), (0, 2, 0, 0)],
+    &[(0, 0, 0, 1), (0, 2, 0, 1), (0, 1, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 2, 0), (1, 0, 1, 0)],
+    &[(1, 0, 0, 0), (0, 0, 1, 0), (1, 0, 2, 0)],
+    &[(0, 0, 0, 0), (0, 0, 0, 1), (1, 0, 0, 1), (1, 0, 0, 0)],
+    &[(0, 0, 0, 0), (0, 0, 1, 0), (1, 0, 1, 0), (1, 0, 0, 0)],
+];
+
+/// Where the copies of a repeated element go, relative to the element itself.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum OasisRepetition {
+    /// `cols` by `rows` copies on the lattice spanned by the two pitches
+    Lattice {
+        cols: u64,
+        rows: u64,
+        col_pitch: (i64, i64),
+        row_pitch: (i64, i64),
+    },
+    /// Displacements of every copy, the first one being `(0, 0)`
+    Offsets(Vec<(i64, i64)>),
+}
+
+impl OasisRepetition {
+    /// Displacements of every copy, row by row for a lattice.
+    pub(super) fn offsets(&self) -> Vec<(i64, i64)> {
+        match self {
+            Self::Lattice { cols, rows, col_pitch, row_pitch } => {
+                let mut offsets = Vec::with_capacity((cols * rows) as usize);
+                for row in 0..*rows as i64 {
+                    for col in 0..*cols as i64 {
+                        offsets.push((
+                            col * col_pitch.0 + row * row_pitch.0,
+                            col * col_pitch.1 + row * row_pitch.1,
+                        ));
+                    }
+                }
+                offsets
+            }
+            Self::Offsets(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// Path extension at either end, as in the extension-scheme of a PATH record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum OasisExtension {
+    Flush,
+    HalfWidth,
+    Explicit(i64),
+}
+
+impl OasisExtension {
+    pub(super) fn value(self, half_width: u64) -> i64 {
+        match self {
+            Self::Flush => 0,
+            Self::HalfWidth => half_width as i64,
+            Self::Explicit(value) => value,
+        }
+    }
+}
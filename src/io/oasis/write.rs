@@ -0,0 +1,558 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use flate2::{Compression, Crc};
+use flate2::write::DeflateEncoder;
+
+use crate::{GdsAref, GdsBoundary, GdsBox, GdsCoord, GdsElementRef, GdsLibrary, GdsPath, GdsPathType, GdsProperty, GdsSref, GdsStructure, GdsText, GdsTransform};
+use super::record::*;
+use super::{OasisWriteError, OasisWriteResult};
+
+/// Writes a library as OASIS.
+///
+/// Boundaries that are axis-aligned rectangles become RECTANGLE records and other
+/// boundaries POLYGON records; boxes are written as the rectangle around their points.
+/// Arefs become placements with a repetition, and element properties become
+/// `S_GDS_PROPERTY` properties. OASIS has no nodes, no text presentation or transform
+/// and no round path ends: nodes are left out, texts lose their style and round-ended
+/// paths are extended by half their width. Path widths are rounded down to even.
+pub struct OasisWriter<W> {
+    writer: W,
+    /// CRC32 of everything written, for the validation of the END record
+    crc: Crc,
+    /// Wrap each cell in a deflate-compressed CBLOCK
+    compress: bool,
+}
+
+impl OasisWriter<BufWriter<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> OasisWriteResult<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> OasisWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, crc: Crc::new(), compress: false }
+    }
+
+    /// Compress the records of each cell into a CBLOCK.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn write(&mut self, library: &GdsLibrary) -> OasisWriteResult<()> {
+        self.crc = Crc::new();
+        let structures = library.ordered_structures();
+
+        // Cells are referenced by number, defined ones first in write order
+        let mut cell_names: HashMap<String, u64> = HashMap::new();
+        let mut undefined = BTreeSet::new();
+        for structure in &structures {
+            let structure = structure.read().unwrap();
+            let number = cell_names.len() as u64;
+            cell_names.insert(structure.name.clone(), number);
+            undefined.extend(structure.srefs().map(|s| s.s_name.clone()));
+            undefined.extend(structure.arefs().map(|a| a.s_name.clone()));
+        }
+        for name in undefined {
+            let number = cell_names.len() as u64;
+            cell_names.entry(name).or_insert(number);
+        }
+        let mut names: Vec<(&String, &u64)> = cell_names.iter().collect();
+        names.sort_by_key(|(_, &number)| number);
+
+        let mut buf = MAGIC.to_vec();
+        put_uint(&mut buf, START);
+        put_string(&mut buf, VERSION.as_bytes());
+        put_real(&mut buf, grid_steps_per_micron(library.meters_per_dbunit));
+        // Table offsets in START, all of them unknown
+        put_uint(&mut buf, 0);
+        for _ in 0..12 {
+            put_uint(&mut buf, 0);
+        }
+        put_uint(&mut buf, PROPNAME);
+        put_string(&mut buf, S_GDS_PROPERTY.as_bytes());
+        for (name, _) in names {
+            put_uint(&mut buf, CELLNAME);
+            put_string(&mut buf, name.as_bytes());
+        }
+        self.emit(&buf)?;
+
+        for structure in structures {
+            buf.clear();
+            CellWriter::new(&mut buf, &cell_names).write(&structure.read().unwrap())?;
+            if self.compress {
+                self.emit(&cblock(&buf)?)?;
+            } else {
+                self.emit(&buf)?;
+            }
+        }
+
+        self.write_end()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// END with padding up to its fixed size and a CRC32 validation.
+    fn write_end(&mut self) -> OasisWriteResult<()> {
+        const VALIDATION_CRC32: u64 = 1;
+
+        let mut buf = vec![];
+        put_uint(&mut buf, END);
+        // The padding string and its length prefix fill what the id, scheme and signature leave
+        let room = END_RECORD_SIZE - buf.len() - 1 - 4;
+        let mut padding = room - 1;
+        while uint_size(padding as u64) + padding > room {
+            padding -= 1;
+        }
+        put_string(&mut buf, &vec![0u8; padding]);
+        put_uint(&mut buf, VALIDATION_CRC32);
+        self.emit(&buf)?;
+
+        let signature = self.crc.sum().to_le_bytes();
+        self.emit(&signature)
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> OasisWriteResult<()> {
+        self.crc.update(bytes);
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+/// Writes the records of one cell, keeping the modal variables it relies on.
+struct CellWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    cell_names: &'a HashMap<String, u64>,
+    layer: Option<u64>,
+    datatype: Option<u64>,
+    textlayer: Option<u64>,
+    texttype: Option<u64>,
+    placement_cell: Option<u64>,
+}
+
+impl<'a> CellWriter<'a> {
+    fn new(buf: &'a mut Vec<u8>, cell_names: &'a HashMap<String, u64>) -> Self {
+        Self { buf, cell_names, layer: None, datatype: None, textlayer: None, texttype: None, placement_cell: None }
+    }
+
+    fn write(mut self, structure: &GdsStructure) -> OasisWriteResult<()> {
+        put_uint(self.buf, CELL_REF);
+        put_uint(self.buf, self.cell_names[&structure.name]);
+
+        for element in structure.elements() {
+            match element {
+                GdsElementRef::Boundary(boundary) => self.write_boundary(boundary)?,
+                GdsElementRef::Path(path) => self.write_path(path)?,
+                GdsElementRef::Sref(sref) => self.write_sref(sref)?,
+                GdsElementRef::Aref(aref) => self.write_aref(aref)?,
+                GdsElementRef::Text(text) => self.write_text(text)?,
+                GdsElementRef::Box(boxx) => self.write_box(boxx)?,
+                GdsElementRef::Node(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn write_boundary(&mut self, boundary: &GdsBoundary) -> OasisWriteResult<()> {
+        let mut points = boundary.xy.as_slice();
+        if let [first, .., last] = points {
+            if (first.x, first.y) == (last.x, last.y) {
+                points = &points[..points.len() - 1];
+            }
+        }
+        if points.is_empty() {
+            return Ok(());
+        }
+
+        match rectangle(points) {
+            Some((lower_left, upper_right)) => self.write_rectangle(boundary.layer, boundary.data_type, lower_left, upper_right)?,
+            None => {
+                let info = 0x20 | 0x18 | self.layer_bits(boundary.layer, boundary.data_type)?;
+                put_uint(self.buf, POLYGON);
+                self.buf.push(info);
+                self.put_layer(boundary.layer, boundary.data_type)?;
+                put_point_list(self.buf, points);
+                put_sint(self.buf, points[0].x as i64);
+                put_sint(self.buf, points[0].y as i64);
+            }
+        }
+        self.write_properties(&boundary.properties)
+    }
+
+    fn write_box(&mut self, boxx: &GdsBox) -> OasisWriteResult<()> {
+        let Some(first) = boxx.xy.first() else {
+            return Ok(());
+        };
+        let (mut lower_left, mut upper_right) = (*first, *first);
+        for p in &boxx.xy {
+            lower_left = GdsCoord::new(lower_left.x.min(p.x), lower_left.y.min(p.y));
+            upper_right = GdsCoord::new(upper_right.x.max(p.x), upper_right.y.max(p.y));
+        }
+        self.write_rectangle(boxx.layer, boxx.box_type, lower_left, upper_right)?;
+        self.write_properties(&boxx.properties)
+    }
+
+    fn write_rectangle(&mut self, layer: i16, data_type: i16, lower_left: GdsCoord, upper_right: GdsCoord) -> OasisWriteResult<()> {
+        let width = (upper_right.x as i64 - lower_left.x as i64) as u64;
+        let height = (upper_right.y as i64 - lower_left.y as i64) as u64;
+        let mut info = 0x40 | 0x18 | self.layer_bits(layer, data_type)?;
+        info |= if width == height { 0x80 } else { 0x20 };
+
+        put_uint(self.buf, RECTANGLE);
+        self.buf.push(info);
+        self.put_layer(layer, data_type)?;
+        put_uint(self.buf, width);
+        if width != height {
+            put_uint(self.buf, height);
+        }
+        put_sint(self.buf, lower_left.x as i64);
+        put_sint(self.buf, lower_left.y as i64);
+        Ok(())
+    }
+
+    fn write_path(&mut self, path: &GdsPath) -> OasisWriteResult<()> {
+        let Some(first) = path.xy.first() else {
+            return Ok(());
+        };
+        let (start, end) = match path.path_type() {
+            GdsPathType::SquareEnd => (OasisExtension::Flush, OasisExtension::Flush),
+            GdsPathType::RoundEnd | GdsPathType::SquareEndExtend => (OasisExtension::HalfWidth, OasisExtension::HalfWidth),
+            GdsPathType::CustomExtend { .. } => {
+                let (begin_extension, end_extension) = path.extensions();
+                (OasisExtension::Explicit(begin_extension as i64), OasisExtension::Explicit(end_extension as i64))
+            }
+        };
+        let scheme = |extension: OasisExtension| match extension {
+            OasisExtension::Flush => 1,
+            OasisExtension::HalfWidth => 2,
+            OasisExtension::Explicit(_) => 3,
+        };
+
+        let info = 0x80 | 0x40 | 0x20 | 0x18 | self.layer_bits(path.layer, path.data_type)?;
+        put_uint(self.buf, PATH);
+        self.buf.push(info);
+        self.put_layer(path.layer, path.data_type)?;
+        put_uint(self.buf, (path.width().unsigned_abs() / 2) as u64);
+        put_uint(self.buf, (scheme(start) << 2) | scheme(end));
+        for extension in [start, end] {
+            if let OasisExtension::Explicit(value) = extension {
+                put_sint(self.buf, value);
+            }
+        }
+        put_point_list(self.buf, &path.xy);
+        put_sint(self.buf, first.x as i64);
+        put_sint(self.buf, first.y as i64);
+        self.write_properties(&path.properties)
+    }
+
+    fn write_sref(&mut self, sref: &GdsSref) -> OasisWriteResult<()> {
+        self.write_placement(&sref.s_name, sref.transform, sref.position, None)?;
+        self.write_properties(&sref.properties)
+    }
+
+    fn write_aref(&mut self, aref: &GdsAref) -> OasisWriteResult<()> {
+        let cols = aref.col.max(1) as u64;
+        let rows = aref.row.max(1) as u64;
+        let repetition = (cols * rows > 1).then(|| {
+            let col_pitch = aref.col_pitch();
+            let row_pitch = aref.row_pitch();
+            OasisRepetition::Lattice {
+                cols,
+                rows,
+                col_pitch: (col_pitch.x as i64, col_pitch.y as i64),
+                row_pitch: (row_pitch.x as i64, row_pitch.y as i64),
+            }
+        });
+        self.write_placement(&aref.s_name, aref.transform, aref.position, repetition.as_ref())?;
+        self.write_properties(&aref.properties)
+    }
+
+    fn write_placement(&mut self, s_name: &str, transform: Option<GdsTransform>, position: GdsCoord, repetition: Option<&OasisRepetition>) -> OasisWriteResult<()> {
+        let cell = self.cell_names[s_name];
+        let reflect = transform.is_some_and(|t| t.flag.reflect);
+        let magnification = transform.map(|t| t.magnification()).unwrap_or(1.0);
+        let angle = transform.map(|t| t.angle()).unwrap_or(0.0);
+
+        let mut info = 0x20 | 0x10;
+        if self.placement_cell != Some(cell) {
+            info |= 0xc0;
+        }
+        if repetition.is_some() {
+            info |= 0x08;
+        }
+        if reflect {
+            info |= 0x01;
+        }
+
+        let quadrants = angle / 90.0;
+        let manhattan = magnification == 1.0 && quadrants.fract() == 0.0;
+        if manhattan {
+            info |= ((quadrants as i64).rem_euclid(4) as u8) << 1;
+            put_uint(self.buf, PLACEMENT);
+        } else {
+            if magnification != 1.0 {
+                info |= 0x04;
+            }
+            if angle != 0.0 {
+                info |= 0x02;
+            }
+            put_uint(self.buf, PLACEMENT_TRANSFORM);
+        }
+        self.buf.push(info);
+        if info & 0x80 != 0 {
+            put_uint(self.buf, cell);
+            self.placement_cell = Some(cell);
+        }
+        if !manhattan {
+            if info & 0x04 != 0 {
+                put_real(self.buf, magnification);
+            }
+            if info & 0x02 != 0 {
+                put_real(self.buf, angle);
+            }
+        }
+        put_sint(self.buf, position.x as i64);
+        put_sint(self.buf, position.y as i64);
+        if let Some(repetition) = repetition {
+            put_repetition(self.buf, repetition);
+        }
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &GdsText) -> OasisWriteResult<()> {
+        let textlayer = to_u64(text.layer, "text layer")?;
+        let texttype = to_u64(text.text_type, "texttype")?;
+        let mut info = 0x40 | 0x18;
+        if self.textlayer != Some(textlayer) {
+            info |= 0x01;
+        }
+        if self.texttype != Some(texttype) {
+            info |= 0x02;
+        }
+
+        put_uint(self.buf, TEXT);
+        self.buf.push(info);
+        put_string(self.buf, text.string.as_bytes());
+        if info & 0x01 != 0 {
+            put_uint(self.buf, textlayer);
+            self.textlayer = Some(textlayer);
+        }
+        if info & 0x02 != 0 {
+            put_uint(self.buf, texttype);
+            self.texttype = Some(texttype);
+        }
+        put_sint(self.buf, text.position.x as i64);
+        put_sint(self.buf, text.position.y as i64);
+        self.write_properties(&text.properties)
+    }
+
+    /// Each property as `S_GDS_PROPERTY`, whose name is reference number 0.
+    fn write_properties(&mut self, properties: &[GdsProperty]) -> OasisWriteResult<()> {
+        for property in properties {
+            // Two values, explicit name by reference number, standard property
+            put_uint(self.buf, PROPERTY);
+            self.buf.push(0x20 | 0x04 | 0x02 | 0x01);
+            put_uint(self.buf, 0);
+            if property.attr >= 0 {
+                put_uint(self.buf, 8);
+                put_uint(self.buf, property.attr as u64);
+            } else {
+                put_uint(self.buf, 9);
+                put_sint(self.buf, property.attr as i64);
+            }
+            put_uint(self.buf, 11);
+            put_string(self.buf, property.value.as_bytes());
+        }
+        Ok(())
+    }
+
+    /// The L and D bits for the layer and datatype differing from the modal ones.
+    fn layer_bits(&self, layer: i16, data_type: i16) -> OasisWriteResult<u8> {
+        let mut bits = 0;
+        if self.layer != Some(to_u64(layer, "layer")?) {
+            bits |= 0x01;
+        }
+        if self.datatype != Some(to_u64(data_type, "datatype")?) {
+            bits |= 0x02;
+        }
+        Ok(bits)
+    }
+
+    /// Layer and datatype where they differ from the modal ones, after the info byte from `layer_bits`.
+    fn put_layer(&mut self, layer: i16, data_type: i16) -> OasisWriteResult<()> {
+        let layer = to_u64(layer, "layer")?;
+        let datatype = to_u64(data_type, "datatype")?;
+        if self.layer != Some(layer) {
+            put_uint(self.buf, layer);
+            self.layer = Some(layer);
+        }
+        if self.datatype != Some(datatype) {
+            put_uint(self.buf, datatype);
+            self.datatype = Some(datatype);
+        }
+        Ok(())
+    }
+}
+
+/// Lower left and upper right corner if the points are an axis-aligned rectangle.
+fn rectangle(points: &[GdsCoord]) -> Option<(GdsCoord, GdsCoord)> {
+    if points.len() != 4 {
+        return None;
+    }
+    let min_x = points.iter().map(|p| p.x).min()?;
+    let min_y = points.iter().map(|p| p.y).min()?;
+    let max_x = points.iter().map(|p| p.x).max()?;
+    let max_y = points.iter().map(|p| p.y).max()?;
+    if min_x == max_x || min_y == max_y {
+        return None;
+    }
+    let corners = points.iter().all(|p| (p.x == min_x || p.x == max_x) && (p.y == min_y || p.y == max_y));
+    let manhattan = (0..4).all(|i| {
+        let (a, b) = (points[i], points[(i + 1) % 4]);
+        (a.x == b.x) != (a.y == b.y)
+    });
+    (corners && manhattan).then(|| (GdsCoord::new(min_x, min_y), GdsCoord::new(max_x, max_y)))
+}
+
+fn cblock(records: &[u8]) -> OasisWriteResult<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+    encoder.write_all(records)?;
+    let compressed = encoder.finish()?;
+
+    let mut buf = Vec::with_capacity(compressed.len() + 16);
+    put_uint(&mut buf, CBLOCK);
+    put_uint(&mut buf, 0);
+    put_uint(&mut buf, records.len() as u64);
+    put_uint(&mut buf, compressed.len() as u64);
+    buf.extend_from_slice(&compressed);
+    Ok(buf)
+}
+
+/// OASIS counts database units per micron, GDSII meters per database unit.
+fn grid_steps_per_micron(meters_per_dbunit: f64) -> f64 {
+    let unit = 1e-6 / meters_per_dbunit;
+    let rounded = unit.round();
+    if (unit - rounded).abs() <= 1e-9 * rounded.abs() {
+        rounded
+    } else {
+        unit
+    }
+}
+
+/// Points after the first one as g-deltas from their predecessor.
+fn put_point_list(buf: &mut Vec<u8>, points: &[GdsCoord]) {
+    put_uint(buf, 4);
+    put_uint(buf, points.len().saturating_sub(1) as u64);
+    for pair in points.windows(2) {
+        put_g_delta(buf, (pair[1].x as i64 - pair[0].x as i64, pair[1].y as i64 - pair[0].y as i64));
+    }
+}
+
+fn put_repetition(buf: &mut Vec<u8>, repetition: &OasisRepetition) {
+    match *repetition {
+        OasisRepetition::Lattice { cols, rows, col_pitch, row_pitch } => {
+            let along_x = col_pitch.1 == 0 && col_pitch.0 >= 0;
+            let along_y = row_pitch.0 == 0 && row_pitch.1 >= 0;
+            if cols > 1 && rows > 1 {
+                if along_x && along_y {
+                    put_uint(buf, 1);
+                    put_uint(buf, cols - 2);
+                    put_uint(buf, rows - 2);
+                    put_uint(buf, col_pitch.0 as u64);
+                    put_uint(buf, row_pitch.1 as u64);
+                } else {
+                    put_uint(buf, 8);
+                    put_uint(buf, cols - 2);
+                    put_uint(buf, rows - 2);
+                    put_g_delta(buf, col_pitch);
+                    put_g_delta(buf, row_pitch);
+                }
+            } else if rows == 1 {
+                if along_x {
+                    put_uint(buf, 2);
+                    put_uint(buf, cols - 2);
+                    put_uint(buf, col_pitch.0 as u64);
+                } else {
+                    put_uint(buf, 9);
+                    put_uint(buf, cols - 2);
+                    put_g_delta(buf, col_pitch);
+                }
+            } else if along_y {
+                put_uint(buf, 3);
+                put_uint(buf, rows - 2);
+                put_uint(buf, row_pitch.1 as u64);
+            } else {
+                put_uint(buf, 9);
+                put_uint(buf, rows - 2);
+                put_g_delta(buf, row_pitch);
+            }
+        }
+        OasisRepetition::Offsets(ref offsets) => {
+            put_uint(buf, 10);
+            put_uint(buf, offsets.len().saturating_sub(2) as u64);
+            for pair in offsets.windows(2) {
+                put_g_delta(buf, (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1));
+            }
+        }
+    }
+}
+
+/// Octangular displacements in the short form, others with both coordinates.
+fn put_g_delta(buf: &mut Vec<u8>, (dx, dy): (i64, i64)) {
+    let magnitude = dx.abs().max(dy.abs());
+    let direction = (magnitude == 0)
+        .then_some(0)
+        .or_else(|| DIRECTIONS.iter().position(|&(ux, uy)| (ux * magnitude, uy * magnitude) == (dx, dy)));
+    match direction {
+        Some(direction) => put_uint(buf, ((magnitude as u64) << 4) | ((direction as u64) << 1)),
+        None => {
+            let sign = if dx < 0 { 0x02 } else { 0 };
+            put_uint(buf, (dx.unsigned_abs() << 2) | sign | 0x01);
+            put_sint(buf, dy);
+        }
+    }
+}
+
+/// Integral values exactly, others as a double.
+fn put_real(buf: &mut Vec<u8>, value: f64) {
+    if value.fract() == 0.0 && value.abs() < (1u64 << 53) as f64 {
+        put_uint(buf, if value < 0.0 { 1 } else { 0 });
+        put_uint(buf, value.abs() as u64);
+    } else {
+        put_uint(buf, 7);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_uint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_sint(buf: &mut Vec<u8>, value: i64) {
+    put_uint(buf, (value.unsigned_abs() << 1) | (value < 0) as u64);
+}
+
+fn put_uint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn uint_size(value: u64) -> usize {
+    (64 - value.leading_zeros() as usize).max(1).div_ceil(7)
+}
+
+fn to_u64(value: i16, what: &'static str) -> OasisWriteResult<u64> {
+    u64::try_from(value).map_err(|_| OasisWriteError::OutOfRange(value as i64, what))
+}
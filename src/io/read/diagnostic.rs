@@ -0,0 +1,87 @@
+use std::fmt;
+
+use crate::GdsProfile;
+
+/// Options that control how `GdsReader` deals with malformed input.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadOptions {
+    /// Skip malformed elements and unknown records instead of failing,
+    /// resyncing on the next ENDEL/ENDSTR and collecting `GdsDiagnostic`s.
+    pub recovery: bool,
+    /// Check the library read against this profile, failing with
+    /// `GdsReadError::Invalid`, or with a warning per violation in recovery mode
+    pub validation: Option<GdsProfile>,
+    /// What happens to records of unknown type that no handler claims
+    pub unknown_records: GdsUnknownRecords,
+}
+
+/// What `GdsReader` does with records of a type it does not know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GdsUnknownRecords {
+    /// Keep them raw in the library, structure or element they are found in,
+    /// so they are written back in place
+    #[default]
+    Keep,
+    /// Fail with `GdsReadError::UnsupportRecordType`, or skip them
+    /// with a warning in recovery mode
+    Reject,
+}
+
+impl ReadOptions {
+    pub fn recovery() -> Self {
+        Self { recovery: true, ..Self::default() }
+    }
+
+    pub fn with_validation(mut self, profile: GdsProfile) -> Self {
+        self.validation = Some(profile);
+        self
+    }
+
+    pub fn with_unknown_records(mut self, unknown_records: GdsUnknownRecords) -> Self {
+        self.unknown_records = unknown_records;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GdsSeverity {
+    /// Something was skipped or breaks the validation profile, but the data read is complete
+    Warning,
+    /// Data was dropped or the file is truncated
+    Error,
+}
+
+impl fmt::Display for GdsSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found while reading in recovery mode.
+#[derive(Debug, Clone)]
+pub struct GdsDiagnostic {
+    pub severity: GdsSeverity,
+    /// Byte offset where the record with the problem starts
+    pub offset: u64,
+    /// Structure being read, if any
+    pub structure: Option<String>,
+    /// Index of the element in its structure, in file order
+    pub element_index: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for GdsDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.severity, self.offset)?;
+        if let Some(structure) = &self.structure {
+            write!(f, " in structure '{structure}'")?;
+        }
+        if let Some(index) = self.element_index {
+            write!(f, " element {index}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
@@ -1,10 +1,14 @@
+use std::fmt;
 use std::string::FromUtf8Error;
 
 use crate::{
-    GdsArefBuilderError, GdsBoundaryBuilderError, GdsBoxBuilderError, GdsLibraryBuilderError, GdsNodeBuilderError, GdsPathBuilderError, GdsSrefBuilderError, GdsTextBuilderError 
+    GdsArefBuilderError, GdsBoundaryBuilderError, GdsBoxBuilderError, GdsElementKind, GdsLibraryBuilderError, GdsNodeBuilderError, GdsPathBuilderError, GdsSrefBuilderError, GdsTextBuilderError, GdsViolation
 };
 use crate::io::record::GdsRecordType;
 
+/// Most bytes of the offending record kept for the excerpt
+pub(super) const EXCERPT_LEN: usize = 64;
+
 #[derive(Debug, thiserror::Error)]
 pub enum GdsReadError {
     #[error("Io error '{0}'")]
@@ -25,6 +29,12 @@ pub enum GdsReadError {
     #[error("Expect record type '{0}', but got '{1}'")]
     UnexpectRecordType(GdsRecordType, GdsRecordType),
 
+    #[error("Record '{0}' is not expected here")]
+    UnexpectRecord(GdsRecordType),
+
+    #[error("No structure named '{0}'")]
+    UnknownStructure(String),
+
     #[error("Invalid format value '{0}'")]
     InvalidFormat(u16),
 
@@ -55,13 +65,183 @@ pub enum GdsReadError {
     #[error("Except one coord in XY, but got {0}")]
     ExecptPosition(usize),
 
-    #[error("When {0} >> {1}")]
-    Wrap(String, Box<GdsReadError>)
+    #[error("Except three coords in AREF XY, but got {0}")]
+    ExecptLattice(usize),
+
+    #[error("Record handler failed: {0}")]
+    Handler(String),
+
+    #[error("Library breaks {} rule(s), first in {}", .0.len(), .0.first().map(|v| v.to_string()).unwrap_or_default())]
+    Invalid(Vec<GdsViolation>),
+
+    #[error("Line {0}: {1}")]
+    Text(usize, String),
+
+    #[error("{0}")]
+    Context(Box<GdsReadErrorContext>)
 }
 
 impl GdsReadError {
-    pub fn wrap<S: Into<String>>(self, context: S) -> Self {
-        Self::Wrap(context.into(), Box::new(self))
+    /// Add a step to the trace of what was being read, outermost steps are added last
+    pub fn wrap<S: Into<String>>(self, step: S) -> Self {
+        let mut context = self.into_context();
+        context.trace.insert(0, step.into());
+        Self::Context(context)
+    }
+
+    /// The error without any context
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Context(context) => context.error.root(),
+            e => e,
+        }
+    }
+
+    /// Where the error happened, if known
+    pub fn context(&self) -> Option<&GdsReadErrorContext> {
+        match self {
+            Self::Context(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Multi-line description with the location, the trace and a hex excerpt of the record
+    pub fn report(&self) -> String {
+        match self {
+            Self::Context(context) => context.report(),
+            e => e.to_string(),
+        }
+    }
+
+    pub(super) fn into_context(self) -> Box<GdsReadErrorContext> {
+        match self {
+            Self::Context(context) => context,
+            error => Box::new(GdsReadErrorContext {
+                offset: None,
+                record_index: None,
+                record_offset: None,
+                record_type: None,
+                structure: None,
+                element_index: None,
+                element_kind: None,
+                trace: vec![],
+                excerpt: vec![],
+                error,
+            }),
+        }
+    }
+
+    /// Whether the input ended before the library was complete
+    pub fn is_eof(&self) -> bool {
+        matches!(self.root(), Self::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+    }
+
+    /// Whether reading can resync after this error, i.e. the input did not
+    /// end and record sizes can still be trusted
+    pub fn is_recoverable(&self) -> bool {
+        !self.is_eof() && !matches!(self.root(), Self::InvalidRecordSize(size) if *size < 4)
+    }
+}
+
+/// Where a read error happened and what was being read at the time.
+#[derive(Debug)]
+pub struct GdsReadErrorContext {
+    /// Byte offset of the reader when the error was raised
+    pub offset: Option<u64>,
+    /// Index of the offending record in the stream, counted from zero
+    pub record_index: Option<usize>,
+    /// Byte offset where the offending record starts
+    pub record_offset: Option<u64>,
+    /// `None` if the record type is unknown
+    pub record_type: Option<GdsRecordType>,
+    pub structure: Option<String>,
+    /// Index of the element in its structure, in file order
+    pub element_index: Option<usize>,
+    pub element_kind: Option<GdsElementKind>,
+    /// What was being read, outermost first
+    pub trace: Vec<String>,
+    /// The offending record, its first `EXCERPT_LEN` bytes if longer
+    /// or as much of it as the input holds
+    pub excerpt: Vec<u8>,
+    /// The underlying error
+    pub error: GdsReadError,
+}
+
+impl GdsReadErrorContext {
+    /// `excerpt` as space separated hex bytes
+    pub fn excerpt_hex(&self) -> String {
+        self.excerpt
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Hex dump of `excerpt`, 16 bytes a line, noting what is left out
+    fn excerpt_dump(&self) -> Vec<String> {
+        let size = match self.excerpt[..] {
+            [s0, s1, ..] => u16::from_be_bytes([s0, s1]) as usize,
+            _ => self.excerpt.len(),
+        };
+        let mut lines = vec![format!("  record of {size} bytes:")];
+        for (i, chunk) in self.excerpt.chunks(16).enumerate() {
+            let hex: Vec<_> = chunk.iter().map(|b| format!("{b:02x}")).collect();
+            lines.push(format!("    {:04x}  {}", i * 16, hex.join(" ")));
+        }
+        if size > self.excerpt.len() {
+            let missing = size - self.excerpt.len();
+            if self.excerpt.len() < EXCERPT_LEN {
+                lines.push(format!("    input ends {missing} bytes short"));
+            } else {
+                lines.push(format!("    ... {missing} more bytes"));
+            }
+        }
+        lines
+    }
+
+    pub fn report(&self) -> String {
+        let mut lines = vec![format!("error: {}", self.error)];
+        if let Some(offset) = self.offset {
+            lines.push(format!("  at byte {offset}"));
+        }
+        if let (Some(index), Some(offset)) = (self.record_index, self.record_offset) {
+            let tp = self.record_type.map(|tp| tp.to_string()).unwrap_or_else(|| "unknown".to_string());
+            lines.push(format!("  in record #{index} ({tp}) starting at byte {offset}"));
+        }
+        if let Some(structure) = &self.structure {
+            lines.push(format!("  in structure '{structure}'"));
+        }
+        match (self.element_index, self.element_kind) {
+            (Some(index), Some(kind)) => lines.push(format!("  in element #{index} ({kind})")),
+            (None, Some(kind)) => lines.push(format!("  in element ({kind})")),
+            _ => {}
+        }
+        if !self.trace.is_empty() {
+            lines.push(format!("  while {}", self.trace.join(" > ")));
+        }
+        if !self.excerpt.is_empty() {
+            lines.extend(self.excerpt_dump());
+        }
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for GdsReadErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        if let Some(tp) = self.record_type {
+            write!(f, " in {tp} record")?;
+        }
+        if let Some(structure) = &self.structure {
+            write!(f, " of structure '{structure}'")?;
+        }
+        if let Some(kind) = self.element_kind {
+            write!(f, " ({kind})")?;
+        }
+        Ok(())
     }
 }
 
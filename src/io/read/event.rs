@@ -0,0 +1,182 @@
+use std::io::Read;
+
+use crate::{GdsAccessControl, GdsAref, GdsBoundary, GdsBox, GdsDateTime, GdsElement, GdsFormat, GdsMask, GdsNode, GdsPath, GdsRawRecord, GdsSref, GdsStructure, GdsText};
+use crate::io::record::GdsRecordType;
+use super::{GdsReadError, GdsReadResult, GdsReader};
+
+/// One step of a GDSII stream, as yielded by `GdsReader::next_event`.
+/// Elements are decoded whole, everything else maps to a single record.
+#[derive(Debug, Clone)]
+pub enum GdsEvent {
+    Header { version: i16 },
+    BeginLibrary { create_date: GdsDateTime, modify_date: GdsDateTime },
+    LibDirSize(i16),
+    SrfName(String),
+    LibSecur(Vec<GdsAccessControl>),
+    LibraryName(String),
+    RefLibs([String; 2]),
+    Fonts([String; 4]),
+    AttrTable(String),
+    Generations(i16),
+    Format(GdsFormat),
+    Mask(GdsMask),
+    EndMasks,
+    Units { usrunits_per_dbunit: f64, meters_per_dbunit: f64 },
+
+    BeginStructure { name: String, create_date: GdsDateTime, modify_date: GdsDateTime },
+    StrClass(u16),
+    Boundary(GdsBoundary),
+    Path(GdsPath),
+    Sref(GdsSref),
+    Aref(GdsAref),
+    Text(GdsText),
+    Node(GdsNode),
+    Box(GdsBox),
+    EndStructure,
+
+    EndLibrary,
+
+    /// An unknown or obsolete record outside of any element, or a whole obsolete element.
+    /// Their positions are always 0, such records inside elements stay in the element.
+    /// Elements decoded by handlers come as the events of their kind.
+    Raw(Vec<GdsRawRecord>),
+}
+
+impl From<GdsElement> for GdsEvent {
+    fn from(element: GdsElement) -> Self {
+        match element {
+            GdsElement::Boundary(boundary) => Self::Boundary(boundary),
+            GdsElement::Path(path) => Self::Path(path),
+            GdsElement::Sref(sref) => Self::Sref(sref),
+            GdsElement::Aref(aref) => Self::Aref(aref),
+            GdsElement::Text(text) => Self::Text(text),
+            GdsElement::Node(node) => Self::Node(node),
+            GdsElement::Box(bx) => Self::Box(bx),
+        }
+    }
+}
+
+impl<R: Read> GdsReader<R> {
+    /// Read the next event from the stream, `None` once ENDLIB has been read.
+    /// Only the current element is held in memory.
+    pub fn next_event(&mut self) -> GdsReadResult<Option<GdsEvent>> {
+        if self.ended {
+            return Ok(None);
+        }
+        self.read_event().map(Some).map_err(|e| self.locate(e))
+    }
+
+    fn read_event(&mut self) -> GdsReadResult<GdsEvent> {
+        // Records not modeled, outside of elements
+        loop {
+            if !self.pending.is_empty() {
+                let (element, _) = self.pending.remove(0);
+                return Ok(GdsEvent::from(element));
+            }
+            let code = self.peek_record_code()?;
+            if GdsRecordType::from_u16(code).is_some_and(|tp| !tp.is_obsolete()) {
+                break;
+            }
+            let (records, _) = self.take_raw_records(0)?;
+            if !records.is_empty() {
+                return Ok(GdsEvent::Raw(records));
+            }
+        }
+
+        let tp = self.peek_record_type()?;
+        self.element_kind = Self::element_kind_of(tp);
+
+        let event = match tp {
+            GdsRecordType::Header => GdsEvent::Header { version: self.take_i16_record()? },
+            GdsRecordType::BgnLib => {
+                let (create_date, modify_date) = self.take_datetime_record()?;
+                GdsEvent::BeginLibrary { create_date, modify_date }
+            }
+            GdsRecordType::LibDirSize => GdsEvent::LibDirSize(self.take_i16_record()?),
+            GdsRecordType::SrfName => GdsEvent::SrfName(self.take_string_record()?),
+            GdsRecordType::LibSecur => GdsEvent::LibSecur(self.take_libsecur_record()?),
+            GdsRecordType::LibName => GdsEvent::LibraryName(self.take_string_record()?),
+            GdsRecordType::RefLibs => GdsEvent::RefLibs(self.take_reflibs_record()?),
+            GdsRecordType::Fonts => GdsEvent::Fonts(self.take_fonts_record()?),
+            GdsRecordType::AttrTable => GdsEvent::AttrTable(self.take_attrtable_record()?),
+            GdsRecordType::Generations => GdsEvent::Generations(self.take_i16_record()?),
+            GdsRecordType::Format => {
+                let value = self.take_u16_record()?;
+                let format = GdsFormat::from_u16(value)
+                    .ok_or(GdsReadError::InvalidFormat(value))?;
+                GdsEvent::Format(format)
+            }
+            GdsRecordType::Mask => GdsEvent::Mask(GdsMask::new(self.take_string_record()?)),
+            GdsRecordType::EndMasks => {
+                self.ensure_record_size(4)?;
+                self.jump_bytes(4)?;
+                GdsEvent::EndMasks
+            }
+            GdsRecordType::Uints => {
+                let (usrunits_per_dbunit, meters_per_dbunit) = self.take_units_record()?;
+                GdsEvent::Units { usrunits_per_dbunit, meters_per_dbunit }
+            }
+            GdsRecordType::BgnStr => {
+                let mut structure = GdsStructure::default();
+                self.read_structure_begin(&mut structure)?;
+                self.read_structure_name(&mut structure)?;
+                self.structure = Some(structure.name.clone());
+                GdsEvent::BeginStructure {
+                    name: structure.name,
+                    create_date: structure.create_date,
+                    modify_date: structure.modify_date,
+                }
+            }
+            GdsRecordType::StrClass => GdsEvent::StrClass(self.take_u16_record()?),
+            GdsRecordType::Boundary => GdsEvent::Boundary(self.read_element_boundary()?),
+            GdsRecordType::Path => GdsEvent::Path(self.read_element_path()?),
+            GdsRecordType::SRef => GdsEvent::Sref(self.read_element_sref()?),
+            GdsRecordType::ARef => GdsEvent::Aref(self.read_element_aref()?),
+            GdsRecordType::Text => GdsEvent::Text(self.read_element_text()?),
+            GdsRecordType::Node => GdsEvent::Node(self.read_element_node()?),
+            GdsRecordType::Box => GdsEvent::Box(self.read_element_box()?),
+            GdsRecordType::EndStr => {
+                self.read_structure_end()?;
+                self.structure = None;
+                GdsEvent::EndStructure
+            }
+            GdsRecordType::EndLib => {
+                self.ensure_record_size(4)?;
+                self.jump_bytes(4)?;
+                self.ended = true;
+                GdsEvent::EndLibrary
+            }
+            tp => return Err(GdsReadError::UnexpectRecord(tp)),
+        };
+
+        self.element_kind = None;
+        Ok(event)
+    }
+
+    /// Iterate over the events of the stream, stopping after ENDLIB or the first error.
+    pub fn events(&mut self) -> GdsEvents<'_, R> {
+        GdsEvents { reader: self, failed: false }
+    }
+}
+
+pub struct GdsEvents<'a, R> {
+    reader: &'a mut GdsReader<R>,
+    failed: bool,
+}
+
+impl<R: Read> Iterator for GdsEvents<'_, R> {
+    type Item = GdsReadResult<GdsEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.reader.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
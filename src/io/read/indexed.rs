@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::{GdsLibrary, GdsLibraryBuilder, GdsStructure};
+use crate::io::record::GdsRecordType;
+use super::{GdsReadError, GdsReadResult, GdsReader};
+use super::raw::RawLevel;
+
+/// Where a structure lives in the stream and which structures it references.
+#[derive(Debug, Clone)]
+pub struct GdsStructureIndex {
+    pub name: String,
+    /// Byte offset of the BGNSTR record
+    pub offset: u64,
+    /// Index of the BGNSTR record in the stream, counted from zero
+    pub record_index: usize,
+    /// Distinct SNAMEs of the SREF and AREF elements, in order of first use
+    pub references: Vec<String>,
+}
+
+/// Reader that indexes the structures of a library in one pass over the record
+/// headers, then loads structures on demand by seeking to them.
+/// Unlike `GdsReader` it needs a seekable source.
+pub struct GdsIndexedReader<R> {
+    reader: GdsReader<R>,
+    header: GdsLibrary,
+    index: Vec<GdsStructureIndex>,
+    lookup: HashMap<String, usize>,
+}
+
+impl GdsIndexedReader<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> GdsReadResult<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> GdsIndexedReader<R> {
+    pub fn new(reader: R) -> GdsReadResult<Self> {
+        let mut reader = GdsReader::new(reader)?;
+        let (header, index) = Self::read_index(&mut reader).map_err(|e| reader.locate(e))?;
+        let lookup = index
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name.clone(), i))
+            .collect();
+
+        Ok(Self { reader, header, index, lookup })
+    }
+
+    /// The library without any structure loaded.
+    pub fn header(&self) -> &GdsLibrary {
+        &self.header
+    }
+
+    /// Indexed structures in file order.
+    pub fn structures(&self) -> &[GdsStructureIndex] {
+        &self.index
+    }
+
+    pub fn structure(&self, name: &str) -> Option<&GdsStructureIndex> {
+        self.lookup.get(name).map(|&i| &self.index[i])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.lookup.contains_key(name)
+    }
+
+    /// Read the structure `name` only.
+    pub fn load_structure(&mut self, name: &str) -> GdsReadResult<GdsStructure> {
+        let (offset, record_index) = self.structure(name)
+            .map(|s| (s.offset, s.record_index))
+            .ok_or_else(|| GdsReadError::UnknownStructure(name.to_string()))?;
+        self.reader.seek_to_record(offset, record_index)?;
+        self.reader.read_structure()
+            .map_err(|e| e.wrap(format!("load structure '{name}'")))
+            .map_err(|e| self.reader.locate(e))
+    }
+
+    /// A library holding the structure `name` and every structure it references,
+    /// directly or not, in file order. References to structures not in this
+    /// library are left dangling.
+    pub fn load_with_dependencies(&mut self, name: &str) -> GdsReadResult<GdsLibrary> {
+        let root = *self.lookup.get(name)
+            .ok_or_else(|| GdsReadError::UnknownStructure(name.to_string()))?;
+
+        let mut visited = HashSet::from([root]);
+        let mut stack = vec![root];
+        while let Some(i) = stack.pop() {
+            for reference in &self.index[i].references {
+                if let Some(&child) = self.lookup.get(reference) {
+                    if visited.insert(child) {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+
+        let mut indices: Vec<usize> = visited.into_iter().collect();
+        indices.sort();
+
+        let mut library = self.header.clone();
+        for i in indices {
+            let name = self.index[i].name.clone();
+            let structure = self.load_structure(&name)?;
+            library.add_structure(structure);
+        }
+        Ok(library)
+    }
+
+    fn read_index(reader: &mut GdsReader<R>) -> GdsReadResult<(GdsLibrary, Vec<GdsStructureIndex>)> {
+        let mut builder = GdsLibraryBuilder::default();
+        reader.begin_raw_scope(RawLevel::Library);
+        reader.read_header(&mut builder).map_err(|e| e.wrap("read header"))?;
+        reader.read_library_begin(&mut builder).map_err(|e| e.wrap("read library begin"))?;
+        reader.read_library_extras(&mut builder).map_err(|e| e.wrap("read library extras"))?;
+        reader.read_library_name(&mut builder).map_err(|e| e.wrap("read library name"))?;
+        reader.read_library_options(&mut builder).map_err(|e| e.wrap("read library options"))?;
+        reader.read_units(&mut builder).map_err(|e| e.wrap("read units"))?;
+        // Obsolete records between structures are skipped by the scan
+        builder.raw_records(reader.end_raw_scope());
+        builder.structures(HashMap::new());
+        let header = builder.build()?;
+
+        let index = Self::scan(reader).map_err(|e| e.wrap("index structures"))?;
+        Ok((header, index))
+    }
+
+    fn scan(reader: &mut GdsReader<R>) -> GdsReadResult<Vec<GdsStructureIndex>> {
+        let mut index: Vec<GdsStructureIndex> = vec![];
+        let mut referenced = HashSet::new();
+        loop {
+            let offset = reader.reader.position();
+            let size = reader.peek_record_size()?;
+            let record_index = reader.records - 1;
+            if size < 4 {
+                return Err(GdsReadError::InvalidRecordSize(size));
+            }
+
+            // Unknown records are skipped with the rest
+            match GdsRecordType::from_u16(reader.peek_record_code()?) {
+                Some(GdsRecordType::BgnStr) => {
+                    let mut structure = GdsStructure::default();
+                    reader.read_structure_begin(&mut structure)?;
+                    reader.read_structure_name(&mut structure)?;
+                    index.push(GdsStructureIndex { name: structure.name, offset, record_index, references: vec![] });
+                    referenced.clear();
+                }
+                Some(GdsRecordType::SName) => {
+                    let name = reader.take_string_record()?;
+                    let current = index.last_mut()
+                        .ok_or(GdsReadError::UnexpectRecord(GdsRecordType::SName))?;
+                    if referenced.insert(name.clone()) {
+                        current.references.push(name);
+                    }
+                }
+                Some(GdsRecordType::EndLib) => break,
+                _ => reader.jump_bytes(size as u64)?,
+            }
+        }
+        Ok(index)
+    }
+}
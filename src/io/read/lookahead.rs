@@ -0,0 +1,79 @@
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use super::EXCERPT_LEN;
+
+/// Buffered byte source that can look a few bytes ahead without seeking,
+/// so a record header can be peeked on pipes and other non-seekable streams.
+pub(super) struct Lookahead<R> {
+    inner: BufReader<R>,
+    /// Bytes read from `inner` but not consumed yet
+    peeked: Vec<u8>,
+    /// Number of bytes consumed so far
+    position: u64,
+}
+
+impl<R: Read> Lookahead<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner: BufReader::new(inner), peeked: Vec::with_capacity(EXCERPT_LEN), position: 0 }
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The next `len` bytes, without consuming them
+    pub fn peek(&mut self, len: usize) -> std::io::Result<&[u8]> {
+        if self.peek_available(len)?.len() < len {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Not enough bytes to peek"));
+        }
+        Ok(&self.peeked[..len])
+    }
+
+    /// Up to `len` next bytes, fewer if the stream ends first
+    pub fn peek_available(&mut self, len: usize) -> std::io::Result<&[u8]> {
+        while self.peeked.len() < len {
+            let filled = self.peeked.len();
+            self.peeked.resize(len, 0);
+            let read = self.inner.read(&mut self.peeked[filled..]);
+            let n = *read.as_ref().unwrap_or(&0);
+            self.peeked.truncate(filled + n);
+            if read? == 0 {
+                break;
+            }
+        }
+        Ok(&self.peeked[..len.min(self.peeked.len())])
+    }
+
+    /// Consume `len` bytes
+    pub fn skip(&mut self, len: u64) -> std::io::Result<()> {
+        let skipped = std::io::copy(&mut self.by_ref().take(len), &mut std::io::sink())?;
+        if skipped < len {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Not enough bytes to skip"));
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Lookahead<R> {
+    pub fn seek_to(&mut self, position: u64) -> std::io::Result<()> {
+        self.inner.seek(SeekFrom::Start(position))?;
+        self.peeked.clear();
+        self.position = position;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Lookahead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = if self.peeked.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            let n = self.peeked.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.peeked[..n]);
+            self.peeked.drain(..n);
+            n
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
@@ -1,57 +1,162 @@
+mod diagnostic;
 mod error;
+mod event;
+mod indexed;
+mod lookahead;
+mod raw;
+mod text;
 
+pub use diagnostic::*;
 pub use error::*;
+pub use event::*;
+pub use indexed::*;
+pub use text::*;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{Read, Seek};
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 
 use crate::{
-    GdsAref, GdsArefBuilder, GdsArefBuilderError, GdsBoundary, GdsBoundaryBuilder, GdsBoundaryBuilderError, GdsBox, GdsBoxBuilder, GdsBoxBuilderError, GdsDateTime, GdsCoord, GdsFormat, GdsLibrary, GdsLibraryBuilder, GdsNode, GdsNodeBuilder, GdsNodeBuilderError, GdsPath, GdsPathBuilder, GdsPathBuilderError, GdsPathType, GdsPresentation, GdsSref, GdsSrefBuilder, GdsSrefBuilderError, GdsStructure, GdsText, GdsTextBuilder, GdsTextBuilderError, GdsTransform
+    GdsAref, GdsArefBuilder, GdsArefBuilderError, GdsBoundary, GdsBoundaryBuilder, GdsBoundaryBuilderError, GdsBox, GdsBoxBuilder, GdsBoxBuilderError, GdsAccessControl, GdsDateTime, GdsCoord, GdsElement, GdsElementKind, GdsFormat, GdsLibrary, GdsMask, GdsLibraryBuilder, GdsNode, GdsNodeBuilder, GdsNodeBuilderError, GdsPath, GdsPathBuilder, GdsPathBuilderError, GdsPathType, GdsPresentation, GdsProperty, GdsRawRecord, GdsSref, GdsSrefBuilder, GdsSrefBuilderError, GdsStructure, GdsText, GdsTextBuilder, GdsTextBuilderError, GdsTransform, GdsValidator
 };
 use super::record::GdsRecordType;
+use super::{GdsHandled, GdsReadHandler, GdsRecordScope};
+use lookahead::Lookahead;
+use raw::{RawLevel, RawScope};
 
 pub struct GdsReader<R> {
-    reader: BufReader<R>,
+    reader: Lookahead<R>,
+    /// Set once ENDLIB has been read by `next_event`
+    ended: bool,
+    options: ReadOptions,
+    diagnostics: Vec<GdsDiagnostic>,
+    /// Structure and element being read, for diagnostics and errors
+    structure: Option<String>,
+    element: Option<usize>,
+    element_kind: Option<GdsElementKind>,
+    /// Number of record headers seen, the last one starting at `record_offset`
+    records: usize,
+    record_offset: Option<u64>,
+    /// Leading bytes of that record, up to `EXCERPT_LEN`, for errors
+    record_bytes: Vec<u8>,
+    /// Open scopes collecting raw records, innermost last
+    raw_scopes: Vec<RawScope>,
+    /// Handlers of raw records by record code
+    handlers: HashMap<u16, GdsReadHandler>,
+    /// Elements decoded by handlers and where their record starts, not yet in a structure
+    pending: Vec<(GdsElement, u64)>,
+    /// Where each structure and its elements start, kept for validation diagnostics
+    offsets: HashMap<String, (u64, Vec<u64>)>,
 }
 
 impl GdsReader<File> {
     pub fn open<P: AsRef<Path>>(path: P) -> GdsReadResult<Self> {
-        let reader = BufReader::new(File::open(path)?);
-        Ok(Self { reader })
+        Self::new(File::open(path)?)
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     pub fn new(reader: R) -> GdsReadResult<Self> {
-        Ok(Self { reader: BufReader::new(reader) })
+        Ok(Self {
+            reader: Lookahead::new(reader),
+            ended: false,
+            options: ReadOptions::default(),
+            diagnostics: vec![],
+            structure: None,
+            element: None,
+            element_kind: None,
+            records: 0,
+            record_offset: None,
+            record_bytes: vec![],
+            raw_scopes: vec![],
+            handlers: HashMap::new(),
+            pending: vec![],
+            offsets: HashMap::new(),
+        })
+    }
+
+    pub fn with_options(mut self, options: ReadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Let `handler` decode the records of type `code`, before they are kept raw or skipped.
+    /// Only records this crate does not model, unknown or obsolete, reach handlers.
+    pub fn with_handler<F>(mut self, code: u16, handler: F) -> Self
+    where
+        F: FnMut(&GdsRawRecord, &GdsRecordScope) -> GdsReadResult<GdsHandled> + 'static,
+    {
+        self.handlers.insert(code, Box::new(handler));
+        self
+    }
+
+    /// Problems skipped over so far in recovery mode
+    pub fn diagnostics(&self) -> &[GdsDiagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<GdsDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     pub fn read(&mut self) -> GdsReadResult<GdsLibrary> {
-        match self.read_impl() {
-            Ok(gds) => Ok(gds),
-            Err(e) => {
-                let context = match self.reader.stream_position() {
-                    Ok(pos) => format!("read until {pos} bytes"),
-                    Err(e) => format!("error to get bytes bias for '{e}'"),
-                };
-                Err(e.wrap(context)) 
-            }
+        self.offsets.clear();
+        let library = self.read_impl().map_err(|e| self.locate(e))?;
+        self.validate(&library)?;
+        Ok(library)
+    }
+
+    /// Check `library` against the profile of the options, if any
+    fn validate(&mut self, library: &GdsLibrary) -> GdsReadResult<()> {
+        let Some(profile) = self.options.validation else {
+            return Ok(());
+        };
+        let violations = GdsValidator::new(profile).validate(library);
+        if violations.is_empty() {
+            return Ok(());
         }
+        if !self.options.recovery {
+            return Err(GdsReadError::Invalid(violations));
+        }
+
+        for violation in violations {
+            let (structure_offset, element_offsets) = self.offsets
+                .get(&violation.structure)
+                .map(|(offset, elements)| (*offset, elements.as_slice()))
+                .unwrap_or_default();
+            let element_index = violation.element.map(|(_, index)| index);
+            let offset = element_index
+                .and_then(|index| element_offsets.get(index).copied())
+                .unwrap_or(structure_offset);
+            self.diagnostics.push(GdsDiagnostic {
+                severity: GdsSeverity::Warning,
+                offset,
+                structure: Some(violation.structure),
+                element_index,
+                message: violation.kind.to_string(),
+            });
+        }
+        Ok(())
     }
 
     fn read_impl(&mut self) -> GdsReadResult<GdsLibrary> {
         let mut builder = GdsLibraryBuilder::default();
+        self.raw_scopes.clear();
+        self.begin_raw_scope(RawLevel::Library);
         self.read_header(&mut builder).map_err(|e| e.wrap("read header"))?;
-        self.read_library(&mut builder).map_err(|e| e.wrap("read Library"))?;
+        self.read_library(&mut builder).map_err(|e| e.wrap("read library"))?;
+
+        // Scopes left open by errors skipped in recovery mode
+        self.raw_scopes.truncate(1);
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build().unwrap())
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn read_header(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
         self.ensure_record(6, GdsRecordType::Header)?;
         builder.version(self.take_i16_record()?);
@@ -59,29 +164,49 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn read_library(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
         self.read_library_begin(builder).map_err(|e| e.wrap("read library begin"))?;
+        self.read_library_extras(builder).map_err(|e| e.wrap("read library extras"))?;
 
         self.read_library_name(builder).map_err(|e| e.wrap("read library name"))?;
-        self.read_library_options(builder).map_err(|e| e.wrap(""))?;
+        self.read_library_options(builder).map_err(|e| e.wrap("read library options"))?;
         self.read_units(builder).map_err(|e| e.wrap("read units"))?;
 
-        let structures = self.read_structures().map_err(|e| e.wrap("read structures"))?;
-        builder.structures(structures);
-        
-        self.read_library_end(builder).map_err(|e| e.wrap("read library end"))?;
-        Ok(())
+        let result = self.read_structures(builder)
+            .map_err(|e| e.wrap("read structures"))
+            .and_then(|_| self.read_library_end(builder).map_err(|e| e.wrap("read library end")));
+
+        match result {
+            // Keep everything read so far
+            Err(e) if self.options.recovery => {
+                self.diagnose_error(&e);
+                Ok(())
+            }
+            result => result,
+        }
     }
 
     fn read_library_begin(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
-        self.ensure_record(28, GdsRecordType::BgnLib)?;
-        self.jump_bytes(4)?;
-
-        builder.create_date(self.take_datetime()?);
-        builder.modify_date(self.take_datetime()?);
+        self.ensure_record_type(GdsRecordType::BgnLib)?;
+        let (create_date, modify_date) = self.take_datetime_record()?;
+        builder.create_date(create_date);
+        builder.modify_date(modify_date);
+        Ok(())
+    }
 
-        Ok(())   
+    /// [LIBDIRSIZE] [SRFNAME] [LIBSECUR]
+    fn read_library_extras(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
+        if self.check_record_type(GdsRecordType::LibDirSize)? {
+            builder.libdirsize(self.take_i16_record()?);
+        }
+        if self.check_record_type(GdsRecordType::SrfName)? {
+            builder.srfname(self.take_string_record()?);
+        }
+        if self.check_record_type(GdsRecordType::LibSecur)? {
+            builder.libsecur(self.take_libsecur_record()?);
+        }
+        Ok(())
     }
 
     fn read_library_name(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
@@ -91,29 +216,32 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 
     fn read_units(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
-        self.ensure_record(20, GdsRecordType::Uints)?;
-        self.jump_bytes(4)?;
-
-        builder.usrunits_per_dbunit(self.take_f64()?);
-        builder.meters_per_dbunit(self.take_f64()?);
-
+        self.ensure_record_type(GdsRecordType::Uints)?;
+        let (usrunits_per_dbunit, meters_per_dbunit) = self.take_units_record()?;
+        builder.usrunits_per_dbunit(usrunits_per_dbunit);
+        builder.meters_per_dbunit(meters_per_dbunit);
         Ok(())
     }
 
-    fn read_library_end(&mut self, _builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
+    fn read_library_end(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
         self.ensure_record(4, GdsRecordType::EndLib)?;
         self.jump_bytes(4)?;
+
+        // Anything after ENDLIB is tape block padding
+        let mut padding = vec![];
+        self.reader.read_to_end(&mut padding)?;
+        builder.block_padding(!padding.is_empty() && padding.iter().all(|&b| b == 0));
         Ok(())
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn read_library_options(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
         loop {
             let tp = self.peek_record_type()?;
             match tp {
                 GdsRecordType::RefLibs => self.read_reflibs(builder).map_err(|e| e.wrap("read reflibs"))?,
-                GdsRecordType::Fonts => self.read_fonts(builder).map_err(|e| e.wrap("read reflibs"))?,
+                GdsRecordType::Fonts => self.read_fonts(builder).map_err(|e| e.wrap("read fonts"))?,
                 GdsRecordType::AttrTable => self.read_attrtable(builder).map_err(|e| e.wrap("read attrtable"))?,
                 GdsRecordType::Generations => self.read_generations(builder).map_err(|e| e.wrap("read generations"))?,
                 GdsRecordType::Format => self.read_format(builder).map_err(|e| e.wrap("read format"))?,
@@ -123,31 +251,18 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 
     pub fn read_reflibs(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
-        self.ensure_record(94, GdsRecordType::RefLibs)?;
-        self.jump_bytes(4)?;
-        builder.reflibs([
-            self.take_string(45)?,
-            self.take_string(45)?,
-        ]);
+        self.ensure_record_type(GdsRecordType::RefLibs)?;
+        builder.reflibs(self.take_reflibs_record()?);
         Ok(())
     }
 
     pub fn read_fonts(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
-        self.ensure_record_size(4 * 44 + 4)?;
-        self.jump_bytes(4)?;
-        builder.fonts([
-            self.take_string(44)?,
-            self.take_string(44)?,
-            self.take_string(44)?,
-            self.take_string(44)?,
-        ]);
+        builder.fonts(self.take_fonts_record()?);
         Ok(())
     }
 
     pub fn read_attrtable(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
-        self.ensure_record_size(48)?;
-        self.jump_bytes(4)?;
-        builder.attrtable(self.take_string(44)?);
+        builder.attrtable(self.take_attrtable_record()?);
         Ok(())
     }
 
@@ -161,8 +276,25 @@ impl<R: Read + Seek> GdsReader<R> {
         let fmt = GdsFormat::from_u16(value)
             .ok_or_else(|| GdsReadError::InvalidFormat(value))?;
         builder.format(fmt);
+        let masks = self.read_masks()?;
+        // ENDMASKS is required after masks, and also found without them
+        let end_masks = !masks.is_empty() || self.check_record_type(GdsRecordType::EndMasks)?;
+        if end_masks {
+            self.ensure_record(4, GdsRecordType::EndMasks)?;
+            self.jump_bytes(4)?;
+        }
+        builder.masks(masks).end_masks(end_masks);
         Ok(())
     }
+
+    /// {MASK}*, present for filtered formats
+    pub fn read_masks(&mut self) -> GdsReadResult<Vec<GdsMask>> {
+        let mut masks = vec![];
+        while self.check_record_type(GdsRecordType::Mask)? {
+            masks.push(GdsMask::new(self.take_string_record()?));
+        }
+        Ok(masks)
+    }
 }
 
 macro_rules! read_optional_field {
@@ -189,36 +321,91 @@ macro_rules! read_required_field {
     };
 }
 
-impl<R: Read + Seek> GdsReader<R> {
-    fn read_structures(&mut self) -> GdsReadResult<HashMap<String, Arc<RwLock<GdsStructure>>>> {
+impl<R: Read> GdsReader<R> {
+    fn read_structures(&mut self, builder: &mut GdsLibraryBuilder) -> GdsReadResult<()> {
         let mut structures = HashMap::new();
-        let mut size = 0;
+        let mut order = vec![];
+        // Structures read before a failure are kept in recovery mode
+        let result = self.read_structures_into(&mut structures, &mut order);
+        builder.structures(structures);
+        builder.structure_order(order);
+        result
+    }
+
+    fn read_structures_into(
+        &mut self,
+        structures: &mut HashMap<String, Arc<RwLock<GdsStructure>>>,
+        order: &mut Vec<String>,
+    ) -> GdsReadResult<()> {
         while self.check_record_type(GdsRecordType::BgnStr)? {
-            let structure = self.read_structure().map_err(|e| e.wrap(format!("read {size} structure")))?;
-            let name = structure.name.clone();
-            structures.insert(name, Arc::new(RwLock::new(structure)));
-            size += 1;
+            let offset = self.reader.position();
+            let mut structure = GdsStructure::default();
+            let mut element_offsets = vec![];
+            let result = self.read_structure_into(&mut structure, &mut element_offsets);
+            if let Err(e) = &result {
+                if self.options.recovery && e.is_recoverable() {
+                    self.diagnose_error(e);
+                    self.resync(GdsRecordType::EndStr)?;
+                }
+            }
+
+            if result.is_ok() || (self.options.recovery && !structure.name.is_empty()) {
+                let name = structure.name.clone();
+                if self.options.validation.is_some() {
+                    self.offsets.insert(name.clone(), (offset, element_offsets));
+                }
+                order.push(name.clone());
+                structures.insert(name, Arc::new(RwLock::new(structure)));
+            }
+            match result {
+                Err(e) if !self.options.recovery || !e.is_recoverable() => return Err(e),
+                _ => {}
+            }
         }
-        Ok(structures)
+        self.structure = None;
+        Ok(())
     }
 
     fn read_structure(&mut self) -> GdsReadResult<GdsStructure> {
         let mut s = GdsStructure::default();
+        self.read_structure_into(&mut s, &mut vec![])?;
+        Ok(s)
+    }
+
+    /// Read into `s`, with where its elements start in `element_offsets`
+    fn read_structure_into(&mut self, s: &mut GdsStructure, element_offsets: &mut Vec<u64>) -> GdsReadResult<()> {
+        self.structure = None;
+        self.element = None;
+        self.pending.clear();
+
+        let depth = self.raw_scopes.len();
+        self.begin_raw_scope(RawLevel::Structure);
+        let result = self.read_structure_content(s, element_offsets);
+
+        // A failed structure may leave its scopes open
+        self.raw_scopes.truncate(depth + 1);
+        s.raw_records = self.end_raw_scope();
+        result?;
+
+        self.structure = None;
+        Ok(())
+    }
 
-        self.read_structure_begin(&mut s).map_err(|e| e.wrap("read structure begin"))?;
-        self.read_structure_name(&mut s).map_err(|e| e.wrap("read structure name"))?;
+    fn read_structure_content(&mut self, s: &mut GdsStructure, element_offsets: &mut Vec<u64>) -> GdsReadResult<()> {
+        self.read_structure_begin(s).map_err(|e| e.wrap("read structure begin"))?;
+        self.read_structure_name(s).map_err(|e| e.wrap("read structure name"))?;
+        self.structure = Some(s.name.clone());
+        self.read_structure_class(s).map_err(|e| e.wrap("read structure class"))?;
 
-        self.read_structure_elements(&mut s).map_err(|e| e.wrap("read structure elements"))?;
+        self.read_structure_elements(s, element_offsets).map_err(|e| e.wrap("read structure elements"))?;
         self.read_structure_end().map_err(|e| e.wrap("read structure end"))?;
 
-        Ok(s)
+        Ok(())
     }
 
     fn read_structure_begin(&mut self, structure: &mut GdsStructure) -> GdsReadResult<()> {
-        self.ensure_record(28, GdsRecordType::BgnStr)?;
-        self.jump_bytes(4)?;
-        structure.modify_date = self.take_datetime()?;
-        structure.create_date = self.take_datetime()?;
+        self.ensure_record_type(GdsRecordType::BgnStr)?;
+        (structure.create_date, structure.modify_date) = self.take_datetime_record()?;
         Ok(())
     }
 
@@ -228,36 +415,93 @@ impl<R: Read + Seek> GdsReader<R> {
         Ok(())
     }
 
+    fn read_structure_class(&mut self, structure: &mut GdsStructure) -> GdsReadResult<()> {
+        if self.check_record_type(GdsRecordType::StrClass)? {
+            structure.str_class = Some(self.take_u16_record()?);
+        }
+        Ok(())
+    }
+
     fn read_structure_end(&mut self) -> GdsReadResult<()> {
         self.ensure_record(4, GdsRecordType::EndStr)?;
         self.jump_bytes(4)?;
         Ok(())
     }
 
-    fn read_structure_elements(&mut self, s: &mut GdsStructure) -> GdsReadResult<()> {
+    fn read_structure_elements(&mut self, s: &mut GdsStructure, element_offsets: &mut Vec<u64>) -> GdsReadResult<()> {
+        let depth = self.raw_scopes.len();
         loop {
-            match self.peek_record_type()? {
-                GdsRecordType::Boundary => 
-                    s.boundarys.push(self.read_element_boundary().map_err(|e| e.wrap("read boundary"))?),
-                GdsRecordType::Path => 
-                    s.paths.push(self.read_element_path().map_err(|e| e.wrap("read path"))?),
-                GdsRecordType::Text => 
-                    s.texts.push(self.read_element_text().map_err(|e| e.wrap("read text"))?),
-                GdsRecordType::ARef => 
-                    s.arefs.push(self.read_element_aref().map_err(|e| e.wrap("read aref"))?),
-                GdsRecordType::SRef => 
-                    s.srefs.push(self.read_element_sref().map_err(|e| e.wrap("read sref"))?),
-                GdsRecordType::Node => 
-                    s.nodes.push(self.read_element_node().map_err(|e| e.wrap("read node"))?),
-                GdsRecordType::Box => 
-                    s.boxes.push(self.read_element_box().map_err(|e| e.wrap("read box"))?),
-                _ => break
+            match self.read_structure_element(s, element_offsets) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    let tp = self.peek_record_type()?;
+                    let ends_structure = matches!(tp, GdsRecordType::EndStr | GdsRecordType::BgnStr | GdsRecordType::EndLib);
+                    if !self.options.recovery || ends_structure {
+                        break;
+                    }
+                    self.diagnose(GdsSeverity::Warning, format!("Record '{tp}' is not expected here, skipped"));
+                    self.skip_record()?;
+                }
+                Err(e) if self.options.recovery && e.is_recoverable() => {
+                    self.diagnose_error(&e);
+                    self.raw_scopes.truncate(depth);
+                    self.resync(GdsRecordType::EndEle)?;
+                }
+                Err(e) => return Err(e),
             }
         }
+        self.element = None;
         Ok(())
     }
 
+    /// Read the next element into `s`, with where it starts into `element_offsets`,
+    /// or `None` if the next record does not begin one
+    fn read_structure_element(&mut self, s: &mut GdsStructure, element_offsets: &mut Vec<u64>) -> GdsReadResult<Option<GdsElementKind>> {
+        let tp = self.peek_record_type()?;
+        // Elements handlers decoded from the records before
+        for (element, offset) in self.pending.drain(..) {
+            s.push(element);
+            element_offsets.push(offset);
+        }
+        self.element = Some(s.element_count());
+
+        let Some(kind) = Self::element_kind_of(tp) else {
+            return Ok(None);
+        };
+        let offset = self.reader.position();
+        self.element_kind = Some(kind);
+
+        let read = |e: GdsReadError| e.wrap(format!("read {}", kind.to_string().to_lowercase()));
+        match kind {
+            GdsElementKind::Boundary => s.push(self.read_element_boundary().map_err(read)?),
+            GdsElementKind::Path => s.push(self.read_element_path().map_err(read)?),
+            GdsElementKind::Sref => s.push(self.read_element_sref().map_err(read)?),
+            GdsElementKind::Aref => s.push(self.read_element_aref().map_err(read)?),
+            GdsElementKind::Text => s.push(self.read_element_text().map_err(read)?),
+            GdsElementKind::Node => s.push(self.read_element_node().map_err(read)?),
+            GdsElementKind::Box => s.push(self.read_element_box().map_err(read)?),
+        }
+
+        self.element_kind = None;
+        element_offsets.push(offset);
+        Ok(Some(kind))
+    }
+
+    fn element_kind_of(tp: GdsRecordType) -> Option<GdsElementKind> {
+        match tp {
+            GdsRecordType::Boundary => Some(GdsElementKind::Boundary),
+            GdsRecordType::Path => Some(GdsElementKind::Path),
+            GdsRecordType::SRef => Some(GdsElementKind::Sref),
+            GdsRecordType::ARef => Some(GdsElementKind::Aref),
+            GdsRecordType::Text => Some(GdsElementKind::Text),
+            GdsRecordType::Node => Some(GdsElementKind::Node),
+            GdsRecordType::Box => Some(GdsElementKind::Box),
+            _ => None,
+        }
+    }
+
     fn read_element_boundary(&mut self) -> GdsReadResult<GdsBoundary> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Boundary));
         self.read_element_header()?;
         let mut builder = GdsBoundaryBuilder::default();
 
@@ -267,11 +511,15 @@ impl<R: Read + Seek> GdsReader<R> {
         read_required_field!(builder.data_type <- self.take_i16_record if DataType  => BuildBoundary(GdsBoundaryBuilderError));
         read_required_field!(builder.xy        <- self.read_xy        if Xy        => BuildBoundary(GdsBoundaryBuilderError));
     
+        builder.properties(self.read_properties()?);
+
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_path(&mut self) -> GdsReadResult<GdsPath> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Path));
         self.read_element_header()?;
         let mut builder = GdsPathBuilder::default();
 
@@ -279,23 +527,31 @@ impl<R: Read + Seek> GdsReader<R> {
         read_optional_field!(builder.plex           <- self.take_i32_record    if Plex);
         read_required_field!(builder.layer          <- self.take_i16_record    if Layer     => BuildPath(GdsPathBuilderError));
         read_required_field!(builder.data_type      <- self.take_i16_record    if DataType  => BuildPath(GdsPathBuilderError));
-        read_optional_field!(builder.path_type      <- self.read_path_type     if PathType);
+
+        let mut path_type = None;
+        if self.peek_record_type()? == GdsRecordType::PathType {
+            path_type = Some(self.read_path_type()?);
+        }
         read_optional_field!(builder.width          <- self.take_i32_record    if Width);
+        self.read_path_extensions(&mut path_type)?;
         read_required_field!(builder.xy             <- self.read_xy            if Xy        => BuildPath(GdsPathBuilderError));
         read_optional_field!(builder.purpose_layer  <- self.take_i16_record    if TextType);
 
-        if self.peek_record_type()? == GdsRecordType::BgnExtn {
-            self.take_i32_record()?;
-        }
-        if self.peek_record_type()? == GdsRecordType::EndExtn {
-            self.take_i32_record()?;
+        // Some writers put the extensions after XY
+        self.read_path_extensions(&mut path_type)?;
+        if let Some(path_type) = path_type {
+            builder.path_type(path_type);
         }
 
+        builder.properties(self.read_properties()?);
+
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_sref(&mut self) -> GdsReadResult<GdsSref> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Sref));
         self.read_element_header()?;
         let mut builder = GdsSrefBuilder::default();
 
@@ -305,11 +561,15 @@ impl<R: Read + Seek> GdsReader<R> {
         read_optional_field!(builder.transform <- self.read_transform       if STrans);
         read_required_field!(builder.position  <- self.read_position        if Xy        => BuildSref(GdsSrefBuilderError));
 
+        builder.properties(self.read_properties()?);
+
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_aref(&mut self) -> GdsReadResult<GdsAref> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Aref));
         self.read_element_header()?;
         let mut builder = GdsArefBuilder::default();
 
@@ -317,24 +577,23 @@ impl<R: Read + Seek> GdsReader<R> {
         read_optional_field!(builder.plex      <- self.take_i32_record      if Plex);
         read_required_field!(builder.s_name    <- self.take_string_record   if SName     => BuildAref(GdsArefBuilderError));
         read_optional_field!(builder.transform <- self.read_transform       if STrans);
+        read_required_field!(builder.col_row   <- self.read_col_row         if ColRow    => BuildAref(GdsArefBuilderError));
+        read_required_field!(builder.lattice   <- self.read_lattice         if Xy        => BuildAref(GdsArefBuilderError));
 
-        if self.peek_record_type()? == GdsRecordType::ColRow {
-            let (col, row) = self.read_col_row()?;
-            builder.col(col);
-            builder.row(row);
-        }
-        read_required_field!(builder.position  <- self.read_position        if Xy        => BuildSref(GdsSrefBuilderError));
+        builder.properties(self.read_properties()?);
 
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_text(&mut self) -> GdsReadResult<GdsText> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Text));
         self.read_element_header()?;
         let mut builder = GdsTextBuilder::default();
 
-        read_optional_field!(builder.plex          <- self.take_i32_record     if Plex);
         read_optional_field!(builder.elf_flags     <- self.take_i16_record     if ElFlags);
+        read_optional_field!(builder.plex          <- self.take_i32_record     if Plex);
         read_required_field!(builder.layer         <- self.take_i16_record     if Layer     => BuildText(GdsTextBuilderError));
         read_required_field!(builder.text_type     <- self.take_i16_record     if TextType  => BuildText(GdsTextBuilderError));
         read_optional_field!(builder.presentation  <- self.read_presentation   if Presentation);
@@ -344,35 +603,46 @@ impl<R: Read + Seek> GdsReader<R> {
         read_required_field!(builder.position      <- self.read_position       if Xy        => BuildSref(GdsSrefBuilderError));
         read_required_field!(builder.string        <- self.take_string_record  if String    => BuildText(GdsTextBuilderError));
 
+        builder.properties(self.read_properties()?);
+
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_node(&mut self) -> GdsReadResult<GdsNode> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Node));
         self.read_element_header()?;
         let mut builder = GdsNodeBuilder::default();
 
         read_optional_field!(builder.elf_flags     <- self.take_i16_record     if ElFlags);
         read_optional_field!(builder.plex          <- self.take_i32_record     if Plex);
         read_required_field!(builder.layer         <- self.take_i16_record     if Layer     => BuildNode(GdsNodeBuilderError));
-        read_required_field!(builder.node_type     <- self.take_i16_record     if Layer     => BuildNode(GdsNodeBuilderError));
-        read_required_field!(builder.xy            <- self.read_xy             if Layer     => BuildNode(GdsNodeBuilderError));
+        read_required_field!(builder.node_type     <- self.take_i16_record     if NodeType  => BuildNode(GdsNodeBuilderError));
+        read_required_field!(builder.xy            <- self.read_xy             if Xy        => BuildNode(GdsNodeBuilderError));
+
+        builder.properties(self.read_properties()?);
 
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
     pub fn read_element_box(&mut self) -> GdsReadResult<GdsBox> {
+        self.begin_raw_scope(RawLevel::Element(GdsElementKind::Box));
         self.read_element_header()?;
         let mut builder = GdsBoxBuilder::default();
 
         read_optional_field!(builder.elf_flags     <- self.take_i16_record     if ElFlags);
         read_optional_field!(builder.plex          <- self.take_i32_record     if Plex);
         read_required_field!(builder.layer         <- self.take_i16_record     if Layer     => BuildBox(GdsBoxBuilderError));
-        read_required_field!(builder.box_type      <- self.take_i16_record     if Layer     => BuildBox(GdsBoxBuilderError));
-        read_required_field!(builder.xy            <- self.read_xy             if Layer     => BuildBox(GdsBoxBuilderError));
+        read_required_field!(builder.box_type      <- self.take_i16_record     if BoxType   => BuildBox(GdsBoxBuilderError));
+        read_required_field!(builder.xy            <- self.read_xy             if Xy        => BuildBox(GdsBoxBuilderError));
+
+        builder.properties(self.read_properties()?);
 
         self.read_element_end()?;
+        builder.raw_records(self.end_raw_scope());
         Ok(builder.build()?)
     }
 
@@ -397,22 +667,9 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 
     pub fn read_xy(&mut self) -> GdsReadResult<Vec<GdsCoord>> {
-        let mut header = [0u8; 4];
-        self.reader.read_exact(&mut header)?;
-        let record_size = u16::from_be_bytes([header[0], header[1]]) as usize;
-        let record_type = u16::from_be_bytes([header[2], header[3]]);
-        let rec_type = GdsRecordType::from_u16(record_type)
-            .ok_or(GdsReadError::UnsupportRecordType(record_type))?;
-
-        if rec_type != GdsRecordType::Xy {
-            return Err(GdsReadError::UnexpectRecordType(GdsRecordType::Xy, rec_type));
-        }
-
-        if record_size < 4 || (record_size - 4) % 8 != 0 {
-            return Err(GdsReadError::InvalidRecordSize(record_size));
-        }
+        let position_count = self.peek_xy_count()?;
+        self.jump_bytes(4)?;
 
-        let position_count = (record_size - 4) / 8;
         let mut coords = Vec::with_capacity(position_count);
         for _ in 0..position_count {
             let x = self.take_i32()?;
@@ -423,25 +680,11 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 
     pub fn read_position(&mut self) -> GdsReadResult<GdsCoord> {
-        let mut header = [0u8; 4];
-        self.reader.read_exact(&mut header)?;
-        let record_size = u16::from_be_bytes([header[0], header[1]]) as usize;
-        let record_type = u16::from_be_bytes([header[2], header[3]]);
-        let rec_type = GdsRecordType::from_u16(record_type)
-            .ok_or(GdsReadError::UnsupportRecordType(record_type))?;
-
-        if rec_type != GdsRecordType::Xy {
-            return Err(GdsReadError::UnexpectRecordType(GdsRecordType::Xy, rec_type));
-        }
-
-        if record_size < 4 || (record_size - 4) % 8 != 0 {
-            return Err(GdsReadError::InvalidRecordSize(record_size));
-        }
-
-        let position_count = (record_size - 4) / 8;
+        let position_count = self.peek_xy_count()?;
         if position_count != 1 {
             return Err(GdsReadError::ExecptPosition(position_count));
         }
+        self.jump_bytes(4)?;
 
         let x = self.take_i32()?;
         let y = self.take_i32()?;
@@ -449,6 +692,37 @@ impl<R: Read + Seek> GdsReader<R> {
         Ok((x, y).into())
     }
 
+    /// Check the next record is XY and return its number of coords, consuming nothing
+    fn peek_xy_count(&mut self) -> GdsReadResult<usize> {
+        self.ensure_record_type(GdsRecordType::Xy)?;
+        let record_size = self.peek_record_size()?;
+        if record_size < 4 || (record_size - 4) % 8 != 0 {
+            return Err(GdsReadError::InvalidRecordSize(record_size));
+        }
+        Ok((record_size - 4) / 8)
+    }
+
+    pub fn read_lattice(&mut self) -> GdsReadResult<[GdsCoord; 3]> {
+        let coords = self.read_xy()?;
+        match coords[..] {
+            [origin, col_corner, row_corner] => Ok([origin, col_corner, row_corner]),
+            _ => Err(GdsReadError::ExecptLattice(coords.len())),
+        }
+    }
+
+    /// {<property>}*
+    /// <property>: PROPATTR PROPVALUE
+    pub fn read_properties(&mut self) -> GdsReadResult<Vec<GdsProperty>> {
+        let mut properties = vec![];
+        while self.check_record_type(GdsRecordType::PropAttr)? {
+            let attr = self.take_i16_record()?;
+            self.ensure_record_type(GdsRecordType::PropValue)?;
+            let value = self.take_string_record()?;
+            properties.push(GdsProperty { attr, value });
+        }
+        Ok(properties)
+    }
+
     pub fn read_transform(&mut self) -> GdsReadResult<GdsTransform> {
         // Take the u16 value as flag
         let value = self.take_u16_record()? as u16;
@@ -473,6 +747,23 @@ impl<R: Read + Seek> GdsReader<R> {
             .ok_or_else(|| GdsReadError::BuildText(GdsTextBuilderError::ValidationError(format!("'{value}' is not an valid path type"))))
     }
 
+    /// [BGNEXTN] [ENDEXTN], only kept for pathtype 4
+    pub fn read_path_extensions(&mut self, path_type: &mut Option<GdsPathType>) -> GdsReadResult<()> {
+        if self.peek_record_type()? == GdsRecordType::BgnExtn {
+            let value = self.take_i32_record()?;
+            if let Some(GdsPathType::CustomExtend { begin_extension, .. }) = path_type {
+                *begin_extension = Some(value);
+            }
+        }
+        if self.peek_record_type()? == GdsRecordType::EndExtn {
+            let value = self.take_i32_record()?;
+            if let Some(GdsPathType::CustomExtend { end_extension, .. }) = path_type {
+                *end_extension = Some(value);
+            }
+        }
+        Ok(())
+    }
+
     pub fn read_presentation(&mut self) -> GdsReadResult<GdsPresentation> {
         let value = self.take_u16_record()?;
         GdsPresentation::from_u16(value)
@@ -482,7 +773,7 @@ impl<R: Read + Seek> GdsReader<R> {
 
 /// These method do not check record type, please ensure it correct!
 /// And will take all record!
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn take_u16_record(&mut self) -> GdsReadResult<u16> {
         self.ensure_record_size(6)?;
         self.jump_bytes(4)?; // Jump record header
@@ -496,7 +787,7 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 
     fn take_i32_record(&mut self) -> GdsReadResult<i32> {
-        self.ensure_record_size(6)?;
+        self.ensure_record_size(8)?;
         self.jump_bytes(4)?;
         self.take_i32()
     }
@@ -512,10 +803,62 @@ impl<R: Read + Seek> GdsReader<R> {
         self.jump_bytes(4)?;
         self.take_f64()
     }
+
+    /// BGNLIB and BGNSTR: creation time then modification time
+    fn take_datetime_record(&mut self) -> GdsReadResult<(GdsDateTime, GdsDateTime)> {
+        self.ensure_record_size(28)?;
+        self.jump_bytes(4)?;
+        Ok((self.take_datetime()?, self.take_datetime()?))
+    }
+
+    /// UNITS: user units per database unit then meters per database unit
+    fn take_units_record(&mut self) -> GdsReadResult<(f64, f64)> {
+        self.ensure_record_size(20)?;
+        self.jump_bytes(4)?;
+        Ok((self.take_f64()?, self.take_f64()?))
+    }
+
+    fn take_reflibs_record(&mut self) -> GdsReadResult<[String; 2]> {
+        self.ensure_record_size(94)?;
+        self.jump_bytes(4)?;
+        Ok([
+            self.take_string(45)?,
+            self.take_string(45)?,
+        ])
+    }
+
+    fn take_fonts_record(&mut self) -> GdsReadResult<[String; 4]> {
+        self.ensure_record_size(4 * 44 + 4)?;
+        self.jump_bytes(4)?;
+        Ok([
+            self.take_string(44)?,
+            self.take_string(44)?,
+            self.take_string(44)?,
+            self.take_string(44)?,
+        ])
+    }
+
+    /// LIBSECUR: (group, user, access rights) for each access control list entry
+    fn take_libsecur_record(&mut self) -> GdsReadResult<Vec<GdsAccessControl>> {
+        let size = self.peek_record_size()?;
+        if size < 4 || (size - 4) % 6 != 0 {
+            return Err(GdsReadError::InvalidRecordSize(size));
+        }
+        self.jump_bytes(4)?;
+        (0..(size - 4) / 6)
+            .map(|_| Ok(GdsAccessControl::new(self.take_i16()?, self.take_i16()?, self.take_i16()?)))
+            .collect()
+    }
+
+    fn take_attrtable_record(&mut self) -> GdsReadResult<String> {
+        self.ensure_record_size(48)?;
+        self.jump_bytes(4)?;
+        self.take_string(44)
+    }
 }
 
 /// Methods for take value from `reader`
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn take_record_size(&mut self) -> GdsReadResult<usize> {
         let mut buf = [0u8; 2];
         self.reader.read_exact(&mut buf)?;
@@ -554,36 +897,18 @@ impl<R: Read + Seek> GdsReader<R> {
         Ok(s)
     }
 
+    /// Decode an 8-byte IBM real: sign bit, 7-bit excess-64 exponent of 16 and 56-bit mantissa.
     fn take_f64(&mut self) -> GdsReadResult<f64> {
         let mut buf = [0u8; 8];
         self.reader.read_exact(&mut buf)?;
-    
-        let data = u64::from_be_bytes(buf); 
-    
-        let sign = (data >> 63) & 0x1;
-        let mut exponent = (data >> 56) & 0x7F;
-        let mut mantissa = data << 8; 
-    
-        if mantissa == 0 {
-            return Ok(0.0);
-        }
-    
-        exponent = ((exponent as i64 - 64) * 4 + 1023) as u64;
-    
-        while (mantissa & 0x8000_0000_0000_0000) == 0 {
-            mantissa <<= 1;
-            exponent -= 1;
-        }
-    
-        mantissa <<= 1;
-        exponent -= 1;
-    
-        let ieee_bits =
-            (sign << 63) |
-            (exponent << 52) |
-            ((mantissa >> 12) & 0x000F_FFFF_FFFF_FFFF);
-    
-        Ok(f64::from_bits(ieee_bits))
+
+        let data = u64::from_be_bytes(buf);
+
+        let sign = if (data >> 63) & 0x1 == 1 { -1.0 } else { 1.0 };
+        let exponent = ((data >> 56) & 0x7F) as i32 - 64;
+        let mantissa = data & 0x00FF_FFFF_FFFF_FFFF;
+
+        Ok(sign * mantissa as f64 * 2f64.powi(4 * exponent - 56))
     }
 
     fn take_datetime(&mut self) -> GdsReadResult<GdsDateTime> {
@@ -605,15 +930,15 @@ impl<R: Read + Seek> GdsReader<R> {
     }
 }
 
-impl<R: Read + Seek> GdsReader<R> {
+impl<R: Read> GdsReader<R> {
     fn check_record_type(&mut self, tp: GdsRecordType) -> GdsReadResult<bool> {
         let real_tp = self.peek_record_type()?;
         Ok(real_tp == tp)
     }
 
     fn ensure_record(&mut self, size: usize, tp: GdsRecordType) -> GdsReadResult<()> {
-        self.ensure_record_size(size)?;
         self.ensure_record_type(tp)?;
+        self.ensure_record_size(size)?;
         Ok(())
     }
 
@@ -640,32 +965,127 @@ impl<R: Read + Seek> GdsReader<R> {
         Ok(u16::from_be_bytes(bytes) as usize)
     }
 
+    /// Unknown and obsolete records are handled, kept raw by the enclosing library,
+    /// structure or element or skipped here, so callers only see the records they model
     fn peek_record_type(&mut self) -> GdsReadResult<GdsRecordType> {
-        let bytes = self.peek_bytes::<4>()?;
-        let bytes = [bytes[2], bytes[3]];
-        let value = u16::from_be_bytes(bytes);
-        match GdsRecordType::from_u16(value) {
-            Some(t) => Ok(t),
-            None => Err(GdsReadError::UnsupportRecordType(value))
+        loop {
+            let value = self.peek_record_code()?;
+            match GdsRecordType::from_u16(value) {
+                Some(t) if !t.is_obsolete() || self.raw_scopes.is_empty() => return Ok(t),
+                None if self.raw_scopes.is_empty() => return Err(GdsReadError::UnsupportRecordType(value)),
+                _ => self.collect_raw_records()?,
+            }
         }
     }
 
+    /// Record type and data type of the next record
+    fn peek_record_code(&mut self) -> GdsReadResult<u16> {
+        let bytes = self.peek_bytes::<4>()?;
+        Ok(u16::from_be_bytes([bytes[2], bytes[3]]))
+    }
+
+    /// Only used on record headers, so a new position means a new record
     fn peek_bytes<const L: usize>(&mut self) -> GdsReadResult<[u8; L]> {
+        let position = self.reader.position();
+        if self.record_offset != Some(position) {
+            self.records += 1;
+            self.record_offset = Some(position);
+            let size = match self.reader.peek_available(2)? {
+                &[s0, s1] => u16::from_be_bytes([s0, s1]) as usize,
+                _ => 0,
+            };
+            self.record_bytes = self.reader.peek_available(size.clamp(4, EXCERPT_LEN))?.to_vec();
+        }
+
         let mut bytes = [0u8; L];
-        let buf = self.reader.fill_buf()?;
-        if buf.len() < L {
-            return Err(GdsReadError::Io(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Not enough bytes to peek")));
+        bytes.copy_from_slice(self.reader.peek(L)?);
+        Ok(bytes)
+    }
+
+    fn jump_bytes(&mut self, size: u64) -> GdsReadResult<()> {
+        self.reader.skip(size)?;
+        Ok(())
+    }
+
+    fn skip_record(&mut self) -> GdsReadResult<()> {
+        let size = self.peek_record_size()?;
+        if size < 4 {
+            return Err(GdsReadError::InvalidRecordSize(size));
         }
+        self.jump_bytes(size as u64)
+    }
+}
 
-        for i in 0..L {
-            bytes[i] = buf[i];
+impl<R: Read + Seek> GdsReader<R> {
+    /// Continue at `offset`, which starts the record number `record_index`
+    fn seek_to_record(&mut self, offset: u64, record_index: usize) -> GdsReadResult<()> {
+        self.reader.seek_to(offset)?;
+        self.records = record_index;
+        self.record_offset = None;
+        self.raw_scopes.clear();
+        Ok(())
+    }
+}
+
+/// Error context
+impl<R: Read> GdsReader<R> {
+    /// Attach where the reader is to `e`
+    pub(super) fn locate(&mut self, e: GdsReadError) -> GdsReadError {
+        let mut context = e.into_context();
+        let offset = self.reader.position();
+        context.offset = Some(offset);
+        context.structure = self.structure.clone();
+        context.element_index = self.element;
+        context.element_kind = self.element_kind;
+
+        if let Some(record_offset) = self.record_offset {
+            context.record_index = Some(self.records - 1);
+            context.record_offset = Some(record_offset);
+            context.excerpt = self.record_bytes.clone();
+            if let [_, _, t0, t1, ..] = self.record_bytes[..] {
+                context.record_type = GdsRecordType::from_u16(u16::from_be_bytes([t0, t1]));
+            }
         }
 
-        Ok(bytes)
+        GdsReadError::Context(context)
     }
+}
 
-    fn jump_bytes(&mut self, size: i64) -> GdsReadResult<()> {
-        self.reader.seek(SeekFrom::Current(size))?;
-        Ok(())
+/// Recovery mode
+impl<R: Read> GdsReader<R> {
+    /// Skip records up to and including `end`, but stop before anything that
+    /// begins or ends a structure or the library
+    fn resync(&mut self, end: GdsRecordType) -> GdsReadResult<()> {
+        loop {
+            let bytes = self.peek_bytes::<4>()?;
+            let tp = GdsRecordType::from_u16(u16::from_be_bytes([bytes[2], bytes[3]]));
+            if tp == Some(end) {
+                return self.skip_record();
+            }
+            if matches!(tp, Some(GdsRecordType::BgnStr | GdsRecordType::EndStr | GdsRecordType::EndLib)) {
+                return Ok(());
+            }
+            self.skip_record()?;
+        }
     }
-}
\ No newline at end of file
+
+    /// Report a problem with the last record whose header was peeked
+    fn diagnose(&mut self, severity: GdsSeverity, message: String) {
+        self.diagnostics.push(GdsDiagnostic {
+            severity,
+            offset: self.record_offset.unwrap_or_else(|| self.reader.position()),
+            structure: self.structure.clone(),
+            element_index: self.element,
+            message,
+        });
+    }
+
+    fn diagnose_error(&mut self, e: &GdsReadError) {
+        let message = if e.is_eof() {
+            "File ends unexpectedly".to_string()
+        } else {
+            format!("{}, skipped", e.root())
+        };
+        self.diagnose(GdsSeverity::Error, message);
+    }
+}
@@ -0,0 +1,149 @@
+use std::io::Read;
+
+use crate::{GdsElementKind, GdsRawRecord};
+use crate::io::record::GdsRecordType;
+use crate::io::{GdsHandled, GdsRecordScope};
+use super::{GdsReadError, GdsReadResult, GdsReader, GdsSeverity, GdsUnknownRecords};
+
+pub(super) enum RawLevel {
+    Library,
+    Structure,
+    Element(GdsElementKind),
+}
+
+/// Raw records of the library, structure or element being read.
+pub(super) struct RawScope {
+    level: RawLevel,
+    /// Index of the first record of the scope
+    start: usize,
+    /// Records of the scope that do not count for positions:
+    /// raw records and all but one record of every child scope
+    skipped: usize,
+    records: Vec<GdsRawRecord>,
+}
+
+impl<R: Read> GdsReader<R> {
+    /// Start collecting raw records for the library, structure or element
+    /// whose first record is the next one
+    pub(super) fn begin_raw_scope(&mut self, level: RawLevel) {
+        let start = self.current_record_index();
+        self.raw_scopes.push(RawScope { level, start, skipped: 0, records: vec![] });
+    }
+
+    /// Stop collecting, the scope counts as a single record of its parent
+    pub(super) fn end_raw_scope(&mut self) -> Vec<GdsRawRecord> {
+        let Some(scope) = self.raw_scopes.pop() else {
+            return vec![];
+        };
+        if let Some(parent) = self.raw_scopes.last_mut() {
+            parent.skipped += (self.records - scope.start).saturating_sub(1);
+        }
+        scope.records
+    }
+
+    /// Take the next record into the current scope, see `take_raw_records`
+    pub(super) fn collect_raw_records(&mut self) -> GdsReadResult<()> {
+        let position = match self.raw_scopes.last() {
+            Some(scope) => self.current_record_index() - scope.start - scope.skipped,
+            None => 0,
+        };
+        let decoded = self.pending.len();
+        let (records, taken) = self.take_raw_records(position)?;
+        // Decoded elements count for positions like any other element
+        let decoded = self.pending.len() - decoded;
+        if let Some(scope) = self.raw_scopes.last_mut() {
+            scope.skipped += taken - decoded;
+            scope.records.extend(records);
+        }
+        Ok(())
+    }
+
+    /// Pass `record`, which starts at `offset`, through its handler,
+    /// returning it if it is to be kept raw
+    fn handle_raw_record(&mut self, record: GdsRawRecord, offset: u64) -> GdsReadResult<Option<GdsRawRecord>> {
+        let scope = self.record_scope();
+        let handled = match self.handlers.get_mut(&record.code) {
+            Some(handler) => handler(&record, &scope)?,
+            None => GdsHandled::NotHandled,
+        };
+
+        match handled {
+            GdsHandled::Element(element) => {
+                if !matches!(scope, GdsRecordScope::Structure(_)) {
+                    let message = format!("record '{:#06x}' is not between elements, it can not become one", record.code);
+                    return Err(GdsReadError::Handler(message));
+                }
+                self.pending.push((element, offset));
+                Ok(None)
+            }
+            GdsHandled::Raw(record) => Ok(Some(record)),
+            GdsHandled::Drop => Ok(None),
+            GdsHandled::NotHandled => {
+                if record.record_type().is_some() || self.options.unknown_records == GdsUnknownRecords::Keep {
+                    Ok(Some(record))
+                } else if self.options.recovery {
+                    self.diagnose(GdsSeverity::Warning, format!("Unsupport record type '{:#06x}', skipped", record.code));
+                    Ok(None)
+                } else {
+                    Err(GdsReadError::UnsupportRecordType(record.code))
+                }
+            }
+        }
+    }
+
+    /// Where the next raw record is
+    fn record_scope(&self) -> GdsRecordScope {
+        let structure = self.structure.clone().unwrap_or_default();
+        match self.raw_scopes.last().map(|scope| &scope.level) {
+            Some(RawLevel::Library) => GdsRecordScope::Library,
+            Some(RawLevel::Structure) => GdsRecordScope::Structure(structure),
+            Some(&RawLevel::Element(kind)) => GdsRecordScope::Element(structure, kind),
+            // Outside of scopes only when reading events
+            None if self.structure.is_some() => GdsRecordScope::Structure(structure),
+            None => GdsRecordScope::Library,
+        }
+    }
+
+    /// Take the next record, with the rest of its element if it begins an obsolete one,
+    /// and pass each through its handler. Returns the records kept raw and the number taken,
+    /// elements decoded by handlers are queued in `pending`.
+    pub(super) fn take_raw_records(&mut self, position: usize) -> GdsReadResult<(Vec<GdsRawRecord>, usize)> {
+        let mut records = vec![];
+        let mut taken = 0;
+        let mut element = false;
+        loop {
+            let offset = self.reader.position();
+            let record = self.take_raw_record(position)?;
+            let tp = record.record_type();
+            if taken == 0 {
+                element = tp.is_some_and(|tp| tp.begins_obsolete_element());
+            }
+            taken += 1;
+            records.extend(self.handle_raw_record(record, offset)?);
+            if !element || tp == Some(GdsRecordType::EndEle) {
+                return Ok((records, taken));
+            }
+        }
+    }
+
+    fn take_raw_record(&mut self, position: usize) -> GdsReadResult<GdsRawRecord> {
+        let [s0, s1, t0, t1] = self.peek_bytes::<4>()?;
+        let size = u16::from_be_bytes([s0, s1]) as usize;
+        if size < 4 {
+            return Err(GdsReadError::InvalidRecordSize(size));
+        }
+        self.jump_bytes(4)?;
+        let mut data = vec![0u8; size - 4];
+        self.reader.read_exact(&mut data)?;
+        Ok(GdsRawRecord::new(position, u16::from_be_bytes([t0, t1]), data))
+    }
+
+    /// Index of the record starting at the current position
+    fn current_record_index(&self) -> usize {
+        if self.record_offset == Some(self.reader.position()) {
+            self.records - 1
+        } else {
+            self.records
+        }
+    }
+}
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::vec::IntoIter;
+
+use crate::{
+    GdsAccessControl, GdsArefBuilder, GdsBoundaryBuilder, GdsBoxBuilder, GdsCoord, GdsDateTime, GdsElementKind, GdsFormat, GdsLibrary, GdsLibraryBuilder, GdsMask, GdsNodeBuilder, GdsPathBuilder, GdsPathType, GdsPresentation, GdsProperty, GdsRawRecord, GdsSrefBuilder, GdsStructure, GdsTextBuilder, GdsTransform, GdsTransformFlag
+};
+use super::{GdsReadError, GdsReadResult};
+
+/// Reads the text written by `TextWriter` back into a library.
+pub struct TextReader<R> {
+    reader: R,
+}
+
+impl TextReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> GdsReadResult<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl<R: BufRead> TextReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    pub fn read(&mut self) -> GdsReadResult<GdsLibrary> {
+        let mut lines = vec![];
+        for (i, line) in (&mut self.reader).lines().enumerate() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                lines.push(TextLine::new(i + 1, &line));
+            }
+        }
+
+        let mut lines = lines.into_iter().peekable();
+        let library = Self::parse_library(&mut lines)?;
+        match lines.next() {
+            Some(line) => Err(line.error("Unexpected indentation")),
+            None => Ok(library),
+        }
+    }
+}
+
+type Lines = Peekable<IntoIter<TextLine>>;
+
+impl<R: BufRead> TextReader<R> {
+    fn parse_library(lines: &mut Lines) -> GdsReadResult<GdsLibrary> {
+        match lines.next() {
+            Some(line) if line.indent == 0 && line.key == "GDSII Layout Object" => {}
+            Some(line) => return Err(line.error("Expect 'GDSII Layout Object'")),
+            None => return Err(GdsReadError::Text(0, "Text is empty".into())),
+        }
+
+        let mut builder = GdsLibraryBuilder::default();
+        let mut masks = vec![];
+        let mut libsecur = vec![];
+        let mut reflibs = vec![];
+        let mut fonts = vec![];
+        let mut raw_records = vec![];
+        let mut structures = HashMap::new();
+        let mut structure_order = vec![];
+
+        while let Some(line) = lines.next_if(|line| line.indent == 1) {
+            match line.key.as_str() {
+                "version" => { builder.version(line.parse()?); }
+                "library name" => { builder.name(line.string()?); }
+                "units" => {
+                    let (usrunits_per_dbunit, meters_per_dbunit) = line.pair("(", ")")?;
+                    builder.usrunits_per_dbunit(usrunits_per_dbunit);
+                    builder.meters_per_dbunit(meters_per_dbunit);
+                }
+                "create date" => { builder.create_date(line.datetime()?); }
+                "modify date" => { builder.modify_date(line.datetime()?); }
+                "format" => {
+                    let value = line.code()?;
+                    builder.format(GdsFormat::from_u16(value).ok_or_else(|| line.error(format!("Invalid format value '{value}'")))?);
+                }
+                "mask" => masks.push(GdsMask::new(line.string()?)),
+                "end masks" => { builder.end_masks(line.parse()?); }
+                "libdirsize" => { builder.libdirsize(line.parse()?); }
+                "srfname" => { builder.srfname(line.string()?); }
+                "libsecur" => libsecur.push(line.access_control()?),
+                "reflib" => reflibs.push(line.string()?),
+                "font" => fonts.push(line.string()?),
+                "attrtable" => { builder.attrtable(line.string()?); }
+                "generations" => { builder.generations(line.parse()?); }
+                "block padding" => { builder.block_padding(line.parse()?); }
+                "raw" => raw_records.push(line.raw_record()?),
+                "Structure" => {
+                    let structure = Self::parse_structure(&line, lines)?;
+                    let name = structure.name.clone();
+                    if structures.insert(name.clone(), Arc::new(RwLock::new(structure))).is_some() {
+                        return Err(line.error(format!("Structure '{name}' is defined twice")));
+                    }
+                    structure_order.push(name);
+                }
+                _ => return Err(line.unknown_key()),
+            }
+        }
+
+        if !reflibs.is_empty() {
+            let reflibs: [String; 2] = reflibs.try_into()
+                .map_err(|r: Vec<String>| GdsReadError::Text(0, format!("Expect 2 reflib lines, but got {}", r.len())))?;
+            builder.reflibs(reflibs);
+        }
+        if !fonts.is_empty() {
+            let fonts: [String; 4] = fonts.try_into()
+                .map_err(|f: Vec<String>| GdsReadError::Text(0, format!("Expect 4 font lines, but got {}", f.len())))?;
+            builder.fonts(fonts);
+        }
+
+        builder.masks(masks);
+        builder.libsecur(libsecur);
+        builder.raw_records(raw_records);
+        builder.structures(structures);
+        builder.structure_order(structure_order);
+        Ok(builder.build()?)
+    }
+
+    fn parse_structure(header: &TextLine, lines: &mut Lines) -> GdsReadResult<GdsStructure> {
+        let mut structure = GdsStructure::default();
+        let mut named = false;
+
+        while let Some(line) = lines.next_if(|line| line.indent == header.indent + 1) {
+            if let Some(kind) = line.element_kind() {
+                let mut attrs = vec![];
+                while let Some(attr) = lines.next_if(|attr| attr.indent == line.indent + 1) {
+                    attrs.push(attr);
+                }
+                Self::parse_element(&mut structure, kind, &line, attrs)?;
+                continue;
+            }
+
+            match line.key.as_str() {
+                "name" => {
+                    structure.name = line.string()?;
+                    named = true;
+                }
+                "create date" => structure.create_date = line.datetime()?,
+                "modify date" => structure.modify_date = line.datetime()?,
+                "str_class" => structure.str_class = Some(line.code()?),
+                "raw" => structure.raw_records.push(line.raw_record()?),
+                _ => return Err(line.unknown_key()),
+            }
+        }
+
+        if !named {
+            return Err(header.error("Structure has no name"));
+        }
+        Ok(structure)
+    }
+
+    fn parse_element(structure: &mut GdsStructure, kind: GdsElementKind, header: &TextLine, attrs: Vec<TextLine>) -> GdsReadResult<()> {
+        let mut extras = ElementExtras::default();
+        let build = |e: &dyn std::fmt::Display| header.error(format!("Build {} failed for '{e}'", kind.to_string().to_lowercase()));
+
+        match kind {
+            GdsElementKind::Boundary => {
+                let mut builder = GdsBoundaryBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse()?); }
+                        "plex" => { builder.plex(line.parse()?); }
+                        "layer" => { builder.layer(line.parse()?); }
+                        "data_type" => { builder.data_type(line.parse()?); }
+                        "xy" => { builder.xy(line.coords()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Path => {
+                let mut builder = GdsPathBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse::<i16>()?); }
+                        "plex" => { builder.plex(line.parse::<i32>()?); }
+                        "layer" => { builder.layer(line.parse::<i16>()?); }
+                        "purpose layer" => { builder.purpose_layer(line.parse::<i16>()?); }
+                        "data_type" => { builder.data_type(line.parse::<i16>()?); }
+                        "path_type" => { builder.path_type(line.path_type()?); }
+                        "width" => { builder.width(line.parse::<i32>()?); }
+                        "xy" => { builder.xy(line.coords()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Sref => {
+                let mut builder = GdsSrefBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse()?); }
+                        "plex" => { builder.plex(line.parse()?); }
+                        "s_name" => { builder.s_name(line.string()?); }
+                        "coordinate" => { builder.position(line.coord()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                if let Some(transform) = extras.transform {
+                    builder.transform(transform);
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Aref => {
+                let mut builder = GdsArefBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse()?); }
+                        "plex" => { builder.plex(line.parse()?); }
+                        "s_name" => { builder.s_name(line.string()?); }
+                        "col" => { builder.col(line.parse()?); }
+                        "row" => { builder.row(line.parse()?); }
+                        "coordinate" => { builder.position(line.coord()?); }
+                        "col displacement" => { builder.col_displacement(line.coord()?); }
+                        "row displacement" => { builder.row_displacement(line.coord()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                if let Some(transform) = extras.transform {
+                    builder.transform(transform);
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Text => {
+                let mut builder = GdsTextBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse::<i16>()?); }
+                        "plex" => { builder.plex(line.parse::<i32>()?); }
+                        "layer" => { builder.layer(line.parse::<i16>()?); }
+                        "data_type" => { builder.text_type(line.parse::<i16>()?); }
+                        "presentation" => {
+                            let presentation = GdsPresentation::from_u16(line.code()?).map_err(|e| line.error(e))?;
+                            builder.presentation(presentation);
+                        }
+                        "path_type" => { builder.path_type(line.path_type()?); }
+                        "width" => { builder.width(line.parse::<i32>()?); }
+                        "coordinate" => { builder.position(line.coord()?); }
+                        "string" => { builder.string(line.string()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                if let Some(transform) = extras.transform {
+                    builder.transform(transform);
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Node => {
+                let mut builder = GdsNodeBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse()?); }
+                        "plex" => { builder.plex(line.parse()?); }
+                        "layer" => { builder.layer(line.parse()?); }
+                        "data_type" => { builder.node_type(line.parse()?); }
+                        "xy" => { builder.xy(line.coords()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+            GdsElementKind::Box => {
+                let mut builder = GdsBoxBuilder::default();
+                for line in &attrs {
+                    match line.key.as_str() {
+                        "elf_flags" => { builder.elf_flags(line.parse()?); }
+                        "plex" => { builder.plex(line.parse()?); }
+                        "layer" => { builder.layer(line.parse()?); }
+                        "box_type" => { builder.box_type(line.parse()?); }
+                        "xy" => { builder.xy(line.coords()?); }
+                        _ => extras.take(line)?,
+                    }
+                }
+                builder.properties(extras.properties).raw_records(extras.raw_records);
+                structure.push(builder.build().map_err(|e| build(&e))?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lines every kind of element may have
+#[derive(Default)]
+struct ElementExtras {
+    transform: Option<GdsTransform>,
+    properties: Vec<GdsProperty>,
+    raw_records: Vec<GdsRawRecord>,
+}
+
+impl ElementExtras {
+    fn take(&mut self, line: &TextLine) -> GdsReadResult<()> {
+        match line.key.as_str() {
+            "s_trans" => {
+                let words: Vec<&str> = line.value.split_whitespace().collect();
+                if let Some(word) = words.iter().find(|w| !matches!(**w, "x_mirror" | "absolute_magnification" | "absolute_angle")) {
+                    return Err(line.error(format!("Unknown transform flag '{word}'")));
+                }
+                let flag = GdsTransformFlag::new(
+                    words.contains(&"x_mirror"),
+                    words.contains(&"absolute_magnification"),
+                    words.contains(&"absolute_angle"),
+                );
+                self.transform = Some(GdsTransform { flag, magnification: None, angle: None });
+            }
+            "mag" => self.transform_of(line)?.magnification = Some(line.parse()?),
+            "angle" => self.transform_of(line)?.angle = Some(line.parse()?),
+            "property" => {
+                let (attr, value) = line.value.split_once(" = ")
+                    .ok_or_else(|| line.error("Expect 'property: <attr> = <value>'"))?;
+                let attr = attr.trim().parse().map_err(|_| line.error(format!("Invalid property attribute '{attr}'")))?;
+                self.properties.push(GdsProperty::new(attr, unescape(line, value)?));
+            }
+            "raw" => self.raw_records.push(line.raw_record()?),
+            _ => return Err(line.unknown_key()),
+        }
+        Ok(())
+    }
+
+    fn transform_of(&mut self, line: &TextLine) -> GdsReadResult<&mut GdsTransform> {
+        self.transform.as_mut().ok_or_else(|| line.error(format!("'{}' must follow 's_trans'", line.key)))
+    }
+}
+
+/// A non-empty line: `<indent><key>: <value>`, indented by four spaces a level
+struct TextLine {
+    number: usize,
+    indent: usize,
+    key: String,
+    value: String,
+}
+
+impl TextLine {
+    fn new(number: usize, line: &str) -> Self {
+        let content = line.trim_start_matches(' ');
+        let indent = (line.len() - content.len()) / 4;
+        let (key, value) = match content.split_once(':') {
+            Some((key, value)) => (key, value.strip_prefix(' ').unwrap_or(value)),
+            None => (content, ""),
+        };
+        Self { number, indent, key: key.trim_end().to_string(), value: value.to_string() }
+    }
+
+    fn error(&self, message: impl Into<String>) -> GdsReadError {
+        GdsReadError::Text(self.number, message.into())
+    }
+
+    fn unknown_key(&self) -> GdsReadError {
+        self.error(format!("Unexpected '{}'", self.key))
+    }
+
+    /// `<kind> Element` headers
+    fn element_kind(&self) -> Option<GdsElementKind> {
+        match self.key.strip_suffix(" Element")? {
+            "Boundary" => Some(GdsElementKind::Boundary),
+            "Path" => Some(GdsElementKind::Path),
+            "Sref" => Some(GdsElementKind::Sref),
+            "Aref" => Some(GdsElementKind::Aref),
+            "Text" => Some(GdsElementKind::Text),
+            "Node" => Some(GdsElementKind::Node),
+            "Box" => Some(GdsElementKind::Box),
+            _ => None,
+        }
+    }
+
+    fn parse<T: FromStr>(&self) -> GdsReadResult<T> {
+        parse_value(self, self.value.trim())
+    }
+
+    fn string(&self) -> GdsReadResult<String> {
+        unescape(self, &self.value)
+    }
+
+    /// `<open>a, b<close>`
+    fn pair<T: FromStr>(&self, open: &str, close: &str) -> GdsReadResult<(T, T)> {
+        parse_pair(self, &self.value, open, close)
+    }
+
+    /// `[x, y]`
+    fn coord(&self) -> GdsReadResult<GdsCoord> {
+        let (x, y) = self.pair("[", "]")?;
+        Ok(GdsCoord::new(x, y))
+    }
+
+    /// `[(x, y), (x, y), ...]`
+    fn coords(&self) -> GdsReadResult<Vec<GdsCoord>> {
+        let inner = self.value.trim()
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .ok_or_else(|| self.error("Expect '[(x, y), ...]'"))?;
+
+        let mut coords = vec![];
+        let mut rest = inner;
+        while let Some(start) = rest.find('(') {
+            let end = rest[start..].find(')')
+                .ok_or_else(|| self.error("Unclosed '(' in coordinates"))? + start;
+            let (x, y) = rest[start + 1..end].split_once(',')
+                .ok_or_else(|| self.error("Expect '(x, y)'"))?;
+            coords.push(GdsCoord::new(parse_value(self, x.trim())?, parse_value(self, y.trim())?));
+            rest = &rest[end + 1..];
+        }
+        Ok(coords)
+    }
+
+    /// `YYYY-MM-DD hh:mm:ss`
+    fn datetime(&self) -> GdsReadResult<GdsDateTime> {
+        let fields: Vec<&str> = self.value.trim().split(['-', ' ', ':']).collect();
+        let [year, month, day, hour, minute, second] = fields[..] else {
+            return Err(self.error("Expect 'YYYY-MM-DD hh:mm:ss'"));
+        };
+        Ok(GdsDateTime {
+            year: parse_value(self, year)?,
+            month: parse_value(self, month)?,
+            day: parse_value(self, day)?,
+            hour: parse_value(self, hour)?,
+            minute: parse_value(self, minute)?,
+            second: parse_value(self, second)?,
+        })
+    }
+
+    /// The number in parentheses of `Name (n)` values, or a hex `0x...` value
+    fn code(&self) -> GdsReadResult<u16> {
+        let value = self.value.trim();
+        if let Some(hex) = value.strip_prefix("0x") {
+            return u16::from_str_radix(hex, 16).map_err(|_| self.error(format!("Invalid hex value '{value}'")));
+        }
+        let start = value.find('(').ok_or_else(|| self.error("Expect a value in parentheses"))?;
+        let end = value[start..].find(')').ok_or_else(|| self.error("Unclosed '('"))? + start;
+        parse_value(self, value[start + 1..end].trim())
+    }
+
+    /// `Name (n)`, with `[begin, end]` extensions for pathtype 4, `-` for an omitted one
+    fn path_type(&self) -> GdsReadResult<GdsPathType> {
+        let value = self.code()?;
+        match GdsPathType::from_u16(value) {
+            Some(GdsPathType::CustomExtend { .. }) => {
+                let extensions = &self.value[self.value.find(')').unwrap_or(0) + 1..];
+                let (begin, end): (String, String) = parse_pair(self, extensions, "[", "]")?;
+                let extension = |value: &str| match value {
+                    "-" => Ok(None),
+                    value => parse_value(self, value).map(Some),
+                };
+                Ok(GdsPathType::CustomExtend { begin_extension: extension(&begin)?, end_extension: extension(&end)? })
+            }
+            Some(path_type) => Ok(path_type),
+            None => Err(self.error(format!("Invalid path type '{value}'"))),
+        }
+    }
+
+    /// `group <g> user <u> rights <r>`
+    fn access_control(&self) -> GdsReadResult<GdsAccessControl> {
+        let words: Vec<&str> = self.value.split_whitespace().collect();
+        let ["group", group, "user", user, "rights", rights] = words[..] else {
+            return Err(self.error("Expect 'group <g> user <u> rights <r>'"));
+        };
+        Ok(GdsAccessControl::new(parse_value(self, group)?, parse_value(self, user)?, parse_value(self, rights)?))
+    }
+
+    /// `<code> [(<type>)] at <position>: <data bytes in hex>`
+    fn raw_record(&self) -> GdsReadResult<GdsRawRecord> {
+        let expect = || self.error("Expect '<code> at <position>: <bytes>'");
+        let (code, rest) = self.value.split_once(" at ").ok_or_else(expect)?;
+        let (position, data) = rest.split_once(':').ok_or_else(expect)?;
+
+        let code = code.split_whitespace().next().ok_or_else(expect)?;
+        let code = code.strip_prefix("0x")
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error(format!("Invalid record code '{code}'")))?;
+        let data = data.split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16).map_err(|_| self.error(format!("Invalid byte '{byte}'"))))
+            .collect::<GdsReadResult<Vec<u8>>>()?;
+        Ok(GdsRawRecord::new(parse_value(self, position.trim())?, code, data))
+    }
+}
+
+fn parse_pair<T: FromStr>(line: &TextLine, value: &str, open: &str, close: &str) -> GdsReadResult<(T, T)> {
+    let expect = || line.error(format!("Expect '{open}a, b{close}'"));
+    let inner = value.trim()
+        .strip_prefix(open)
+        .and_then(|v| v.strip_suffix(close))
+        .ok_or_else(expect)?;
+    let (a, b) = inner.split_once(',').ok_or_else(expect)?;
+    Ok((parse_value(line, a.trim())?, parse_value(line, b.trim())?))
+}
+
+fn parse_value<T: FromStr>(line: &TextLine, value: &str) -> GdsReadResult<T> {
+    value.parse().map_err(|_| line.error(format!("Invalid value '{value}' for '{}'", line.key)))
+}
+
+/// Undo the escapes of `TextWriter`
+fn unescape(line: &TextLine, value: &str) -> GdsReadResult<String> {
+    if !value.contains('\\') {
+        return Ok(value.to_string());
+    }
+
+    let mut string = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => string.push('\\'),
+            Some('n') => string.push('\n'),
+            Some('r') => string.push('\r'),
+            Some('t') => string.push('\t'),
+            Some('u') => {
+                let rest = chars.as_str();
+                let code = rest.strip_prefix('{')
+                    .and_then(|r| r.split_once('}'))
+                    .and_then(|(hex, _)| u32::from_str_radix(hex, 16).ok().map(|code| (hex.len(), code)));
+                let Some((len, code)) = code else {
+                    return Err(line.error("Invalid '\\u{..}' escape"));
+                };
+                string.push(char::from_u32(code).ok_or_else(|| line.error("Invalid '\\u{..}' escape"))?);
+                chars = rest[len + 2..].chars();
+            }
+            _ => return Err(line.error("Invalid escape")),
+        }
+    }
+    Ok(string)
+}
@@ -49,6 +49,40 @@ pub enum GdsRecordType {
 
     BgnExtn = 0x3003,
     EndExtn = 0x3103,
+
+    PropAttr = 0x2B02,
+    PropValue = 0x2C06,
+
+    LibDirSize = 0x3902,
+    SrfName = 0x3A06,
+    LibSecur = 0x3B02,
+    StrClass = 0x3401,
+
+    // Obsolete or never released, kept as raw records
+    TapeNum = 0x3202,
+    TapeCode = 0x3302,
+    Spacing = 0x1802,
+    UInteger = 0x1D03,
+    UString = 0x1E06,
+    StypTable = 0x2406,
+    StrType = 0x2502,
+    ElKey = 0x2703,
+    LinkType = 0x2802,
+    LinkKeys = 0x2903,
+    Reserved = 0x3503,
+
+    // Obsolete elements, kept as raw records up to their ENDEL
+    TextNode = 0x1400,
+    Border = 0x3C00,
+    SoftFence = 0x3D00,
+    HardFence = 0x3E00,
+    SoftWire = 0x3F00,
+    HardWire = 0x4000,
+    PathPort = 0x4100,
+    NodePort = 0x4200,
+    UserConstraint = 0x4300,
+    SpacerError = 0x4400,
+    Contact = 0x4500,
 }
 
 impl GdsRecordType {
@@ -97,9 +131,57 @@ impl GdsRecordType {
             0x2E02 => Some(BoxType),
             0x3003 => Some(BgnExtn),
             0x3103 => Some(EndExtn),
+            0x2B02 => Some(PropAttr),
+            0x2C06 => Some(PropValue),
+            0x3902 => Some(LibDirSize),
+            0x3A06 => Some(SrfName),
+            0x3B02 => Some(LibSecur),
+            0x3401 => Some(StrClass),
+            0x3202 => Some(TapeNum),
+            0x3302 => Some(TapeCode),
+            0x1802 => Some(Spacing),
+            0x1D03 => Some(UInteger),
+            0x1E06 => Some(UString),
+            0x2406 => Some(StypTable),
+            0x2502 => Some(StrType),
+            0x2703 => Some(ElKey),
+            0x2802 => Some(LinkType),
+            0x2903 => Some(LinkKeys),
+            0x3503 => Some(Reserved),
+            0x1400 => Some(TextNode),
+            0x3C00 => Some(Border),
+            0x3D00 => Some(SoftFence),
+            0x3E00 => Some(HardFence),
+            0x3F00 => Some(SoftWire),
+            0x4000 => Some(HardWire),
+            0x4100 => Some(PathPort),
+            0x4200 => Some(NodePort),
+            0x4300 => Some(UserConstraint),
+            0x4400 => Some(SpacerError),
+            0x4500 => Some(Contact),
             _ => None,
         }
     }
+
+    /// Obsolete or never released records, which are not modeled but kept as `GdsRawRecord`s
+    pub fn is_obsolete(self) -> bool {
+        use GdsRecordType::*;
+        matches!(
+            self,
+            TapeNum | TapeCode | Spacing | UInteger | UString | StypTable | StrType
+                | ElKey | LinkType | LinkKeys | Reserved
+        ) || self.begins_obsolete_element()
+    }
+
+    /// Begins an obsolete element, which is kept raw up to and including its ENDEL
+    pub fn begins_obsolete_element(self) -> bool {
+        use GdsRecordType::*;
+        matches!(
+            self,
+            TextNode | Border | SoftFence | HardFence | SoftWire | HardWire
+                | PathPort | NodePort | UserConstraint | SpacerError | Contact
+        )
+    }
 }
 
 impl From<GdsRecordType> for u16 {
@@ -160,6 +242,36 @@ impl std::fmt::Display for GdsRecordType {
 
             BgnExtn => "BgnExtn",
             EndExtn => "EndExtn",
+
+            PropAttr => "PropAttr",
+            PropValue => "PropValue",
+
+            LibDirSize => "LibDirSize",
+            SrfName => "SrfName",
+            LibSecur => "LibSecur",
+            StrClass => "StrClass",
+            TapeNum => "TapeNum",
+            TapeCode => "TapeCode",
+            Spacing => "Spacing",
+            UInteger => "UInteger",
+            UString => "UString",
+            StypTable => "StypTable",
+            StrType => "StrType",
+            ElKey => "ElKey",
+            LinkType => "LinkType",
+            LinkKeys => "LinkKeys",
+            Reserved => "Reserved",
+            TextNode => "TextNode",
+            Border => "Border",
+            SoftFence => "SoftFence",
+            HardFence => "HardFence",
+            SoftWire => "SoftWire",
+            HardWire => "HardWire",
+            PathPort => "PathPort",
+            NodePort => "NodePort",
+            UserConstraint => "UserConstraint",
+            SpacerError => "SpacerError",
+            Contact => "Contact",
         };
         write!(f, "{}", name)
     }
@@ -1,28 +1,77 @@
+use std::collections::HashMap;
 use std::{fs::File, io::Write, path::Path};
-use crate::{GdsAref, GdsBoundary, GdsBox, GdsDateTime, GdsCoord, GdsFormat, GdsLibrary, GdsNode, GdsPath, GdsPathType, GdsPresentation, GdsSref, GdsStructure, GdsText, GdsTransform};
-use crate::io::{record::GdsRecordType, GdsWriteResult};
+use crate::{GdsAccessControl, GdsAref, GdsBoundary, GdsBox, GdsDateTime, GdsCoord, GdsElementKind, GdsElementRef, GdsFormat, GdsLibrary, GdsMask, GdsNode, GdsPath, GdsPathType, GdsPresentation, GdsProperty, GdsRawRecord, GdsSref, GdsStructure, GdsText, GdsTransform};
+use crate::{GdsProfile, GdsValidator};
+use crate::io::{record::GdsRecordType, GdsRecordScope, GdsWriteError, GdsWriteHandler, GdsWriteResult};
 
 pub struct GdsWriter<W> {
     writer: W,
+    written: usize,
+    /// Refuse to write libraries that break this profile
+    validation: Option<GdsProfile>,
+    /// Open scopes writing back raw records, innermost last
+    raw_scopes: Vec<RawScope>,
+    /// Handlers of raw records by record code
+    handlers: HashMap<u16, GdsWriteHandler>,
+}
+
+/// Raw records of the library, structure or element being written.
+struct RawScope {
+    scope: GdsRecordScope,
+    records: Vec<GdsRawRecord>,
+    /// Index of the next record to write
+    next: usize,
+    /// Records written in the scope, child scopes counting as one
+    units: usize,
 }
 
 impl GdsWriter<File> {
     pub fn open<P: AsRef<Path>>(path: P) -> GdsWriteResult<Self> {
         let file = File::create(path)?;
-        Ok(Self { writer: file })
+        Ok(Self::new(file))
     }
 }
 
 impl<W: Write> GdsWriter<W> {
     pub fn write(&mut self, gds: &GdsLibrary) -> GdsWriteResult<()> {
+        if let Some(profile) = self.validation {
+            let violations = GdsValidator::new(profile).validate(gds);
+            if !violations.is_empty() {
+                return Err(GdsWriteError::Invalid(violations));
+            }
+        }
+
+        self.begin_raw_scope(&gds.raw_records, GdsRecordScope::Library)?;
         self.write_header(&gds)?;
-        self.write_library(gds)
+        self.write_library(gds)?;
+        self.end_raw_scope();
+        Ok(())
     }
 }
 
 impl<W: std::io::Write> GdsWriter<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer }
+        Self { writer, written: 0, validation: None, raw_scopes: vec![], handlers: HashMap::new() }
+    }
+
+    /// Validate libraries against `profile` before writing anything,
+    /// failing with `GdsWriteError::Invalid` if they break it.
+    pub fn with_validation(mut self, profile: GdsProfile) -> Self {
+        self.validation = Some(profile);
+        self
+    }
+
+    /// Pass the raw records of type `code` to `handler` before they are written.
+    pub fn with_handler<F>(mut self, code: u16, handler: F) -> Self
+    where
+        F: FnMut(&GdsRawRecord, &GdsRecordScope) -> GdsWriteResult<Option<GdsRawRecord>> + 'static,
+    {
+        self.handlers.insert(code, Box::new(handler));
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
     }
 }
 
@@ -33,17 +82,46 @@ impl<W: std::io::Write> GdsWriter<W> {
 
     pub fn write_library(&mut self, gds: &GdsLibrary) -> GdsWriteResult<()> {
         self.write_begin_library(gds)?;
+        self.write_library_extras(gds)?;
         self.write_library_name(gds)?;
         self.write_library_options(gds)?;
         self.write_units(gds)?;
         self.write_structures(gds)?;
-        self.write_end_library()
+        self.write_end_library()?;
+        if gds.block_padding {
+            self.write_block_padding()?;
+        }
+        Ok(())
     }
 
     pub fn write_begin_library(&mut self, lib: &GdsLibrary) -> GdsWriteResult<()> {
         self.write_datetime_record(GdsRecordType::BgnLib, &lib.create_date, &lib.modify_date)
     }
 
+    /// [LIBDIRSIZE] [SRFNAME] [LIBSECUR]
+    pub fn write_library_extras(&mut self, lib: &GdsLibrary) -> GdsWriteResult<()> {
+        if let Some(libdirsize) = lib.libdirsize {
+            self.write_i16_record(GdsRecordType::LibDirSize, libdirsize)?;
+        }
+        if let Some(ref srfname) = lib.srfname {
+            self.write_string_record(GdsRecordType::SrfName, srfname)?;
+        }
+        if !lib.libsecur.is_empty() {
+            self.write_libsecur(&lib.libsecur)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_libsecur(&mut self, libsecur: &[GdsAccessControl]) -> GdsWriteResult<()> {
+        self.write_record(4 + 6 * libsecur.len(), GdsRecordType::LibSecur)?;
+        for acl in libsecur {
+            self.write_i16(acl.group)?;
+            self.write_i16(acl.user)?;
+            self.write_i16(acl.rights)?;
+        }
+        Ok(())
+    }
+
     pub fn write_library_name(&mut self, lib: &GdsLibrary) -> GdsWriteResult<()> {
         self.write_string_record(GdsRecordType::LibName, &lib.name)
     }
@@ -69,6 +147,10 @@ impl<W: std::io::Write> GdsWriter<W> {
             self.write_format(fmt)?;
         }
 
+        if !lib.masks.is_empty() || lib.end_masks {
+            self.write_masks(&lib.masks)?;
+        }
+
         Ok(())
     }
 
@@ -91,6 +173,13 @@ impl<W: std::io::Write> GdsWriter<W> {
         self.write_u16_record(GdsRecordType::Format, fmt.to_u16())
     }
 
+    pub fn write_masks(&mut self, masks: &[GdsMask]) -> GdsWriteResult<()> {
+        for mask in masks {
+            self.write_string_record(GdsRecordType::Mask, &mask.spec)?;
+        }
+        self.write_record(4, GdsRecordType::EndMasks)
+    }
+
     pub fn write_generations(&mut self, generations: i16) -> GdsWriteResult<()> {
         self.write_i16_record(GdsRecordType::Generations, generations)
     }
@@ -109,7 +198,7 @@ impl<W: std::io::Write> GdsWriter<W> {
     }
 
     pub fn write_structures(&mut self, gds: &GdsLibrary) -> GdsWriteResult<()> {
-        for structure in gds.structures.values() {
+        for structure in gds.ordered_structures() {
             self.write_structure(&structure.read().unwrap())?;
         }
         Ok(())
@@ -118,15 +207,31 @@ impl<W: std::io::Write> GdsWriter<W> {
     pub fn write_end_library(&mut self) -> GdsWriteResult<()> {
         self.write_empty_record(GdsRecordType::EndLib)
     }
+
+    /// Fill the last 2048-byte tape block with nulls
+    pub fn write_block_padding(&mut self) -> GdsWriteResult<()> {
+        const BLOCK_SIZE: usize = 2048;
+        let rest = self.written % BLOCK_SIZE;
+        if rest != 0 {
+            self.write_bytes(&vec![0; BLOCK_SIZE - rest])?;
+        }
+        Ok(())
+    }
 }
 
 
 impl<W: std::io::Write> GdsWriter<W> {
     pub fn write_structure(&mut self, structure: &GdsStructure) -> GdsWriteResult<()> {
+        self.begin_raw_scope(&structure.raw_records, GdsRecordScope::Structure(structure.name.clone()))?;
         self.write_structure_begin(structure)?;
         self.write_structure_name(structure)?;
+        if let Some(str_class) = structure.str_class {
+            self.write_u16_record(GdsRecordType::StrClass, str_class)?;
+        }
         self.write_structure_elements(structure)?;
-        self.write_structure_end()
+        self.write_structure_end()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     pub fn write_structure_begin(&mut self, structure: &GdsStructure) -> GdsWriteResult<()> {
@@ -142,39 +247,23 @@ impl<W: std::io::Write> GdsWriter<W> {
     }
 
     pub fn write_structure_elements(&mut self, structure: &GdsStructure) -> GdsWriteResult<()> {
-        for boundary in &structure.boundarys {
-            self.write_boundary_element(boundary)?;
-        }
-
-        for path in &structure.paths {
-            self.write_path_element(path)?;
-        }
-
-        for sref in &structure.srefs {
-            self.write_sref_element(sref)?;
-        }
-
-        for aref in &structure.arefs {
-            self.write_aref_element(aref)?;
-        }
-
-        for text in &structure.texts {
-            self.write_text_element(text)?;
-        }
-
-        for node in &structure.nodes {
-            self.write_node_element(node)?;
-        }
-
-        for bx in &structure.boxes {
-            self.write_box_element(bx)?;
+        for element in structure.elements() {
+            match element {
+                GdsElementRef::Boundary(boundary) => self.write_boundary_element(boundary)?,
+                GdsElementRef::Path(path) => self.write_path_element(path)?,
+                GdsElementRef::Sref(sref) => self.write_sref_element(sref)?,
+                GdsElementRef::Aref(aref) => self.write_aref_element(aref)?,
+                GdsElementRef::Text(text) => self.write_text_element(text)?,
+                GdsElementRef::Node(node) => self.write_node_element(node)?,
+                GdsElementRef::Box(boxx) => self.write_box_element(boxx)?,
+            }
         }
-
         Ok(())
     }
 
     /// <boundary>: BOUNDARY [ELFLAGS] [PLEX] LAYER DATATYPE XY
     pub fn write_boundary_element(&mut self, boundary: &GdsBoundary) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&boundary.raw_records, GdsElementKind::Boundary)?;
         self.write_empty_record(GdsRecordType::Boundary)?;
         if let Some(flags) = boundary.elf_flags {
             self.write_elflags_record(flags)?;
@@ -185,11 +274,15 @@ impl<W: std::io::Write> GdsWriter<W> {
         self.write_layer_record(boundary.layer)?;
         self.write_datatype_record(boundary.data_type)?;
         self.write_xy_record(&boundary.xy)?;
-        self.write_element_end_record()
+        self.write_properties(&boundary.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
-    /// <path>: PATH [ELFLAGS] [PLEX] LAYER DATATYPE [PATHTYPE][WIDTH] XY
+    /// <path>: PATH [ELFLAGS] [PLEX] LAYER DATATYPE [PATHTYPE] [WIDTH] [BGNEXTN] [ENDEXTN] XY [TEXTTYPE]
     pub fn write_path_element(&mut self, path: &GdsPath) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&path.raw_records, GdsElementKind::Path)?;
         self.write_empty_record(GdsRecordType::Path)?;
         if let Some(flags) = path.elf_flags {
             self.write_elflags_record(flags)?;
@@ -199,17 +292,34 @@ impl<W: std::io::Write> GdsWriter<W> {
         }
         self.write_layer_record(path.layer)?;
         self.write_datatype_record(path.data_type)?;
-        self.write_pathtype_record(path.path_type)?;
+        if let Some(path_type) = path.path_type {
+            self.write_pathtype_record(path_type)?;
+        }
         if let Some(width) = path.width {
             self.write_width_record(width)?;
         }
+        if let Some(GdsPathType::CustomExtend { begin_extension, end_extension }) = path.path_type {
+            if let Some(begin_extension) = begin_extension {
+                self.write_i32_record(GdsRecordType::BgnExtn, begin_extension)?;
+            }
+            if let Some(end_extension) = end_extension {
+                self.write_i32_record(GdsRecordType::EndExtn, end_extension)?;
+            }
+        }
         self.write_xy_record(&path.xy)?;
-        self.write_element_end_record()
+        if let Some(purpose) = path.purpose_layer {
+            self.write_texttype_record(purpose)?;
+        }
+        self.write_properties(&path.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     /// <sref>:   SREF [ELFLAGS] [PLEX] SNAME [<strans>] XY
     /// <strans>: STRANS [MAG] [ANGLE]
     pub fn write_sref_element(&mut self, sref: &GdsSref) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&sref.raw_records, GdsElementKind::Sref)?;
         self.write_empty_record(GdsRecordType::SRef)?;
         if let Some(flags) = sref.elf_flags {
             self.write_elflags_record(flags)?;
@@ -222,12 +332,16 @@ impl<W: std::io::Write> GdsWriter<W> {
             self.write_transform_record(transform)?;
         }
         self.write_xy_record(&[sref.position])?;
-        self.write_element_end_record()
+        self.write_properties(&sref.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     /// <aref>:   AREF [ELFLAGS] [PLEX] SNAME [<strans>] COLROW XY
     /// <strans>: STRANS [MAG] [ANGLE]
     pub fn write_aref_element(&mut self, aref: &GdsAref) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&aref.raw_records, GdsElementKind::Aref)?;
         self.write_empty_record(GdsRecordType::ARef)?;
         if let Some(flags) = aref.elf_flags {
             self.write_elflags_record(flags)?;
@@ -235,18 +349,23 @@ impl<W: std::io::Write> GdsWriter<W> {
         if let Some(plex) = aref.plex {
             self.write_plex_record(plex)?;
         }
+        self.write_sname_record(&aref.s_name)?;
         if let Some(transform) = &aref.transform {
             self.write_transform_record(transform)?;
         }
         self.write_colrow_record(aref.col, aref.row)?;
-        self.write_xy_record(&[aref.position])?;
-        self.write_element_end_record()
+        self.write_xy_record(&aref.lattice())?;
+        self.write_properties(&aref.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     /// <text>:     TEXT [ELFLAGS] [PLEX] LAYER <textbody>
     /// <textbody>: TEXTYPE [PRESENTATION] [PATHTYPE] [WIDTH] [<strans>] XY STRING
     /// <strans>:   STRANS [MAG] [ANGLE]
     pub fn write_text_element(&mut self, text: &GdsText) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&text.raw_records, GdsElementKind::Text)?;
         self.write_empty_record(GdsRecordType::Text)?;
         if let Some(flags) = text.elf_flags {
             self.write_elflags_record(flags)?;
@@ -259,7 +378,9 @@ impl<W: std::io::Write> GdsWriter<W> {
         if let Some(pres) = &text.presentation {
             self.write_presentation_record(pres)?;
         }
-        self.write_pathtype_record(text.path_type)?;
+        if let Some(path_type) = text.path_type {
+            self.write_pathtype_record(path_type)?;
+        }
         if let Some(width) = text.width {
             self.write_width_record(width)?;
         }
@@ -268,11 +389,15 @@ impl<W: std::io::Write> GdsWriter<W> {
         }
         self.write_xy_record(&[text.position])?;
         self.write_ascii_string_record(&text.string)?;
-        self.write_element_end_record()
+        self.write_properties(&text.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     /// <node>: NODE [ELFLAGS]. [PLEX] LAYER NODETYPE XY
     pub fn write_node_element(&mut self, node: &GdsNode) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&node.raw_records, GdsElementKind::Node)?;
         self.write_empty_record(GdsRecordType::Node)?;
         if let Some(flags) = node.elf_flags {
             self.write_elflags_record(flags)?;
@@ -283,11 +408,15 @@ impl<W: std::io::Write> GdsWriter<W> {
         self.write_layer_record(node.layer)?;
         self.write_nodetype_record(node.node_type)?;
         self.write_xy_record(&node.xy)?;
-        self.write_element_end_record()
+        self.write_properties(&node.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 
     /// <box>: NODE [ELFLAGS]. [PLEX] LAYER BOXTYPE XY
     pub fn write_box_element(&mut self, bx: &GdsBox) -> GdsWriteResult<()> {
+        self.begin_element_raw_scope(&bx.raw_records, GdsElementKind::Box)?;
         self.write_empty_record(GdsRecordType::Box)?;
         if let Some(flags) = bx.elf_flags {
             self.write_elflags_record(flags)?;
@@ -298,7 +427,10 @@ impl<W: std::io::Write> GdsWriter<W> {
         self.write_layer_record(bx.layer)?;
         self.write_boxtype_record(bx.box_type)?;
         self.write_xy_record(&bx.xy)?;
-        self.write_element_end_record()
+        self.write_properties(&bx.properties)?;
+        self.write_element_end_record()?;
+        self.end_raw_scope();
+        Ok(())
     }
 }
 
@@ -346,6 +478,16 @@ impl<W: Write> GdsWriter<W> {
         self.write_string_record(GdsRecordType::SName, sname)
     }
 
+    /// {<property>}*
+    /// <property>: PROPATTR PROPVALUE
+    pub fn write_properties(&mut self, properties: &[GdsProperty]) -> GdsWriteResult<()> {
+        for property in properties {
+            self.write_i16_record(GdsRecordType::PropAttr, property.attr)?;
+            self.write_string_record(GdsRecordType::PropValue, &property.value)?;
+        }
+        Ok(())
+    }
+
     pub fn write_transform_record(&mut self, tranform: &GdsTransform) -> GdsWriteResult<()> {
         let value = tranform.flag.to_u16();
         self.write_u16_record(GdsRecordType::STrans, value)?;
@@ -443,33 +585,99 @@ impl<W: Write> GdsWriter<W> {
     }
 
     fn write_record(&mut self, size: usize, tp: GdsRecordType) -> GdsWriteResult<()> {
+        // Nothing of a scope follows its end record
+        let ends = matches!(tp, GdsRecordType::EndEle | GdsRecordType::EndStr | GdsRecordType::EndLib);
+        self.write_pending_raw_records(ends)?;
+        if let Some(scope) = self.raw_scopes.last_mut() {
+            scope.units += 1;
+        }
         self.write_u16(size as u16)?;
         self.write_u16(tp as u16)?;
         Ok(())
     }
 }
 
+/// Method to write obsolete records back where they were read
+impl<W: Write> GdsWriter<W> {
+    /// Open the scope of a library, structure or element about to be written,
+    /// it counts as a single record of its parent
+    pub(super) fn begin_raw_scope(&mut self, records: &[GdsRawRecord], scope: GdsRecordScope) -> GdsWriteResult<()> {
+        self.write_pending_raw_records(false)?;
+        if let Some(parent) = self.raw_scopes.last_mut() {
+            parent.units += 1;
+        }
+        self.raw_scopes.push(RawScope { scope, records: records.to_vec(), next: 0, units: 0 });
+        Ok(())
+    }
+
+    fn begin_element_raw_scope(&mut self, records: &[GdsRawRecord], kind: GdsElementKind) -> GdsWriteResult<()> {
+        let structure = match self.raw_scopes.last() {
+            Some(RawScope { scope: GdsRecordScope::Structure(name), .. }) => name.clone(),
+            _ => String::new(),
+        };
+        self.begin_raw_scope(records, GdsRecordScope::Element(structure, kind))
+    }
+
+    pub(super) fn end_raw_scope(&mut self) {
+        self.raw_scopes.pop();
+    }
+
+    /// Write the raw records of the current scope due before the next record, or all left if `all`
+    fn write_pending_raw_records(&mut self, all: bool) -> GdsWriteResult<()> {
+        let Some(scope) = self.raw_scopes.last_mut() else {
+            return Ok(());
+        };
+        let units = scope.units;
+        let pending: Vec<GdsRawRecord> = scope.records[scope.next..]
+            .iter()
+            .take_while(|record| all || record.position <= units)
+            .cloned()
+            .collect();
+        scope.next += pending.len();
+
+        for record in pending {
+            let record = match (self.handlers.get_mut(&record.code), self.raw_scopes.last()) {
+                (Some(handler), Some(scope)) => handler(&record, &scope.scope)?,
+                _ => Some(record),
+            };
+            if let Some(record) = record {
+                self.write_raw_record(&record)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_raw_record(&mut self, record: &GdsRawRecord) -> GdsWriteResult<()> {
+        self.write_u16(record.size() as u16)?;
+        self.write_u16(record.code)?;
+        self.write_bytes(&record.data)
+    }
+}
+
 /// Method to write data
 impl<W: Write> GdsWriter<W> {
-    fn write_u16(&mut self, value: u16) -> GdsWriteResult<()> {
-        self.writer.write_all(&value.to_be_bytes())?;
+    fn write_bytes(&mut self, bytes: &[u8]) -> GdsWriteResult<()> {
+        self.writer.write_all(bytes)?;
+        self.written += bytes.len();
         Ok(())
     }
+
+    fn write_u16(&mut self, value: u16) -> GdsWriteResult<()> {
+        self.write_bytes(&value.to_be_bytes())
+    }
     
     fn write_i16(&mut self, value: i16) -> GdsWriteResult<()> {
-        self.writer.write_all(&value.to_be_bytes())?;
-        Ok(())
+        self.write_bytes(&value.to_be_bytes())
     }
 
     fn write_i32(&mut self, value: i32) -> GdsWriteResult<()> {
-        self.writer.write_all(&value.to_be_bytes())?;
-        Ok(())
+        self.write_bytes(&value.to_be_bytes())
     }
 
     fn write_string(&mut self, string: &str) -> GdsWriteResult<()> {
-        self.writer.write_all(string.as_bytes())?;
+        self.write_bytes(string.as_bytes())?;
         if string.len() % 2 != 0 {
-            self.writer.write_all(&[0])?;
+            self.write_bytes(&[0])?;
         }
         Ok(())
     }
@@ -478,41 +686,46 @@ impl<W: Write> GdsWriter<W> {
         let bytes = string.as_bytes();
         let len = bytes.len();
         if len < size {
-            self.writer.write_all(bytes)?;
-            self.writer.write_all(&vec![0; size - len])?;
+            self.write_bytes(bytes)?;
+            self.write_bytes(&vec![0; size - len])?;
         } else {
-            self.writer.write_all(&bytes[..size])?;
+            self.write_bytes(&bytes[..size])?;
         }
         Ok(())
     }
 
+    /// Encode an 8-byte IBM real. Every finite f64 in the IBM range is encoded exactly,
+    /// since the 56-bit mantissa can hold the 53-bit one at any hexadecimal alignment.
     fn write_f64_ibm(&mut self, value: f64) -> GdsWriteResult<()> {
-        let mut sign = 0i64;
-        let mut exponent = 0i64;
-        let mut mantissa = 0i64;
-
-        if value != 0.0 {
-            let bits = value.to_bits() as i64;
-            sign = (bits >> 63) & 0x1;
-            exponent = ((bits >> 52) & 0x7ff) - 1023;
-            mantissa = (bits & 0x000f_ffff_ffff_ffff) | 0x0010_0000_0000_0000;
-            mantissa <<= 11; // left align
-            mantissa >>= 1;  // discard one bit
-
-            let shift = (-exponent) & 0x3;
-            for _ in 0..shift {
-                mantissa >>= 1;
+        let mut ibm = 0u64;
+
+        if value != 0.0 && value.is_finite() {
+            let bits = value.to_bits();
+            let sign = bits >> 63;
+            let biased = ((bits >> 52) & 0x7ff) as i32;
+            let fraction = bits & 0x000f_ffff_ffff_ffff;
+
+            // value = mantissa * 2^power
+            let (mantissa, power) = if biased == 0 {
+                (fraction, -1074)
+            } else {
+                (fraction | 0x0010_0000_0000_0000, biased - 1075)
+            };
+
+            // Smallest exponent of 16 for which the mantissa fits in 56 bits
+            let length = 64 - mantissa.leading_zeros() as i32;
+            let exponent = (length + power + 3).div_euclid(4);
+            let shift = power + 56 - 4 * exponent;
+
+            if exponent + 64 > 127 {
+                ibm = (sign << 63) | 0x7fff_ffff_ffff_ffff;
+            } else if exponent + 64 >= 0 {
+                let mantissa = if shift >= 0 { mantissa << shift } else { mantissa >> -shift };
+                ibm = (sign << 63) | (((exponent + 64) as u64) << 56) | (mantissa & 0x00ff_ffff_ffff_ffff);
             }
-
-            exponent = (exponent + 3) >> 2;
-            exponent += 64;
         }
 
-        let ibm = ((sign << 63) | (exponent << 56) | ((mantissa >> 8) & 0x00ff_ffff_ffff_ffff)) as u64;
-        let ibm_bytes = ibm.to_be_bytes();
-        self.writer.write_all(&ibm_bytes)?;
-
-        Ok(())
+        self.write_bytes(&ibm.to_be_bytes())
     }
 
     fn write_datetime(&mut self, datetime: &GdsDateTime) -> GdsWriteResult<()> {
@@ -1,8 +1,28 @@
 
+use crate::GdsViolation;
+
 #[derive(Debug, thiserror::Error)]
 pub enum GdsWriteError {
     #[error("Io error '{0}'")]
     Io(#[from] std::io::Error),
+
+    #[error("No structure is open")]
+    NoOpenStructure,
+
+    #[error("Structure '{0}' is still open")]
+    StructureStillOpen(String),
+
+    #[error("Structure '{0}' is defined twice")]
+    DuplicateStructure(String),
+
+    #[error("Referenced structures are never defined: {0:?}")]
+    UndefinedStructures(Vec<String>),
+
+    #[error("Library breaks {} rule(s), first in {}", .0.len(), .0.first().map(|v| v.to_string()).unwrap_or_default())]
+    Invalid(Vec<GdsViolation>),
+
+    #[error("Record handler failed: {0}")]
+    Handler(String),
 }
 
 pub type GdsWriteResult<T> = Result<T, GdsWriteError>;
\ No newline at end of file
@@ -1,7 +1,9 @@
 mod error;
 mod binary;
 mod text;
+mod stream;
 
 pub use error::*;
 pub use binary::*;
-pub use text::*;
\ No newline at end of file
+pub use text::*;
+pub use stream::*;
\ No newline at end of file
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::{fs::File, io::Write, path::Path};
+
+use crate::{
+    GdsAref, GdsBoundary, GdsBox, GdsDateTime, GdsElementRef, GdsLibrary, GdsNode, GdsPath, GdsProfile, GdsSref, GdsStructure, GdsText, GdsValidator, GdsViolation
+};
+use crate::io::GdsRecordScope;
+use super::{GdsWriteError, GdsWriteResult, GdsWriter};
+
+/// Writes a library incrementally, so structures never have to be held in memory together.
+///
+/// The library header is written on creation, then whole structures with
+/// `write_structure` or open ones with `begin_structure`, their elements and
+/// `end_structure`. `finish` writes ENDLIB.
+pub struct GdsStreamWriter<W: Write> {
+    writer: GdsWriter<W>,
+    block_padding: bool,
+    open_structure: Option<String>,
+    /// Number of elements written to the open structure
+    open_elements: usize,
+    /// Refuse structures and elements that break its profile
+    validator: Option<GdsValidator>,
+    defined: HashSet<String>,
+    referenced: HashSet<String>,
+    allow_undefined: bool,
+}
+
+impl GdsStreamWriter<File> {
+    pub fn create<P: AsRef<Path>>(path: P, library: &GdsLibrary) -> GdsWriteResult<Self> {
+        Self::new(File::create(path)?, library)
+    }
+}
+
+impl<W: Write> GdsStreamWriter<W> {
+    /// Write the header of `library`, everything up to and including UNITS.
+    /// The structures of `library` are not written.
+    pub fn new(writer: W, library: &GdsLibrary) -> GdsWriteResult<Self> {
+        let mut writer = GdsWriter::new(writer);
+        writer.begin_raw_scope(&library.raw_records, GdsRecordScope::Library)?;
+        writer.write_header(library)?;
+        writer.write_begin_library(library)?;
+        writer.write_library_extras(library)?;
+        writer.write_library_name(library)?;
+        writer.write_library_options(library)?;
+        writer.write_units(library)?;
+
+        Ok(Self {
+            writer,
+            block_padding: library.block_padding,
+            open_structure: None,
+            open_elements: 0,
+            validator: None,
+            defined: HashSet::new(),
+            referenced: HashSet::new(),
+            allow_undefined: false,
+        })
+    }
+
+    /// Let `finish` succeed while SREF/AREF names are not defined in this library,
+    /// e.g. when they come from a reference library.
+    pub fn allow_undefined_references(mut self) -> Self {
+        self.allow_undefined = true;
+        self
+    }
+
+    /// Check every structure and element against `profile` before writing it,
+    /// failing with `GdsWriteError::Invalid` if it breaks the profile.
+    /// Undefined references are checked by `finish`, and allowed if the profile allows them.
+    pub fn with_validation(mut self, profile: GdsProfile) -> Self {
+        self.validator = Some(GdsValidator::new(profile));
+        self
+    }
+
+    /// Write a complete structure.
+    pub fn write_structure(&mut self, structure: &GdsStructure) -> GdsWriteResult<()> {
+        self.ensure_closed()?;
+        if let Some(validator) = &self.validator {
+            let violations = validator.validate_structure(structure);
+            if !violations.is_empty() {
+                return Err(GdsWriteError::Invalid(violations));
+            }
+        }
+        self.define(&structure.name)?;
+        for sref in structure.srefs() {
+            self.referenced.insert(sref.s_name.clone());
+        }
+        for aref in structure.arefs() {
+            self.referenced.insert(aref.s_name.clone());
+        }
+        self.writer.write_structure(structure)
+    }
+
+    /// Open a structure dated now, elements are written into it until `end_structure`.
+    pub fn begin_structure(&mut self, name: impl Into<String>) -> GdsWriteResult<()> {
+        let now = GdsDateTime::now();
+        self.begin_structure_with_dates(name, now.clone(), now)
+    }
+
+    pub fn begin_structure_with_dates(
+        &mut self,
+        name: impl Into<String>,
+        create_date: GdsDateTime,
+        modify_date: GdsDateTime
+    ) -> GdsWriteResult<()> {
+        self.ensure_closed()?;
+        let mut structure = GdsStructure::new(name);
+        structure.create_date = create_date;
+        structure.modify_date = modify_date;
+        if let Some(validator) = &self.validator {
+            let violations: Vec<_> = validator.validate_name(&structure.name)
+                .into_iter()
+                .map(|kind| GdsViolation { structure: structure.name.clone(), element: None, kind })
+                .collect();
+            if !violations.is_empty() {
+                return Err(GdsWriteError::Invalid(violations));
+            }
+        }
+        self.define(&structure.name)?;
+        self.writer.begin_raw_scope(&[], GdsRecordScope::Structure(structure.name.clone()))?;
+        self.writer.write_structure_begin(&structure)?;
+        self.writer.write_structure_name(&structure)?;
+        self.open_structure = Some(structure.name);
+        self.open_elements = 0;
+        Ok(())
+    }
+
+    pub fn end_structure(&mut self) -> GdsWriteResult<()> {
+        self.ensure_open()?;
+        self.writer.write_structure_end()?;
+        self.writer.end_raw_scope();
+        self.open_structure = None;
+        Ok(())
+    }
+
+    pub fn write_boundary(&mut self, boundary: &GdsBoundary) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Boundary(boundary))?;
+        self.writer.write_boundary_element(boundary)
+    }
+
+    pub fn write_path(&mut self, path: &GdsPath) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Path(path))?;
+        self.writer.write_path_element(path)
+    }
+
+    pub fn write_sref(&mut self, sref: &GdsSref) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Sref(sref))?;
+        self.referenced.insert(sref.s_name.clone());
+        self.writer.write_sref_element(sref)
+    }
+
+    pub fn write_aref(&mut self, aref: &GdsAref) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Aref(aref))?;
+        self.referenced.insert(aref.s_name.clone());
+        self.writer.write_aref_element(aref)
+    }
+
+    pub fn write_text(&mut self, text: &GdsText) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Text(text))?;
+        self.writer.write_text_element(text)
+    }
+
+    pub fn write_node(&mut self, node: &GdsNode) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Node(node))?;
+        self.writer.write_node_element(node)
+    }
+
+    pub fn write_box(&mut self, bx: &GdsBox) -> GdsWriteResult<()> {
+        self.ensure_element(GdsElementRef::Box(bx))?;
+        self.writer.write_box_element(bx)
+    }
+
+    /// Names referenced by SREF/AREF elements so far that no written structure defines, sorted.
+    pub fn undefined_structures(&self) -> Vec<String> {
+        let mut undefined: Vec<String> = self.referenced
+            .difference(&self.defined)
+            .cloned()
+            .collect();
+        undefined.sort();
+        undefined
+    }
+
+    /// Write ENDLIB and hand back the underlying writer.
+    /// Fails before writing anything if a structure is still open, or if a
+    /// referenced structure was never defined and that was not allowed.
+    pub fn finish(mut self) -> GdsWriteResult<W> {
+        self.ensure_closed()?;
+        let profile_allows = self.validator.as_ref().is_some_and(|v| v.profile().allow_undefined_references);
+        if !self.allow_undefined && !profile_allows {
+            let undefined = self.undefined_structures();
+            if !undefined.is_empty() {
+                return Err(GdsWriteError::UndefinedStructures(undefined));
+            }
+        }
+
+        self.writer.write_end_library()?;
+        self.writer.end_raw_scope();
+        if self.block_padding {
+            self.writer.write_block_padding()?;
+        }
+        Ok(self.writer.into_inner())
+    }
+}
+
+impl<W: Write> GdsStreamWriter<W> {
+    fn define(&mut self, name: &str) -> GdsWriteResult<()> {
+        if !self.defined.insert(name.to_string()) {
+            return Err(GdsWriteError::DuplicateStructure(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// An element can be written to the open structure, count it
+    fn ensure_element(&mut self, element: GdsElementRef<'_>) -> GdsWriteResult<()> {
+        self.ensure_open()?;
+        if let (Some(validator), Some(structure)) = (&self.validator, &self.open_structure) {
+            let violations: Vec<_> = validator.validate_element(element)
+                .into_iter()
+                .map(|kind| GdsViolation {
+                    structure: structure.clone(),
+                    element: Some((element.kind(), self.open_elements)),
+                    kind,
+                })
+                .collect();
+            if !violations.is_empty() {
+                return Err(GdsWriteError::Invalid(violations));
+            }
+        }
+        self.open_elements += 1;
+        Ok(())
+    }
+
+    fn ensure_open(&self) -> GdsWriteResult<()> {
+        match self.open_structure {
+            Some(_) => Ok(()),
+            None => Err(GdsWriteError::NoOpenStructure),
+        }
+    }
+
+    fn ensure_closed(&self) -> GdsWriteResult<()> {
+        match &self.open_structure {
+            Some(name) => Err(GdsWriteError::StructureStillOpen(name.clone())),
+            None => Ok(()),
+        }
+    }
+}
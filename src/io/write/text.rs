@@ -1,8 +1,11 @@
+use std::borrow::Cow;
 use std::{fs::File, path::Path};
-use crate::{GdsAref, GdsBoundary, GdsBox, GdsLibrary, GdsNode, GdsPath, GdsSref, GdsStructure, GdsText};
+use crate::{GdsAref, GdsBoundary, GdsBox, GdsCoord, GdsElementRef, GdsLibrary, GdsNode, GdsPath, GdsProperty, GdsRawRecord, GdsSref, GdsStructure, GdsText, GdsTransform};
 
 use super::GdsWriteResult;
 
+/// Writes a library as indented text, one field per line.
+/// Everything is kept, so `TextReader` reads the text back into the same library.
 pub struct TextWriter<W> {
     writer: W,
 }
@@ -32,7 +35,7 @@ impl<W: std::io::Write> TextWriter<W> {
         writeln!(self.writer, "version: {}", layout.version)?;
 
         self.write_indent(attr_indent)?;
-        writeln!(self.writer, "library name: {}", layout.name)?;
+        writeln!(self.writer, "library name: {}", escape(&layout.name))?;
 
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "units: ({}, {})", layout.usrunits_per_dbunit, layout.meters_per_dbunit)?;
@@ -43,7 +46,64 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "modify date: {}", layout.modify_date.to_string())?;
 
-        for (_key, structure) in &layout.structures {
+        if let Some(format) = layout.format {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "format: {:?} ({})", format, format.to_u16())?;
+        }
+
+        for mask in &layout.masks {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "mask: {}", escape(&mask.spec))?;
+        }
+
+        if layout.end_masks {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "end masks: true")?;
+        }
+
+        if let Some(libdirsize) = layout.libdirsize {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "libdirsize: {}", libdirsize)?;
+        }
+
+        if let Some(srfname) = &layout.srfname {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "srfname: {}", escape(srfname))?;
+        }
+
+        for acl in &layout.libsecur {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "libsecur: group {} user {} rights {}", acl.group, acl.user, acl.rights)?;
+        }
+
+        for reflib in layout.reflibs.iter().flatten() {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "reflib: {}", escape(reflib))?;
+        }
+
+        for font in layout.fonts.iter().flatten() {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "font: {}", escape(font))?;
+        }
+
+        if let Some(attrtable) = &layout.attrtable {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "attrtable: {}", escape(attrtable))?;
+        }
+
+        if let Some(generations) = layout.generations {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "generations: {}", generations)?;
+        }
+
+        if layout.block_padding {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "block padding: true")?;
+        }
+
+        self.write_raw_records(&layout.raw_records, attr_indent)?;
+
+        for structure in layout.ordered_structures() {
             self.write_structure(&structure.read().unwrap(), attr_indent)?;
         }
 
@@ -60,7 +120,7 @@ impl<W: std::io::Write> TextWriter<W> {
         let attr_indent = indent + 1;
 
         self.write_indent(attr_indent)?;
-        writeln!(self.writer, "name: {}", structure.name)?;
+        writeln!(self.writer, "name: {}", escape(&structure.name))?;
 
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "create date: {}", structure.create_date.to_string())?;
@@ -68,32 +128,23 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "modify date: {}", structure.modify_date.to_string())?;
 
-        for e in &structure.boundarys {
-            self.write_boundary(e, attr_indent)?;
-        }
-
-        for e in &structure.paths {
-            self.write_path(e, attr_indent)?;
-        }
-
-        for e in &structure.srefs {
-            self.write_sref(e, attr_indent)?;
-        }
-
-        for e in &structure.arefs {
-            self.write_aref(e, attr_indent)?;
-        }
-
-        for e in &structure.texts {
-            self.write_text(e, attr_indent)?;
+        if let Some(str_class) = structure.str_class {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "str_class: {:#06x}", str_class)?;
         }
 
-        for e in &structure.nodes {
-            self.write_node(e, attr_indent)?;
-        }
+        self.write_raw_records(&structure.raw_records, attr_indent)?;
 
-        for e in &structure.boxes {
-            self.write_box(e, attr_indent)?;
+        for element in structure.elements() {
+            match element {
+                GdsElementRef::Boundary(boundary) => self.write_boundary(boundary, attr_indent)?,
+                GdsElementRef::Path(path) => self.write_path(path, attr_indent)?,
+                GdsElementRef::Sref(sref) => self.write_sref(sref, attr_indent)?,
+                GdsElementRef::Aref(aref) => self.write_aref(aref, attr_indent)?,
+                GdsElementRef::Text(text) => self.write_text(text, attr_indent)?,
+                GdsElementRef::Node(node) => self.write_node(node, attr_indent)?,
+                GdsElementRef::Box(boxx) => self.write_box(boxx, attr_indent)?,
+            }
         }
 
         Ok(())
@@ -124,15 +175,10 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "data_type: {}", boundary.data_type)?;
 
-        self.write_indent(attr_indent)?;
-        write!(self.writer, "xy: [")?;
-        for (i, coord) in boundary.xy.iter().enumerate() {
-            if i > 0 {
-                write!(self.writer, ", ")?;
-            }
-            write!(self.writer, "({}, {})", coord.x, coord.y)?;
-        }
-        writeln!(self.writer, "]")?;
+        self.write_xy(&boundary.xy, attr_indent)?;
+
+        self.write_properties(&boundary.properties, attr_indent)?;
+        self.write_raw_records(&boundary.raw_records, attr_indent)?;
 
         Ok(())
     }
@@ -164,23 +210,20 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "data_type: {}", path.data_type)?;
 
-        self.write_indent(attr_indent)?;
-        writeln!(self.writer, "path_type: {}", path.path_type)?;
+        if let Some(path_type) = path.path_type {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "path_type: {}", path_type)?;
+        }
 
         if let Some(width) = path.width {
             self.write_indent(attr_indent)?;
             writeln!(self.writer, "width: {}", width)?;
         }
 
-        self.write_indent(attr_indent)?;
-        write!(self.writer, "xy: [")?;
-        for (i, coord) in path.xy.iter().enumerate() {
-            if i > 0 {
-                write!(self.writer, ", ")?;
-            }
-            write!(self.writer, "({}, {})", coord.x, coord.y)?;
-        }
-        writeln!(self.writer, "]")?;
+        self.write_xy(&path.xy, attr_indent)?;
+
+        self.write_properties(&path.properties, attr_indent)?;
+        self.write_raw_records(&path.raw_records, attr_indent)?;
 
         Ok(())
     }
@@ -202,31 +245,18 @@ impl<W: std::io::Write> TextWriter<W> {
         }
 
         self.write_indent(attr_indent)?;
-        writeln!(self.writer, "s_name: {}", sref.s_name)?;
+        writeln!(self.writer, "s_name: {}", escape(&sref.s_name))?;
 
         if let Some(transform) = &sref.transform {
-            self.write_indent(attr_indent)?;
-            write!(self.writer, "s_trans:")?;
-            if transform.flag.reflect {
-                write!(self.writer, " x_mirror")?;
-            }
-            if transform.flag.absolute_magnification {
-                write!(self.writer, "absolute magnification")?;
-            }
-            if transform.flag.absolute_angle {
-                write!(self.writer, "absolute angle")?;
-            }
-            writeln!(self.writer)?;
-        
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "mag: {}", transform.magnification())?;
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "angle: {}", transform.angle())?;
+            self.write_transform(transform, attr_indent)?;
         }
 
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "coordinate: [{}, {}]", sref.position.x, sref.position.y)?;
 
+        self.write_properties(&sref.properties, attr_indent)?;
+        self.write_raw_records(&sref.raw_records, attr_indent)?;
+
         Ok(())
     }
 
@@ -247,26 +277,10 @@ impl<W: std::io::Write> TextWriter<W> {
         }
 
         self.write_indent(attr_indent)?;
-        writeln!(self.writer, "s_name: {}", aref.s_name)?;
+        writeln!(self.writer, "s_name: {}", escape(&aref.s_name))?;
 
         if let Some(transform) = &aref.transform {
-            self.write_indent(attr_indent)?;
-            write!(self.writer, "s_trans:")?;
-            if transform.flag.reflect {
-                write!(self.writer, " x_mirror")?;
-            }
-            if transform.flag.absolute_magnification {
-                write!(self.writer, "absolute magnification")?;
-            }
-            if transform.flag.absolute_angle {
-                write!(self.writer, "absolute angle")?;
-            }
-            writeln!(self.writer)?;
-        
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "mag: {}", transform.magnification())?;
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "angle: {}", transform.angle())?;
+            self.write_transform(transform, attr_indent)?;
         }
 
         self.write_indent(attr_indent)?;
@@ -278,6 +292,15 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "coordinate: [{}, {}]", aref.position.x, aref.position.y)?;
 
+        self.write_indent(attr_indent)?;
+        writeln!(self.writer, "col displacement: [{}, {}]", aref.col_displacement.x, aref.col_displacement.y)?;
+
+        self.write_indent(attr_indent)?;
+        writeln!(self.writer, "row displacement: [{}, {}]", aref.row_displacement.x, aref.row_displacement.y)?;
+
+        self.write_properties(&aref.properties, attr_indent)?;
+        self.write_raw_records(&aref.raw_records, attr_indent)?;
+
         Ok(())
     }
 
@@ -305,11 +328,13 @@ impl<W: std::io::Write> TextWriter<W> {
 
         if let Some(presentation) = text.presentation {
             self.write_indent(attr_indent)?;
-            writeln!(self.writer, "presentation: {}", presentation)?;
+            writeln!(self.writer, "presentation: {} ({})", presentation, presentation.to_u16())?;
         }
 
-        self.write_indent(attr_indent)?;
-        writeln!(self.writer, "path_type: {}", text.path_type)?;
+        if let Some(path_type) = text.path_type {
+            self.write_indent(attr_indent)?;
+            writeln!(self.writer, "path_type: {}", path_type)?;
+        }
 
         if let Some(width) = text.width {
             self.write_indent(attr_indent)?;
@@ -317,30 +342,17 @@ impl<W: std::io::Write> TextWriter<W> {
         }
 
         if let Some(transform) = &text.transform {
-            self.write_indent(attr_indent)?;
-            write!(self.writer, "s_trans:")?;
-            if transform.flag.reflect {
-                write!(self.writer, " x_mirror")?;
-            }
-            if transform.flag.absolute_magnification {
-                write!(self.writer, "absolute magnification")?;
-            }
-            if transform.flag.absolute_angle {
-                write!(self.writer, "absolute angle")?;
-            }
-            writeln!(self.writer)?;
-            
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "mag: {}", transform.magnification())?;
-            self.write_indent(attr_indent)?;
-            writeln!(self.writer, "angle: {}", transform.angle())?;
+            self.write_transform(transform, attr_indent)?;
         }
 
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "coordinate: [{}, {}]", text.position.x, text.position.y)?;
 
         self.write_indent(attr_indent)?;
-        writeln!(self.writer, "string: {}", text.string)?;
+        writeln!(self.writer, "string: {}", escape(&text.string))?;
+
+        self.write_properties(&text.properties, attr_indent)?;
+        self.write_raw_records(&text.raw_records, attr_indent)?;
 
         Ok(())
     }
@@ -367,12 +379,10 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "data_type: {}", node.node_type)?;
 
-        self.write_indent(attr_indent)?;
-        write!(self.writer, "xy: [")?;
-        for coord in &node.xy {
-            write!(self.writer, "({}, {}), ", coord.x, coord.y)?;
-        }
-        writeln!(self.writer, "]")?;
+        self.write_xy(&node.xy, attr_indent)?;
+
+        self.write_properties(&node.properties, attr_indent)?;
+        self.write_raw_records(&node.raw_records, attr_indent)?;
 
         Ok(())
     }
@@ -399,22 +409,103 @@ impl<W: std::io::Write> TextWriter<W> {
         self.write_indent(attr_indent)?;
         writeln!(self.writer, "box_type: {}", boxx.box_type)?;
 
-        self.write_indent(attr_indent)?;
+        self.write_xy(&boxx.xy, attr_indent)?;
+
+        self.write_properties(&boxx.properties, attr_indent)?;
+        self.write_raw_records(&boxx.raw_records, attr_indent)?;
+
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> TextWriter<W> {
+    fn write_properties(&mut self, properties: &[GdsProperty], indent: usize) -> GdsWriteResult<()> {
+        for property in properties {
+            self.write_indent(indent)?;
+            writeln!(self.writer, "property: {} = {}", property.attr, escape(&property.value))?;
+        }
+        Ok(())
+    }
+
+    fn write_transform(&mut self, transform: &GdsTransform, indent: usize) -> GdsWriteResult<()> {
+        self.write_indent(indent)?;
+        write!(self.writer, "s_trans:")?;
+        if transform.flag.reflect {
+            write!(self.writer, " x_mirror")?;
+        }
+        if transform.flag.absolute_magnification {
+            write!(self.writer, " absolute_magnification")?;
+        }
+        if transform.flag.absolute_angle {
+            write!(self.writer, " absolute_angle")?;
+        }
+        writeln!(self.writer)?;
+
+        if let Some(magnification) = transform.magnification {
+            self.write_indent(indent)?;
+            writeln!(self.writer, "mag: {}", magnification)?;
+        }
+        if let Some(angle) = transform.angle {
+            self.write_indent(indent)?;
+            writeln!(self.writer, "angle: {}", angle)?;
+        }
+        Ok(())
+    }
+
+    fn write_xy(&mut self, xy: &[GdsCoord], indent: usize) -> GdsWriteResult<()> {
+        self.write_indent(indent)?;
         write!(self.writer, "xy: [")?;
-        for coord in &boxx.xy {
-            write!(self.writer, "({}, {}), ", coord.x, coord.y)?;
+        for (i, coord) in xy.iter().enumerate() {
+            if i > 0 {
+                write!(self.writer, ", ")?;
+            }
+            write!(self.writer, "({}, {})", coord.x, coord.y)?;
         }
         writeln!(self.writer, "]")?;
+        Ok(())
+    }
 
+    /// `raw: <code> (<type>) at <position>: <data bytes in hex>`
+    fn write_raw_records(&mut self, records: &[GdsRawRecord], indent: usize) -> GdsWriteResult<()> {
+        for record in records {
+            self.write_indent(indent)?;
+            write!(self.writer, "raw: {:#06x}", record.code)?;
+            if let Some(tp) = record.record_type() {
+                write!(self.writer, " ({})", tp)?;
+            }
+            write!(self.writer, " at {}:", record.position)?;
+            for byte in &record.data {
+                write!(self.writer, " {:02x}", byte)?;
+            }
+            writeln!(self.writer)?;
+        }
         Ok(())
     }
-}
 
-impl<W: std::io::Write> TextWriter<W> {
     fn write_indent(&mut self, level: usize) -> GdsWriteResult<()> {
         for _ in 0..level {
             write!(self.writer, "    ")?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Escape backslashes and control characters so every string stays on its line
+fn escape(string: &str) -> Cow<'_, str> {
+    if !string.chars().any(|c| c == '\\' || c.is_control()) {
+        return Cow::Borrowed(string);
+    }
+
+    let mut escaped = String::with_capacity(string.len() + 2);
+    for c in string.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
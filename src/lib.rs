@@ -1,6 +1,10 @@
 mod models;
 mod io;
 mod library;
+mod validate;
+mod render;
 
 pub use library::*;
+pub use validate::*;
+pub use render::*;
 
@@ -1,16 +1,22 @@
 
-use std::collections::HashMap;
-use std::io::Read;
-use std::io::Seek;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufWriter, Read};
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::RwLock;
 use derive_builder::Builder;
+use flate2::Compression;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
 
 pub use crate::models::*;
 pub use crate::io::*;
+use crate::render::{ImageRenderer, SvgRenderer};
+use crate::{GdsImage, GdsRenderResult, RasterOptions, SvgOptions};
 
 #[derive(Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option))]
 pub struct GdsLibrary {
     pub version: i16,
@@ -18,6 +24,16 @@ pub struct GdsLibrary {
     pub modify_date: GdsDateTime,
     pub name: String,
 
+    /// LIBDIRSIZE, number of pages of the library directory
+    #[builder(default)]
+    pub libdirsize: Option<i16>,
+    /// SRFNAME, name of the spacing rules file
+    #[builder(default)]
+    pub srfname: Option<String>,
+    /// LIBSECUR, the access control list
+    #[builder(default)]
+    pub libsecur: Vec<GdsAccessControl>,
+
     #[builder(default)]
     pub reflibs: Option<[String; 2]>,
     #[builder(default)]
@@ -28,21 +44,102 @@ pub struct GdsLibrary {
     pub generations: Option<i16>,
     #[builder(default)]
     pub format: Option<GdsFormat>,
+    /// Layer and datatype masks of a filtered format, written after FORMAT and followed by ENDMASKS
+    #[builder(default)]
+    pub masks: Vec<GdsMask>,
+    /// ENDMASKS was read, as it is after masks; set it to write ENDMASKS without masks
+    #[builder(default)]
+    pub end_masks: bool,
 
     pub usrunits_per_dbunit: f64,
     pub meters_per_dbunit: f64,
 
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::structures"))]
     pub structures: HashMap<String, Arc<RwLock<GdsStructure>>>,
+    /// Order in which structures are written, normally the order they were read in.
+    /// Structures missing from it are written afterwards, sorted by name.
+    #[builder(default)]
+    pub structure_order: Vec<String>,
+
+    /// Pad the stream with nulls after ENDLIB up to a multiple of the 2048-byte tape block
+    #[builder(default)]
+    pub block_padding: bool,
+
+    /// Obsolete records found in the library, such as TAPENUM and TAPECODE,
+    /// positioned by the number of header records and structures before them
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
 
 impl GdsLibrary {
+    /// Insert `structure`, replacing any structure with the same name, and keep it in write order.
+    pub fn add_structure(&mut self, structure: GdsStructure) -> Arc<RwLock<GdsStructure>> {
+        let name = structure.name.clone();
+        let structure = Arc::new(RwLock::new(structure));
+        if self.structures.insert(name.clone(), structure.clone()).is_none() {
+            self.structure_order.push(name);
+        }
+        structure
+    }
+
+    /// Structures in write order, see `structure_order`.
+    pub fn ordered_structures(&self) -> Vec<&Arc<RwLock<GdsStructure>>> {
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::with_capacity(self.structures.len());
+        for name in &self.structure_order {
+            if let Some(structure) = self.structures.get(name) {
+                if visited.insert(name.as_str()) {
+                    ordered.push(structure);
+                }
+            }
+        }
+
+        let mut rest: Vec<_> = self.structures
+            .iter()
+            .filter(|(name, _)| !visited.contains(name.as_str()))
+            .collect();
+        rest.sort_by(|a, b| a.0.cmp(b.0));
+        ordered.extend(rest.into_iter().map(|(_, s)| s));
+
+        ordered
+    }
+
+    /// Load a `.gds` file, or a gzip-compressed one, recognized by its magic bytes.
     pub fn load_file<P: AsRef<Path>>(path: P) -> GdsReadResult<Self> {
-        let mut reader = GdsReader::open(path)?;
+        Self::read_auto(File::open(path)?)
+    }
+
+    /// Like `load_file`, also returning the diagnostics collected in recovery mode.
+    pub fn load_file_with_options<P: AsRef<Path>>(path: P, options: ReadOptions) -> GdsReadResult<(Self, Vec<GdsDiagnostic>)> {
+        Self::read_auto_with_options(File::open(path)?, options)
+    }
+
+    /// Load a library from the text written by `save_text_file`.
+    pub fn load_text_file<P: AsRef<Path>>(path: P) -> GdsReadResult<Self> {
+        let mut reader = TextReader::open(path)?;
         reader.read()
     }
 
+    /// Save as GDSII, gzip-compressed if the file name ends with `.gz`.
     pub fn save_gds_file<P: AsRef<Path>>(&self, path: P) -> GdsWriteResult<()> {
-        let mut writer = GdsWriter::open(path)?;
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz")) {
+            self.write_gz(BufWriter::new(File::create(path)?))
+        } else {
+            let mut writer = GdsWriter::open(path)?;
+            writer.write(self)
+        }
+    }
+
+    /// Load an OASIS file, naming the library after the file.
+    pub fn load_oasis<P: AsRef<Path>>(path: P) -> OasisReadResult<Self> {
+        let mut reader = OasisReader::open(path)?;
+        reader.read()
+    }
+
+    /// Save as OASIS, see `OasisWriter` for what can not be represented.
+    pub fn save_oasis<P: AsRef<Path>>(&self, path: P) -> OasisWriteResult<()> {
+        let mut writer = OasisWriter::open(path)?;
         writer.write(self)
     }
 
@@ -51,18 +148,152 @@ impl GdsLibrary {
         writer.write(self)
     }
 
-    pub fn read<R: Read + Seek>(reader: R) -> GdsReadResult<Self> {
+    pub fn read<R: Read>(reader: R) -> GdsReadResult<Self> {
         let mut reader = GdsReader::new(reader)?;
         reader.read()
     }
-    
+
+    pub fn read_with_options<R: Read>(reader: R, options: ReadOptions) -> GdsReadResult<(Self, Vec<GdsDiagnostic>)> {
+        let mut reader = GdsReader::new(reader)?.with_options(options);
+        let library = reader.read()?;
+        Ok((library, reader.take_diagnostics()))
+    }
+
+    /// Read a gzip-compressed stream.
+    pub fn read_gz<R: Read>(reader: R) -> GdsReadResult<Self> {
+        Self::read(MultiGzDecoder::new(reader))
+    }
+
+    /// Read a stream that may be gzip-compressed, recognized by its magic bytes.
+    pub fn read_auto<R: Read>(reader: R) -> GdsReadResult<Self> {
+        Self::read_auto_with_options(reader, ReadOptions::default()).map(|(library, _)| library)
+    }
+
+    pub fn read_auto_with_options<R: Read>(mut reader: R, options: ReadOptions) -> GdsReadResult<(Self, Vec<GdsDiagnostic>)> {
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        // A single read may return fewer bytes on pipes and sockets
+        let mut magic = Vec::with_capacity(GZIP_MAGIC.len());
+        reader.by_ref().take(GZIP_MAGIC.len() as u64).read_to_end(&mut magic)?;
+
+        let gzip = magic == GZIP_MAGIC;
+        let reader = std::io::Cursor::new(magic).chain(reader);
+        if gzip {
+            Self::read_with_options(MultiGzDecoder::new(reader), options)
+        } else {
+            Self::read_with_options(reader, options)
+        }
+    }
+
     pub fn write_gds<W: std::io::Write>(&self, writer: W) -> GdsWriteResult<()> {
         let mut writer = GdsWriter::new(writer);
         writer.write(self)
     }
 
+    /// Write gzip-compressed GDSII.
+    pub fn write_gz<W: std::io::Write>(&self, writer: W) -> GdsWriteResult<()> {
+        let mut writer = GdsWriter::new(GzEncoder::new(writer, Compression::default()));
+        writer.write(self)?;
+        writer.into_inner().finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn read_oasis<R: Read>(reader: R) -> OasisReadResult<Self> {
+        let mut reader = OasisReader::new(reader);
+        reader.read()
+    }
+
+    pub fn write_oasis<W: std::io::Write>(&self, writer: W) -> OasisWriteResult<()> {
+        let mut writer = OasisWriter::new(writer);
+        writer.write(self)
+    }
+
+    /// Load a CIF file, see `CifReader` for how it maps onto structures.
+    pub fn load_cif<P: AsRef<Path>>(path: P) -> CifReadResult<Self> {
+        let mut reader = CifReader::open(path)?;
+        reader.read()
+    }
+
+    /// Save as CIF, see `CifWriter` for what can not be represented.
+    pub fn save_cif<P: AsRef<Path>>(&self, path: P) -> CifWriteResult<()> {
+        let mut writer = CifWriter::open(path)?;
+        writer.write(self)
+    }
+
+    pub fn read_cif<R: Read>(reader: R) -> CifReadResult<Self> {
+        let mut reader = CifReader::new(reader);
+        reader.read()
+    }
+
+    pub fn write_cif<W: std::io::Write>(&self, writer: W) -> CifWriteResult<()> {
+        let mut writer = CifWriter::new(writer);
+        writer.write(self)
+    }
+
+    /// Save `cell` as a DXF drawing.
+    pub fn save_dxf<P: AsRef<Path>>(&self, path: P, cell: &str, options: DxfOptions) -> DxfWriteResult<()> {
+        let mut writer = DxfWriter::open(path)?.with_options(options);
+        writer.write(self, cell)
+    }
+
+    pub fn write_dxf<W: std::io::Write>(&self, writer: W, cell: &str, options: DxfOptions) -> DxfWriteResult<()> {
+        let mut writer = DxfWriter::new(writer).with_options(options);
+        writer.write(self, cell)
+    }
+
+    /// Load a DEF design into a library of its own, see `DefReader::read_into`
+    /// to place the cells of an existing library.
+    pub fn load_def<P: AsRef<Path>>(path: P) -> DefReadResult<Self> {
+        let mut reader = DefReader::open(path)?;
+        reader.read()
+    }
+
+    pub fn read_def<R: Read>(reader: R) -> DefReadResult<Self> {
+        let mut reader = DefReader::new(reader);
+        reader.read()
+    }
+
+    /// Save `cells` as LEF macros.
+    pub fn save_lef<P: AsRef<Path>>(&self, path: P, cells: &[&str], options: LefOptions) -> LefWriteResult<()> {
+        let mut writer = LefWriter::open(path)?.with_options(options);
+        writer.write(self, cells)
+    }
+
+    pub fn write_lef<W: std::io::Write>(&self, writer: W, cells: &[&str], options: LefOptions) -> LefWriteResult<()> {
+        let mut writer = LefWriter::new(writer).with_options(options);
+        writer.write(self, cells)
+    }
+
+    pub fn read_text<R: BufRead>(reader: R) -> GdsReadResult<Self> {
+        let mut reader = TextReader::new(reader);
+        reader.read()
+    }
+
     pub fn write_text<W: std::io::Write>(&self, writer: W) -> GdsWriteResult<()> {
         let mut writer = TextWriter::new(writer);
         writer.write(self)
     }
+
+    /// Draw `cell` as an SVG document, expanding instances `options.depth` levels deep.
+    pub fn render_svg(&self, cell: &str, options: &SvgOptions) -> GdsRenderResult<String> {
+        SvgRenderer::new(self, options).render(cell)
+    }
+
+    /// Draw `cell` into an RGBA image, e.g. to save it as a PNG thumbnail.
+    pub fn render_image(&self, cell: &str, options: &RasterOptions) -> GdsRenderResult<GdsImage> {
+        ImageRenderer::new(self, options).render(cell)
+    }
+
+    /// Serialize the whole library as JSON, structures sorted by name.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Load a library from the JSON written by `to_json`.
+    /// Fails if a structure is stored under a name other than its own.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
 }
@@ -1,7 +1,13 @@
 use derive_builder::Builder;
-use crate::{GdsCoord, GdsTransform};
+use crate::{GdsCoord, GdsProperty, GdsRawRecord, GdsTransform};
 
+/// An array of structure instances placed on a (possibly non-orthogonal) lattice.
+///
+/// GDSII stores three points for an AREF: the array origin, the point displaced
+/// from the origin by `col` column pitches and the point displaced by `row` row
+/// pitches. Here the latter two are kept relative to `position`.
 #[derive(Debug, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option))]
 pub struct GdsAref {
     #[builder(default)]
@@ -10,25 +16,86 @@ pub struct GdsAref {
     pub plex: Option<i32>,
 
     pub s_name: String,
-    
+
     #[builder(default)]
     pub transform: Option<GdsTransform>,
-    
+
     pub col: i16,
     pub row: i16,
 
+    /// Origin of the array, i.e. the position of instance (0, 0)
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coord"))]
     pub position: GdsCoord,
+    /// Displacement from `position` across all `col` columns
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coord"))]
+    pub col_displacement: GdsCoord,
+    /// Displacement from `position` across all `row` rows
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coord"))]
+    pub row_displacement: GdsCoord,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
+}
+
+impl GdsArefBuilder {
+    /// Set `col` and `row` from a COLROW record.
+    pub fn col_row(&mut self, (col, row): (i16, i16)) -> &mut Self {
+        self.col(col).row(row)
+    }
+
+    /// Set the position and displacements from the three XY points of an AREF record.
+    pub fn lattice(&mut self, [origin, col_corner, row_corner]: [GdsCoord; 3]) -> &mut Self {
+        self.position(origin)
+            .col_displacement(GdsCoord::new(col_corner.x - origin.x, col_corner.y - origin.y))
+            .row_displacement(GdsCoord::new(row_corner.x - origin.x, row_corner.y - origin.y))
+    }
 }
 
 impl GdsAref {
-    pub fn new(ref_name: impl Into<String>, row: i16, col: i16, position: impl Into<GdsCoord>, transform: Option<GdsTransform>) -> Self {
+    /// Create an array whose neighbouring instances are `row_pitch` and `col_pitch` apart.
+    pub fn new(
+        ref_name: impl Into<String>,
+        row: i16,
+        col: i16,
+        position: impl Into<GdsCoord>,
+        row_pitch: impl Into<GdsCoord>,
+        col_pitch: impl Into<GdsCoord>,
+        transform: Option<GdsTransform>
+    ) -> Self {
+        let row_pitch = row_pitch.into();
+        let col_pitch = col_pitch.into();
         Self {
             elf_flags: None,
             plex: None,
             s_name: ref_name.into(),
             transform,
             col, row,
-            position: position.into()
+            position: position.into(),
+            col_displacement: GdsCoord::new(col_pitch.x * col as i32, col_pitch.y * col as i32),
+            row_displacement: GdsCoord::new(row_pitch.x * row as i32, row_pitch.y * row as i32),
+            properties: vec![],
+            raw_records: vec![],
+        }
+    }
+
+    /// Build an array from the three XY points of an AREF record.
+    pub fn from_lattice(ref_name: impl Into<String>, row: i16, col: i16, lattice: [GdsCoord; 3], transform: Option<GdsTransform>) -> Self {
+        let [origin, col_corner, row_corner] = lattice;
+        Self {
+            elf_flags: None,
+            plex: None,
+            s_name: ref_name.into(),
+            transform,
+            col, row,
+            position: origin,
+            col_displacement: GdsCoord::new(col_corner.x - origin.x, col_corner.y - origin.y),
+            row_displacement: GdsCoord::new(row_corner.x - origin.x, row_corner.y - origin.y),
+            properties: vec![],
+            raw_records: vec![],
         }
     }
 
@@ -36,6 +103,60 @@ impl GdsAref {
         self.position
     }
 
+    /// The three XY points as stored in GDSII: origin, column corner and row corner.
+    pub fn lattice(&self) -> [GdsCoord; 3] {
+        let origin = self.position;
+        [
+            origin,
+            GdsCoord::new(origin.x + self.col_displacement.x, origin.y + self.col_displacement.y),
+            GdsCoord::new(origin.x + self.row_displacement.x, origin.y + self.row_displacement.y),
+        ]
+    }
+
+    /// Vector between two neighbouring columns.
+    pub fn col_pitch(&self) -> GdsCoord {
+        Self::pitch(self.col_displacement, self.col)
+    }
+
+    /// Vector between two neighbouring rows.
+    pub fn row_pitch(&self) -> GdsCoord {
+        Self::pitch(self.row_displacement, self.row)
+    }
+
+    /// Position of the instance at (`row`, `col`), both counted from zero.
+    pub fn instance_position(&self, row: i16, col: i16) -> GdsCoord {
+        let offset = |displacement: i32, index: i16, count: i16| {
+            if count == 0 {
+                0
+            } else {
+                (displacement as i64 * index as i64 / count as i64) as i32
+            }
+        };
+        GdsCoord::new(
+            self.position.x
+                + offset(self.col_displacement.x, col, self.col)
+                + offset(self.row_displacement.x, row, self.row),
+            self.position.y
+                + offset(self.col_displacement.y, col, self.col)
+                + offset(self.row_displacement.y, row, self.row),
+        )
+    }
+
+    /// Positions of every instance, row by row.
+    pub fn positions(&self) -> Vec<GdsCoord> {
+        let mut positions = Vec::with_capacity(self.instance_count());
+        for row in 0..self.row {
+            for col in 0..self.col {
+                positions.push(self.instance_position(row, col));
+            }
+        }
+        positions
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.col.max(0) as usize * self.row.max(0) as usize
+    }
+
     pub fn magnification(&self) -> f64 {
         self.transform.map(|t| t.magnification()).unwrap_or(1.0)
     }
@@ -43,4 +164,12 @@ impl GdsAref {
     pub fn angle(&self) -> f64 {
         self.transform.map(|t| t.angle()).unwrap_or(0.0)
     }
-}
\ No newline at end of file
+
+    fn pitch(displacement: GdsCoord, count: i16) -> GdsCoord {
+        if count == 0 {
+            GdsCoord::new(0, 0)
+        } else {
+            GdsCoord::new(displacement.x / count as i32, displacement.y / count as i32)
+        }
+    }
+}
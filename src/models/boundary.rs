@@ -1,8 +1,9 @@
 use derive_builder::Builder;
 use reda_geometry::shape::Rect;
-use crate::GdsCoord;
+use crate::{GdsCoord, GdsProperty, GdsRawRecord};
 
 #[derive(Debug, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option))]
 pub struct GdsBoundary {
     #[builder(default)]
@@ -14,7 +15,15 @@ pub struct GdsBoundary {
 
     #[builder(default)]
     pub data_type: i16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coords"))]
     pub xy: Vec<GdsCoord>,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
 
 impl GdsBoundary {
@@ -30,6 +39,8 @@ impl GdsBoundary {
             layer,
             data_type: 0,
             xy,
+            properties: vec![],
+            raw_records: vec![],
         }
     }
     
@@ -46,6 +57,8 @@ impl GdsBoundary {
             layer,
             data_type: 0,
             xy,
+            properties: vec![],
+            raw_records: vec![],
         }
     }
 
@@ -56,6 +69,8 @@ impl GdsBoundary {
             layer,
             data_type: 0,
             xy: vec![],
+            properties: vec![],
+            raw_records: vec![],
         }
     }
 
@@ -1,7 +1,8 @@
 use derive_builder::Builder;
-use crate::GdsCoord;
+use crate::{GdsCoord, GdsProperty, GdsRawRecord};
 
 #[derive(Debug, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option))]
 pub struct GdsBox {
     #[builder(default)]
@@ -11,5 +12,13 @@ pub struct GdsBox {
     
     pub layer: i16,
     pub box_type: i16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coords"))]
     pub xy: Vec<GdsCoord>,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
\ No newline at end of file
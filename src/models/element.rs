@@ -0,0 +1,394 @@
+use reda_geometry::shape::Rect;
+
+use super::Affine;
+use crate::{GdsAref, GdsBoundary, GdsBox, GdsCoord, GdsElementKind, GdsNode, GdsPath, GdsPathType, GdsProperty, GdsSref, GdsText, GdsTransform, GdsTransformFlag};
+
+/// What elements of every kind have in common.
+pub trait GdsElementTrait {
+    fn kind(&self) -> GdsElementKind;
+
+    /// LAYER, `None` for srefs and arefs
+    fn layer(&self) -> Option<i16>;
+
+    /// DATATYPE, or the TEXTTYPE, NODETYPE or BOXTYPE, `None` for srefs and arefs
+    fn datatype(&self) -> Option<i16>;
+
+    /// Bounding box, `None` for srefs and arefs, whose extent is that of the structure they refer to
+    fn bbox(&self) -> Option<Rect<i32>>;
+
+    /// Move the element as if it were placed by an sref at `position` with `transform`:
+    /// reflection about the X-axis, magnification, rotation and then translation.
+    /// Coordinates are rounded to the grid, absolute magnifications and angles are kept.
+    fn transform_by(&mut self, position: GdsCoord, transform: Option<GdsTransform>);
+
+    fn elf_flags(&self) -> Option<i16>;
+
+    fn properties(&self) -> &[GdsProperty];
+
+    fn properties_mut(&mut self) -> &mut Vec<GdsProperty>;
+}
+
+macro_rules! impl_element_common {
+    ($($element:ty => $kind:ident),*) => {
+        $(
+            impl GdsElementTrait for $element {
+                fn kind(&self) -> GdsElementKind {
+                    GdsElementKind::$kind
+                }
+
+                fn layer(&self) -> Option<i16> {
+                    ElementGeometry::layer(self)
+                }
+
+                fn datatype(&self) -> Option<i16> {
+                    ElementGeometry::datatype(self)
+                }
+
+                fn bbox(&self) -> Option<Rect<i32>> {
+                    ElementGeometry::bbox(self)
+                }
+
+                fn transform_by(&mut self, position: GdsCoord, transform: Option<GdsTransform>) {
+                    ElementGeometry::transform_by(self, &Affine::placement(position, transform), transform)
+                }
+
+                fn elf_flags(&self) -> Option<i16> {
+                    self.elf_flags
+                }
+
+                fn properties(&self) -> &[GdsProperty] {
+                    &self.properties
+                }
+
+                fn properties_mut(&mut self) -> &mut Vec<GdsProperty> {
+                    &mut self.properties
+                }
+            }
+        )*
+    };
+}
+
+impl_element_common!(
+    GdsBoundary => Boundary, GdsPath => Path, GdsSref => Sref, GdsAref => Aref,
+    GdsText => Text, GdsNode => Node, GdsBox => Box
+);
+
+/// The parts of each kind that differ, for `impl_element_common`.
+trait ElementGeometry {
+    fn layer(&self) -> Option<i16>;
+    fn datatype(&self) -> Option<i16>;
+    fn bbox(&self) -> Option<Rect<i32>>;
+    fn transform_by(&mut self, affine: &Affine, transform: Option<GdsTransform>);
+}
+
+impl ElementGeometry for GdsBoundary {
+    fn layer(&self) -> Option<i16> {
+        Some(self.layer)
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        Some(self.data_type)
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        bounds(&self.xy)
+    }
+
+    fn transform_by(&mut self, affine: &Affine, _: Option<GdsTransform>) {
+        self.xy.iter_mut().for_each(|c| *c = apply(affine, *c));
+    }
+}
+
+impl ElementGeometry for GdsPath {
+    fn layer(&self) -> Option<i16> {
+        Some(self.layer)
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        Some(self.data_type)
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        GdsPath::bbox(self)
+    }
+
+    /// Widths and extensions grow with the magnification, negative (absolute) widths stay.
+    fn transform_by(&mut self, affine: &Affine, _: Option<GdsTransform>) {
+        self.xy.iter_mut().for_each(|c| *c = apply(affine, *c));
+        let magnification = affine.magnification();
+        let scale = |length: i32| (length as f64 * magnification).round() as i32;
+        if let Some(width) = self.width.filter(|&w| w > 0) {
+            self.width = Some(scale(width));
+        }
+        if let Some(GdsPathType::CustomExtend { begin_extension, end_extension }) = self.path_type {
+            self.path_type = Some(GdsPathType::CustomExtend {
+                begin_extension: begin_extension.map(scale),
+                end_extension: end_extension.map(scale),
+            });
+        }
+    }
+}
+
+impl ElementGeometry for GdsSref {
+    fn layer(&self) -> Option<i16> {
+        None
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        None
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        None
+    }
+
+    fn transform_by(&mut self, affine: &Affine, transform: Option<GdsTransform>) {
+        self.position = apply(affine, self.position);
+        self.transform = compose(transform, self.transform);
+    }
+}
+
+impl ElementGeometry for GdsAref {
+    fn layer(&self) -> Option<i16> {
+        None
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        None
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        None
+    }
+
+    /// The displacements turn with the array but do not move.
+    fn transform_by(&mut self, affine: &Affine, transform: Option<GdsTransform>) {
+        let origin = affine.apply(0.0, 0.0);
+        let turn = |c: GdsCoord| {
+            let (x, y) = affine.apply_coord(c);
+            GdsCoord::new((x - origin.0).round() as i32, (y - origin.1).round() as i32)
+        };
+        self.position = apply(affine, self.position);
+        self.col_displacement = turn(self.col_displacement);
+        self.row_displacement = turn(self.row_displacement);
+        self.transform = compose(transform, self.transform);
+    }
+}
+
+impl ElementGeometry for GdsText {
+    fn layer(&self) -> Option<i16> {
+        Some(self.layer)
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        Some(self.text_type)
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        bounds(&[self.position])
+    }
+
+    fn transform_by(&mut self, affine: &Affine, transform: Option<GdsTransform>) {
+        self.position = apply(affine, self.position);
+        if let Some(width) = self.width.filter(|&w| w > 0) {
+            self.width = Some((width as f64 * affine.magnification()).round() as i32);
+        }
+        self.transform = compose(transform, self.transform);
+    }
+}
+
+impl ElementGeometry for GdsNode {
+    fn layer(&self) -> Option<i16> {
+        Some(self.layer)
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        Some(self.node_type)
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        bounds(&self.xy)
+    }
+
+    fn transform_by(&mut self, affine: &Affine, _: Option<GdsTransform>) {
+        self.xy.iter_mut().for_each(|c| *c = apply(affine, *c));
+    }
+}
+
+impl ElementGeometry for GdsBox {
+    fn layer(&self) -> Option<i16> {
+        Some(self.layer)
+    }
+
+    fn datatype(&self) -> Option<i16> {
+        Some(self.box_type)
+    }
+
+    fn bbox(&self) -> Option<Rect<i32>> {
+        bounds(&self.xy)
+    }
+
+    fn transform_by(&mut self, affine: &Affine, _: Option<GdsTransform>) {
+        self.xy.iter_mut().for_each(|c| *c = apply(affine, *c));
+    }
+}
+
+fn apply(affine: &Affine, coord: GdsCoord) -> GdsCoord {
+    let (x, y) = affine.apply_coord(coord);
+    GdsCoord::new(x.round() as i32, y.round() as i32)
+}
+
+fn bounds(points: &[GdsCoord]) -> Option<Rect<i32>> {
+    let first = points.first()?;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (first.x, first.y, first.x, first.y);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    Some(Rect::new(GdsCoord::new(min_x, min_y), GdsCoord::new(max_x, max_y)))
+}
+
+/// The transform of an instance placed inside an instance with transform `outer`.
+/// A reflection turns the inner angle the other way, `None` if nothing is left to do.
+fn compose(outer: Option<GdsTransform>, inner: Option<GdsTransform>) -> Option<GdsTransform> {
+    let Some(outer) = outer else {
+        return inner;
+    };
+    let inner = inner.unwrap_or_else(GdsTransform::identity);
+    let magnification = if inner.flag.absolute_magnification {
+        inner.magnification()
+    } else {
+        outer.magnification() * inner.magnification()
+    };
+    let angle = if inner.flag.absolute_angle {
+        inner.angle()
+    } else if outer.flag.reflect {
+        outer.angle() - inner.angle()
+    } else {
+        outer.angle() + inner.angle()
+    }
+    .rem_euclid(360.0);
+
+    let flag = GdsTransformFlag::new(
+        outer.flag.reflect != inner.flag.reflect,
+        inner.flag.absolute_magnification,
+        inner.flag.absolute_angle,
+    );
+    if flag == GdsTransformFlag::default() && magnification == 1.0 && angle == 0.0 {
+        return None;
+    }
+    Some(GdsTransform {
+        flag,
+        magnification: if magnification == 1.0 { None } else { Some(magnification) },
+        angle: if angle == 0.0 { None } else { Some(angle) },
+    })
+}
+
+macro_rules! element_enums {
+    ($($kind:ident($element:ty)),*) => {
+        /// An element of any kind.
+        #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum GdsElement {
+            $($kind($element),)*
+        }
+
+        /// An element of a structure, see `GdsStructure::elements`.
+        /// Dereferences to the `GdsElementTrait` of the element.
+        #[derive(Debug, Clone, Copy)]
+        pub enum GdsElementRef<'a> {
+            $($kind(&'a $element),)*
+        }
+
+        /// An element of a structure that can be changed, see `GdsStructure::elements_mut`.
+        #[derive(Debug)]
+        pub enum GdsElementMut<'a> {
+            $($kind(&'a mut $element),)*
+        }
+
+        impl<'a> From<&'a GdsElement> for GdsElementRef<'a> {
+            fn from(element: &'a GdsElement) -> Self {
+                match element {
+                    $(GdsElement::$kind(element) => Self::$kind(element),)*
+                }
+            }
+        }
+
+        impl<'a> From<&'a mut GdsElement> for GdsElementMut<'a> {
+            fn from(element: &'a mut GdsElement) -> Self {
+                match element {
+                    $(GdsElement::$kind(element) => Self::$kind(element),)*
+                }
+            }
+        }
+
+        impl GdsElementRef<'_> {
+            pub fn cloned(self) -> GdsElement {
+                match self {
+                    $(Self::$kind(element) => GdsElement::$kind(element.clone()),)*
+                }
+            }
+        }
+
+        impl std::ops::Deref for GdsElement {
+            type Target = dyn GdsElementTrait;
+
+            fn deref(&self) -> &Self::Target {
+                match self {
+                    $(Self::$kind(element) => element,)*
+                }
+            }
+        }
+
+        impl std::ops::DerefMut for GdsElement {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                match self {
+                    $(Self::$kind(element) => element,)*
+                }
+            }
+        }
+
+        impl<'a> std::ops::Deref for GdsElementRef<'a> {
+            type Target = dyn GdsElementTrait + 'a;
+
+            fn deref(&self) -> &Self::Target {
+                match *self {
+                    $(Self::$kind(element) => element,)*
+                }
+            }
+        }
+
+        impl<'a> std::ops::Deref for GdsElementMut<'a> {
+            type Target = dyn GdsElementTrait + 'a;
+
+            fn deref(&self) -> &Self::Target {
+                match self {
+                    $(Self::$kind(element) => &**element,)*
+                }
+            }
+        }
+
+        impl std::ops::DerefMut for GdsElementMut<'_> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                match self {
+                    $(Self::$kind(element) => &mut **element,)*
+                }
+            }
+        }
+
+        $(
+            impl From<$element> for GdsElement {
+                fn from(element: $element) -> Self {
+                    Self::$kind(element)
+                }
+            }
+        )*
+    };
+}
+
+element_enums!(
+    Boundary(GdsBoundary), Path(GdsPath), Sref(GdsSref), Aref(GdsAref),
+    Text(GdsText), Node(GdsNode), Box(GdsBox)
+);
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::{GdsCoord, GdsLibrary, GdsRenderError, GdsRenderResult, GdsStructure, GdsTransform};
+
+/// A point in database units, in the coordinates of the top cell
+pub(crate) type Point = (f64, f64);
+
+/// Maps the coordinates of a cell into those of the top cell:
+/// `x' = a * x + b * y + tx`, `y' = c * x + d * y + ty`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Affine {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+impl Affine {
+    pub(crate) fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Placement of an instance: reflection about the X-axis, magnification,
+    /// rotation and then translation to `position`. Absolute magnification and
+    /// angle are treated as relative.
+    pub(crate) fn placement(position: GdsCoord, transform: Option<GdsTransform>) -> Self {
+        let (reflect, magnification, angle) = match transform {
+            Some(t) => (t.flag.reflect, t.magnification(), t.angle()),
+            None => (false, 1.0, 0.0),
+        };
+        let (sin, cos) = match angle.rem_euclid(360.0) {
+            0.0 => (0.0, 1.0),
+            90.0 => (1.0, 0.0),
+            180.0 => (0.0, -1.0),
+            270.0 => (-1.0, 0.0),
+            a => a.to_radians().sin_cos(),
+        };
+        let flip = if reflect { -1.0 } else { 1.0 };
+        Self {
+            a: magnification * cos,
+            b: -magnification * sin * flip,
+            c: magnification * sin,
+            d: magnification * cos * flip,
+            tx: position.x as f64,
+            ty: position.y as f64,
+        }
+    }
+
+    /// Apply `inner` first, then `self`.
+    pub(crate) fn then(&self, inner: &Affine) -> Self {
+        Self {
+            a: self.a * inner.a + self.b * inner.c,
+            b: self.a * inner.b + self.b * inner.d,
+            c: self.c * inner.a + self.d * inner.c,
+            d: self.c * inner.b + self.d * inner.d,
+            tx: self.a * inner.tx + self.b * inner.ty + self.tx,
+            ty: self.c * inner.tx + self.d * inner.ty + self.ty,
+        }
+    }
+
+    pub(crate) fn apply(&self, x: f64, y: f64) -> Point {
+        (self.a * x + self.b * y + self.tx, self.c * x + self.d * y + self.ty)
+    }
+
+    pub(crate) fn apply_coord(&self, coord: GdsCoord) -> Point {
+        self.apply(coord.x as f64, coord.y as f64)
+    }
+
+    /// How much lengths grow, e.g. for the width of paths.
+    pub(crate) fn magnification(&self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+/// Axis-aligned bounds of a set of points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Bounds {
+    pub(crate) min_x: f64,
+    pub(crate) min_y: f64,
+    pub(crate) max_x: f64,
+    pub(crate) max_y: f64,
+}
+
+impl Bounds {
+    pub(crate) fn of(points: &[Point]) -> Option<Self> {
+        let (&first, rest) = points.split_first()?;
+        let mut bounds = Self { min_x: first.0, min_y: first.1, max_x: first.0, max_y: first.1 };
+        for &point in rest {
+            bounds.add(point);
+        }
+        Some(bounds)
+    }
+
+    pub(crate) fn add(&mut self, (x, y): Point) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    pub(crate) fn union(&mut self, other: &Bounds) {
+        self.add((other.min_x, other.min_y));
+        self.add((other.max_x, other.max_y));
+    }
+
+    pub(crate) fn intersects(&self, other: &Bounds) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x && self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+
+    pub(crate) fn corners(&self) -> [Point; 4] {
+        [
+            (self.min_x, self.min_y),
+            (self.min_x, self.max_y),
+            (self.max_x, self.max_y),
+            (self.max_x, self.min_y),
+        ]
+    }
+
+    pub(crate) fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub(crate) fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+}
+
+/// A shape of a flattened cell, in the coordinates of the top cell.
+#[derive(Debug, Clone)]
+pub(crate) enum Shape {
+    /// A boundary, box or path outline, not closed
+    Polygon { layer: i16, data_type: i16, points: Vec<Point> },
+    Label { layer: i16, text_type: i16, position: Point, string: String },
+    /// Bounding box of an instance that is not expanded
+    Instance { name: String, outline: [Point; 4] },
+}
+
+impl Shape {
+    pub(crate) fn bounds(&self) -> Option<Bounds> {
+        match self {
+            Self::Polygon { points, .. } => Bounds::of(points),
+            Self::Label { position, .. } => Bounds::of(&[*position]),
+            Self::Instance { outline, .. } => Bounds::of(outline),
+        }
+    }
+}
+
+/// Bounds of the polygons on `layer`, or of all polygons if there are none on it.
+pub(crate) fn footprint(shapes: &[Shape], layer: Option<(i16, i16)>) -> Option<Bounds> {
+    let bounds = |on_layer: &dyn Fn(i16, i16) -> bool| {
+        shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Polygon { layer, data_type, points } if on_layer(*layer, *data_type) => Bounds::of(points),
+                _ => None,
+            })
+            .reduce(|mut a, b| {
+                a.union(&b);
+                a
+            })
+    };
+    layer
+        .and_then(|layer| bounds(&|l, d| (l, d) == layer))
+        .or_else(|| bounds(&|_, _| true))
+}
+
+/// Expands the hierarchy of a library into shapes, caching the bounds of each cell.
+pub(crate) struct Flattener<'a> {
+    library: &'a GdsLibrary,
+    bounds: HashMap<String, Option<Bounds>>,
+}
+
+impl<'a> Flattener<'a> {
+    pub(crate) fn new(library: &'a GdsLibrary) -> Self {
+        Self { library, bounds: HashMap::new() }
+    }
+
+    pub(crate) fn contains(&self, cell: &str) -> bool {
+        self.library.structures.contains_key(cell)
+    }
+
+    /// Shapes of `cell` with instances expanded `depth` levels deep.
+    /// Instances below that are kept as outlines, those of undefined cells are left out.
+    pub(crate) fn flatten(&mut self, cell: &str, depth: usize) -> GdsRenderResult<Vec<Shape>> {
+        if !self.library.structures.contains_key(cell) {
+            return Err(GdsRenderError::UnknownStructure(cell.to_string()));
+        }
+        let mut shapes = vec![];
+        let mut stack = vec![];
+        self.expand(cell, &Affine::identity(), depth, &mut stack, &mut shapes)?;
+        Ok(shapes)
+    }
+
+    fn expand(&mut self, name: &str, affine: &Affine, depth: usize, stack: &mut Vec<String>, shapes: &mut Vec<Shape>) -> GdsRenderResult<()> {
+        let Some(structure) = self.structure(name) else {
+            return Ok(());
+        };
+        if stack.iter().any(|s| s == name) {
+            return Err(GdsRenderError::RecursiveStructure(name.to_string()));
+        }
+        stack.push(name.to_string());
+        let structure = structure.read().unwrap();
+
+        let points = |xy: &[GdsCoord]| -> Vec<Point> {
+            let mut xy = xy;
+            if let [first, .., last] = xy {
+                if (first.x, first.y) == (last.x, last.y) {
+                    xy = &xy[..xy.len() - 1];
+                }
+            }
+            xy.iter().map(|&c| affine.apply_coord(c)).collect()
+        };
+        for boundary in structure.boundarys() {
+            shapes.push(Shape::Polygon { layer: boundary.layer, data_type: boundary.data_type, points: points(&boundary.xy) });
+        }
+        for path in structure.paths() {
+            shapes.push(Shape::Polygon { layer: path.layer, data_type: path.data_type, points: points(&path.outline()) });
+        }
+        for boxx in structure.boxes() {
+            shapes.push(Shape::Polygon { layer: boxx.layer, data_type: boxx.box_type, points: points(&boxx.xy) });
+        }
+        for text in structure.texts() {
+            shapes.push(Shape::Label {
+                layer: text.layer,
+                text_type: text.text_type,
+                position: affine.apply_coord(text.position),
+                string: text.string.clone(),
+            });
+        }
+
+        for (child, placements) in Self::placements(&structure) {
+            for placement in placements {
+                let placement = affine.then(&placement);
+                if depth > 0 {
+                    self.expand(&child, &placement, depth - 1, stack, shapes)?;
+                } else if let Some(bounds) = self.cell_bounds(&child, stack)? {
+                    let outline = bounds.corners().map(|(x, y)| placement.apply(x, y));
+                    shapes.push(Shape::Instance { name: child.clone(), outline });
+                }
+            }
+        }
+
+        stack.pop();
+        Ok(())
+    }
+
+    /// Bounds of `name` in its own coordinates, `None` if it is empty or undefined.
+    fn cell_bounds(&mut self, name: &str, stack: &mut Vec<String>) -> GdsRenderResult<Option<Bounds>> {
+        if let Some(bounds) = self.bounds.get(name) {
+            return Ok(*bounds);
+        }
+        let Some(structure) = self.structure(name) else {
+            return Ok(None);
+        };
+        if stack.iter().any(|s| s == name) {
+            return Err(GdsRenderError::RecursiveStructure(name.to_string()));
+        }
+        stack.push(name.to_string());
+        let structure = structure.read().unwrap();
+
+        let mut points: Vec<Point> = vec![];
+        let mut add = |xy: &[GdsCoord]| points.extend(xy.iter().map(|c| (c.x as f64, c.y as f64)));
+        for boundary in structure.boundarys() {
+            add(&boundary.xy);
+        }
+        for path in structure.paths() {
+            add(&path.outline());
+        }
+        for boxx in structure.boxes() {
+            add(&boxx.xy);
+        }
+        for text in structure.texts() {
+            add(&[text.position]);
+        }
+
+        for (child, placements) in Self::placements_at_corners(&structure) {
+            if let Some(bounds) = self.cell_bounds(&child, stack)? {
+                for placement in placements {
+                    points.extend(bounds.corners().map(|(x, y)| placement.apply(x, y)));
+                }
+            }
+        }
+
+        stack.pop();
+        let bounds = Bounds::of(&points);
+        self.bounds.insert(name.to_string(), bounds);
+        Ok(bounds)
+    }
+
+    fn structure(&self, name: &str) -> Option<Arc<RwLock<GdsStructure>>> {
+        self.library.structures.get(name).cloned()
+    }
+
+    /// Every instance of every referenced cell.
+    fn placements(structure: &GdsStructure) -> Vec<(String, Vec<Affine>)> {
+        let mut placements: Vec<(String, Vec<Affine>)> = structure.srefs()
+            .map(|sref| (sref.s_name.clone(), vec![Affine::placement(sref.position, sref.transform)]))
+            .collect();
+        for aref in structure.arefs() {
+            let instances = aref.positions()
+                .into_iter()
+                .map(|position| Affine::placement(position, aref.transform))
+                .collect();
+            placements.push((aref.s_name.clone(), instances));
+        }
+        placements
+    }
+
+    /// Like `placements`, with only the corner instances of arrays,
+    /// which is enough for bounds since arrays lie on a lattice.
+    fn placements_at_corners(structure: &GdsStructure) -> Vec<(String, Vec<Affine>)> {
+        let mut placements: Vec<(String, Vec<Affine>)> = structure.srefs()
+            .map(|sref| (sref.s_name.clone(), vec![Affine::placement(sref.position, sref.transform)]))
+            .collect();
+        for aref in structure.arefs() {
+            let (cols, rows) = (aref.col.max(1), aref.row.max(1));
+            let corners = [(0, 0), (cols - 1, 0), (0, rows - 1), (cols - 1, rows - 1)];
+            let instances = corners
+                .into_iter()
+                .map(|(col, row)| Affine::placement(aref.instance_position(row, col), aref.transform))
+                .collect();
+            placements.push((aref.s_name.clone(), instances));
+        }
+        placements
+    }
+}
@@ -6,7 +6,13 @@ mod node;
 mod path;
 mod sref;
 mod text;
+mod property;
+mod raw;
 mod structure;
+mod element;
+mod geometry;
+#[cfg(feature = "serde")]
+pub(crate) mod serialize;
 
 pub use primitive::*;
 pub use aref::*;
@@ -16,4 +22,8 @@ pub use node::*;
 pub use path::*;
 pub use sref::*;
 pub use text::*;
-pub use structure::*;
\ No newline at end of file
+pub use property::*;
+pub use raw::*;
+pub use structure::*;
+pub use element::*;
+pub(crate) use geometry::{footprint, Affine, Bounds, Flattener, Point, Shape};
\ No newline at end of file
@@ -1,10 +1,12 @@
 use derive_builder::Builder;
+use reda_geometry::shape::Rect;
 
-use crate::GdsCoord;
+use crate::{GdsCoord, GdsProperty, GdsRawRecord};
 
 use super::GdsPathType;
 
 #[derive(Debug, Default, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option, into))]
 pub struct GdsPath {
     #[builder(default)]
@@ -17,16 +19,27 @@ pub struct GdsPath {
     pub data_type: i16,
 
     #[builder(default)]
-    pub path_type: GdsPathType,
+    pub path_type: Option<GdsPathType>,
     #[builder(default)]
     pub width: Option<i32>,
 
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coords"))]
     pub xy: Vec<GdsCoord>,
     
     #[builder(default)]
     pub purpose_layer: Option<i16>,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
 
+/// Number of segments used to approximate a half circle of a round-ended path
+const ROUND_END_SEGMENTS: usize = 8;
+
 impl GdsPath {
     pub fn new(layer: i16, coords: impl Into<Vec<GdsCoord>>, width: i32) -> Self {
         GdsPathBuilder::default()
@@ -37,4 +50,136 @@ impl GdsPath {
             .build()
             .unwrap()
     }
-}
\ No newline at end of file
+
+    /// The path type, square-ended flush if the PATHTYPE record is omitted.
+    pub fn path_type(&self) -> GdsPathType {
+        self.path_type.unwrap_or_default()
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width.unwrap_or(0)
+    }
+
+    /// Extensions beyond the first and last point implied by the path type.
+    pub fn extensions(&self) -> (i32, i32) {
+        self.path_type().extensions(self.width())
+    }
+
+    /// Length of the center line, including the begin and end extensions.
+    pub fn length(&self) -> f64 {
+        let center: f64 = self.xy
+            .windows(2)
+            .map(|w| {
+                let dx = (w[1].x - w[0].x) as f64;
+                let dy = (w[1].y - w[0].y) as f64;
+                dx.hypot(dy)
+            })
+            .sum();
+        let (begin, end) = self.extensions();
+        center + begin as f64 + end as f64
+    }
+
+    /// Bounding box of the path outline, `None` if the path has no points.
+    pub fn bbox(&self) -> Option<Rect<i32>> {
+        let outline = self.outline();
+        let points: &[GdsCoord] = if outline.is_empty() { &self.xy } else { &outline };
+        let first = points.first()?;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (first.x, first.y, first.x, first.y);
+        for p in points {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        Some(Rect::new(GdsCoord::new(min_x, min_y), GdsCoord::new(max_x, max_y)))
+    }
+
+    /// Polygon covered by the path, with mitered joints and the end style of
+    /// its path type. Round ends are approximated by line segments.
+    /// The polygon is not closed, and is empty for paths with fewer than two distinct points.
+    pub fn outline(&self) -> Vec<GdsCoord> {
+        let mut points: Vec<(f64, f64)> = Vec::with_capacity(self.xy.len());
+        for p in &self.xy {
+            let p = (p.x as f64, p.y as f64);
+            if points.last() != Some(&p) {
+                points.push(p);
+            }
+        }
+        if points.len() < 2 {
+            return vec![];
+        }
+
+        let half_width = self.width().abs() as f64 / 2.0;
+        let (begin, end) = match self.path_type() {
+            GdsPathType::RoundEnd => (0, 0),
+            _ => self.extensions(),
+        };
+
+        // Unit direction and left normal of each segment
+        let directions: Vec<(f64, f64)> = points
+            .windows(2)
+            .map(|w| {
+                let (dx, dy) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+                let len = dx.hypot(dy);
+                (dx / len, dy / len)
+            })
+            .collect();
+        let normals: Vec<(f64, f64)> = directions.iter().map(|&(dx, dy)| (-dy, dx)).collect();
+
+        let last = points.len() - 1;
+        let start = {
+            let (dx, dy) = directions[0];
+            (points[0].0 - dx * begin as f64, points[0].1 - dy * begin as f64)
+        };
+        let finish = {
+            let (dx, dy) = directions[last - 1];
+            (points[last].0 + dx * end as f64, points[last].1 + dy * end as f64)
+        };
+
+        // Offset of the outline at each point along the left side
+        let mut offsets = Vec::with_capacity(points.len());
+        offsets.push(normals[0]);
+        for i in 1..last {
+            let (a, b) = (normals[i - 1], normals[i]);
+            let denominator = 1.0 + a.0 * b.0 + a.1 * b.1;
+            if denominator.abs() < 1e-9 {
+                offsets.push(b);
+            } else {
+                offsets.push(((a.0 + b.0) / denominator, (a.1 + b.1) / denominator));
+            }
+        }
+        offsets.push(normals[last - 1]);
+
+        let mut centers = points.clone();
+        centers[0] = start;
+        centers[last] = finish;
+
+        let mut outline = Vec::with_capacity(points.len() * 2 + ROUND_END_SEGMENTS * 2);
+        for (c, o) in centers.iter().zip(&offsets) {
+            outline.push((c.0 + o.0 * half_width, c.1 + o.1 * half_width));
+        }
+        if self.path_type() == GdsPathType::RoundEnd {
+            Self::push_round_end(&mut outline, finish, normals[last - 1], half_width);
+        }
+        for (c, o) in centers.iter().zip(&offsets).rev() {
+            outline.push((c.0 - o.0 * half_width, c.1 - o.1 * half_width));
+        }
+        if self.path_type() == GdsPathType::RoundEnd {
+            Self::push_round_end(&mut outline, start, (-normals[0].0, -normals[0].1), half_width);
+        }
+
+        outline
+            .into_iter()
+            .map(|(x, y)| GdsCoord::new(x.round() as i32, y.round() as i32))
+            .collect()
+    }
+
+    /// Arc from the left side (`normal`) to the right side around `center`, endpoints excluded.
+    fn push_round_end(outline: &mut Vec<(f64, f64)>, center: (f64, f64), normal: (f64, f64), radius: f64) {
+        let start_angle = normal.1.atan2(normal.0);
+        for i in 1..ROUND_END_SEGMENTS {
+            let angle = start_angle - std::f64::consts::PI * i as f64 / ROUND_END_SEGMENTS as f64;
+            outline.push((center.0 + radius * angle.cos(), center.1 + radius * angle.sin()));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// A MASK record of a filtered-format library, selecting the layers and
+/// datatypes kept in it, e.g. `"1 5-7 ; 0 2"`: layers and datatypes are
+/// separated by a semicolon, ranges are written with a hyphen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GdsMask {
+    /// The record as stored, kept verbatim so it is written back unchanged
+    pub spec: String,
+}
+
+impl GdsMask {
+    pub fn new(spec: impl Into<String>) -> Self {
+        Self { spec: spec.into() }
+    }
+
+    /// Build a mask from layer and datatype ranges.
+    pub fn from_ranges(layers: &[RangeInclusive<i16>], data_types: &[RangeInclusive<i16>]) -> Self {
+        let list = |ranges: &[RangeInclusive<i16>]| {
+            ranges
+                .iter()
+                .map(|r| if r.start() == r.end() { r.start().to_string() } else { format!("{}-{}", r.start(), r.end()) })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        Self::new(format!("{} ; {}", list(layers), list(data_types)).trim_end())
+    }
+
+    pub fn layers(&self) -> Vec<RangeInclusive<i16>> {
+        let (layers, _) = self.split();
+        Self::parse_list(layers)
+    }
+
+    pub fn data_types(&self) -> Vec<RangeInclusive<i16>> {
+        let (_, data_types) = self.split();
+        Self::parse_list(data_types)
+    }
+
+    /// Whether the mask keeps `layer` and `data_type`. An empty list keeps everything.
+    pub fn contains(&self, layer: i16, data_type: i16) -> bool {
+        let matches = |ranges: Vec<RangeInclusive<i16>>, value: i16| {
+            ranges.is_empty() || ranges.iter().any(|r| r.contains(&value))
+        };
+        matches(self.layers(), layer) && matches(self.data_types(), data_type)
+    }
+
+    fn split(&self) -> (&str, &str) {
+        self.spec.split_once(';').unwrap_or((&self.spec, ""))
+    }
+
+    /// Unparsable items are skipped
+    fn parse_list(list: &str) -> Vec<RangeInclusive<i16>> {
+        list.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|item| !item.is_empty())
+            .filter_map(|item| match item.split_once('-') {
+                Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+                None => item.parse().ok().map(|value| value..=value),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for GdsMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.spec)
+    }
+}
@@ -1,6 +1,8 @@
+mod mask;
 mod presentation;
 mod transform;
 
+pub use mask::*;
 pub use presentation::*;
 use reda_geometry::shape::Point;
 pub use transform::*;
@@ -12,11 +14,19 @@ use chrono::{Datelike, Timelike, Local};
 /// - 0 for square-ended paths that endflush with their endpoints
 /// - 1 for round-ended paths
 /// - 2 for square-ended paths that extend a half-width beyond their endpoints
-#[derive(Debug, Clone, Copy)]
+/// - 4 for paths with custom square-end extensions, given by the BGNEXTN and ENDEXTN records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GdsPathType {
-    SquareEnd = 0,
-    RoundEnd = 1,
-    SquareEndExtend = 2,
+    SquareEnd,
+    RoundEnd,
+    SquareEndExtend,
+    CustomExtend {
+        /// BGNEXTN: extension beyond the first point, may be negative, `None` if the record is omitted
+        begin_extension: Option<i32>,
+        /// ENDEXTN: extension beyond the last point, may be negative, `None` if the record is omitted
+        end_extension: Option<i32>,
+    },
 }
 
 impl Default for GdsPathType {
@@ -26,11 +36,13 @@ impl Default for GdsPathType {
 }
 
 impl GdsPathType {
+    /// Pathtype 4 is decoded without extensions, they come from the BGNEXTN/ENDEXTN records.
     pub fn from_u16(value: u16) -> Option<Self> {
         match value {
             0 => Some(Self::SquareEnd),
             1 => Some(Self::RoundEnd),
             2 => Some(Self::SquareEndExtend),
+            4 => Some(Self::CustomExtend { begin_extension: None, end_extension: None }),
             _ => None,
         }
     }
@@ -40,18 +52,36 @@ impl GdsPathType {
             Self::SquareEnd => 0,
             Self::RoundEnd => 1,
             Self::SquareEndExtend => 2,
+            Self::CustomExtend { .. } => 4,
+        }
+    }
+
+    pub fn custom_extend(begin_extension: i32, end_extension: i32) -> Self {
+        Self::CustomExtend { begin_extension: Some(begin_extension), end_extension: Some(end_extension) }
+    }
+
+    /// How far the path reaches beyond its first and last point for a given width.
+    pub fn extensions(&self, width: i32) -> (i32, i32) {
+        let half_width = width.abs() / 2;
+        match *self {
+            Self::SquareEnd => (0, 0),
+            Self::RoundEnd | Self::SquareEndExtend => (half_width, half_width),
+            Self::CustomExtend { begin_extension, end_extension } => (begin_extension.unwrap_or(0), end_extension.unwrap_or(0)),
         }
     }
 }
 
 impl std::fmt::Display for GdsPathType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let description = match self {
-            GdsPathType::SquareEnd => "SquareEnd (0)",
-            GdsPathType::RoundEnd => "RoundEnd (1)",
-            GdsPathType::SquareEndExtend => "SquareEndExtend (2)",
-        };
-        write!(f, "{}", description)
+        match self {
+            GdsPathType::SquareEnd => write!(f, "SquareEnd (0)"),
+            GdsPathType::RoundEnd => write!(f, "RoundEnd (1)"),
+            GdsPathType::SquareEndExtend => write!(f, "SquareEndExtend (2)"),
+            GdsPathType::CustomExtend { begin_extension, end_extension } => {
+                let extension = |e: &Option<i32>| e.map_or("-".to_string(), |e| e.to_string());
+                write!(f, "CustomExtend (4) [{}, {}]", extension(begin_extension), extension(end_extension))
+            }
+        }
     }
 }
 
@@ -61,6 +91,7 @@ impl std::fmt::Display for GdsPathType {
 /// 3. for EDSM Archive format
 /// 4. for EDSHI Filtered forrnat
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GdsFormat {
     /// GDSII Archive format
     GdsiiArchive = 0,
@@ -90,7 +121,23 @@ impl GdsFormat {
     }
 }
 
+/// One entry of LIBSECUR: a group, a user and the access rights they have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GdsAccessControl {
+    pub group: i16,
+    pub user: i16,
+    pub rights: i16,
+}
+
+impl GdsAccessControl {
+    pub fn new(group: i16, user: i16, rights: i16) -> Self {
+        Self { group, user, rights }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GdsDateTime {
     pub year: i16,
     pub month: i16,
@@ -8,6 +8,7 @@ use std::fmt;
 /// - Bits 14 and 15 specify the horizontal justification 
 ///   (00 means left, 01 means center, and 10 means right). Bits 0 through 9 are reserved for future use and must be cleared. If this record is omitted, then top-left justification and font 0 are assumed. The following shows a PRESENTATION record.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GdsFontNumber {
     Font0,
     Font1,
@@ -16,6 +17,7 @@ pub enum GdsFontNumber {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GdsVJustify {
     Top,
     Middle,
@@ -23,6 +25,7 @@ pub enum GdsVJustify {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GdsHJustify {
     Left,
     Center,
@@ -31,6 +34,7 @@ pub enum GdsHJustify {
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GdsPresentation {
     pub font_number: GdsFontNumber,
     pub v_justify: GdsVJustify,
@@ -155,7 +159,7 @@ impl GdsPresentation {
         let font_bits = self.font_number.to_bits() << 4;
         let vj_bits = self.v_justify.to_bits() << 2;
         let hj_bits = self.h_justify.to_bits() << 0;
-        (font_bits | vj_bits | hj_bits) << 10
+        font_bits | vj_bits | hj_bits
     }
 }
 
@@ -8,6 +8,7 @@
 /// - Bit 15 (the rightmost bit) and all remaining bits are reserved for future use and must be cleared. 
 ///   If this record is omitted, the element is assumed to have no reflection, non-absolute magnification, and non- absolute angle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GdsTransformFlag {
     /// Reflect the element about the X-axis before rotation.
     pub reflect: bool,              // Bit 0 (leftmost bit)
@@ -48,6 +49,7 @@ impl GdsTransformFlag {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GdsTransform {
     /// STRANS record flags
     pub flag: GdsTransformFlag,
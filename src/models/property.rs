@@ -0,0 +1,50 @@
+use crate::{GdsAref, GdsBoundary, GdsBox, GdsNode, GdsPath, GdsSref, GdsText};
+
+/// A PROPATTR / PROPVALUE pair attached to an element.
+/// - PROPATTR is the attribute number, a two-byte integer (1-127 by convention)
+/// - PROPVALUE is the ASCII value of the property, at most 126 bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GdsProperty {
+    pub attr: i16,
+    pub value: String,
+}
+
+impl GdsProperty {
+    pub fn new(attr: i16, value: impl Into<String>) -> Self {
+        Self { attr, value: value.into() }
+    }
+}
+
+macro_rules! impl_element_properties {
+    ($($element:ty),*) => {
+        $(
+            impl $element {
+                /// Value of the property with attribute number `attr`, if present.
+                pub fn property(&self, attr: i16) -> Option<&str> {
+                    self.properties
+                        .iter()
+                        .find(|p| p.attr == attr)
+                        .map(|p| p.value.as_str())
+                }
+
+                /// Set the property `attr`, replacing any existing value.
+                pub fn set_property(&mut self, attr: i16, value: impl Into<String>) {
+                    let value = value.into();
+                    match self.properties.iter_mut().find(|p| p.attr == attr) {
+                        Some(p) => p.value = value,
+                        None => self.properties.push(GdsProperty::new(attr, value)),
+                    }
+                }
+
+                /// Remove the property `attr` and return its value.
+                pub fn remove_property(&mut self, attr: i16) -> Option<String> {
+                    let index = self.properties.iter().position(|p| p.attr == attr)?;
+                    Some(self.properties.remove(index).value)
+                }
+            }
+        )*
+    };
+}
+
+impl_element_properties!(GdsBoundary, GdsPath, GdsSref, GdsAref, GdsText, GdsNode, GdsBox);
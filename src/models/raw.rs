@@ -0,0 +1,31 @@
+use crate::io::GdsRecordType;
+
+/// A record kept as read, for records that are not modeled,
+/// so that they are written back in place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GdsRawRecord {
+    /// Number of records of its element, structure or library before it,
+    /// counting a whole element or structure as one record
+    pub position: usize,
+    /// Record type and data type, as in the record header
+    pub code: u16,
+    /// Record content without the header
+    pub data: Vec<u8>,
+}
+
+impl GdsRawRecord {
+    pub fn new(position: usize, code: u16, data: Vec<u8>) -> Self {
+        Self { position, code, data }
+    }
+
+    /// `None` if the code is not a known record type
+    pub fn record_type(&self) -> Option<GdsRecordType> {
+        GdsRecordType::from_u16(self.code)
+    }
+
+    /// Size of the record including its header
+    pub fn size(&self) -> usize {
+        self.data.len() + 4
+    }
+}
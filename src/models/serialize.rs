@@ -0,0 +1,64 @@
+//! Serde helpers for the fields whose types carry no serde support of their own.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::ser::Error;
+use crate::{GdsCoord, GdsStructure};
+
+/// A `GdsCoord` as `[x, y]`
+pub mod coord {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(coord: &GdsCoord, serializer: S) -> Result<S::Ok, S::Error> {
+        [coord.x, coord.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GdsCoord, D::Error> {
+        let [x, y] = <[i32; 2]>::deserialize(deserializer)?;
+        Ok(GdsCoord::new(x, y))
+    }
+}
+
+/// A list of `GdsCoord` as `[[x, y], ...]`
+pub mod coords {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(coords: &[GdsCoord], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(coords.iter().map(|c| [c.x, c.y]))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<GdsCoord>, D::Error> {
+        let coords = Vec::<[i32; 2]>::deserialize(deserializer)?;
+        Ok(coords.into_iter().map(|[x, y]| GdsCoord::new(x, y)).collect())
+    }
+}
+
+/// The structure map of a library, as a map from name to structure sorted by name
+pub mod structures {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(structures: &HashMap<String, Arc<RwLock<GdsStructure>>>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut sorted = BTreeMap::new();
+        for (name, structure) in structures {
+            let structure = structure.read()
+                .map_err(|_| S::Error::custom(format!("structure '{}' is poisoned", name)))?;
+            sorted.insert(name, structure);
+        }
+        serializer.collect_map(sorted.iter().map(|(name, structure)| (name, &**structure)))
+    }
+
+    /// Each structure must be stored under its own name
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, Arc<RwLock<GdsStructure>>>, D::Error> {
+        let structures = HashMap::<String, GdsStructure>::deserialize(deserializer)?;
+        structures.into_iter()
+            .map(|(name, structure)| {
+                if name != structure.name {
+                    let message = format!("structure '{}' is stored under '{}'", structure.name, name);
+                    return Err(<D::Error as serde::de::Error>::custom(message));
+                }
+                Ok((name, Arc::new(RwLock::new(structure))))
+            })
+            .collect()
+    }
+}
@@ -1,7 +1,8 @@
 use derive_builder::Builder;
-use crate::{GdsCoord, GdsTransform};
+use crate::{GdsCoord, GdsProperty, GdsRawRecord, GdsTransform};
 
 #[derive(Debug, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option))]
 pub struct GdsSref {
     #[builder(default)]
@@ -14,7 +15,15 @@ pub struct GdsSref {
     #[builder(default)]
     pub transform: Option<GdsTransform>,
     
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coord"))]
     pub position: GdsCoord,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
 
 impl GdsSref {
@@ -24,7 +33,9 @@ impl GdsSref {
             plex: None,
             s_name: ref_name.into(),
             transform,
-            position: position.into()
+            position: position.into(),
+            properties: vec![],
+            raw_records: vec![],
         }
     }
 
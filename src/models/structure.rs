@@ -1,18 +1,70 @@
-use crate::{GdsDateTime, GdsBoundary, GdsPath, GdsSref, GdsAref, GdsText};
-use super::{GdsBox, GdsCoord, GdsNode, GdsTransform};
+use crate::{GdsDateTime, GdsBoundary, GdsPath, GdsRawRecord, GdsSref, GdsAref, GdsText};
+use super::{GdsBox, GdsCoord, GdsElement, GdsElementMut, GdsElementRef, GdsNode, GdsTransform};
+
+/// The kinds of element a structure can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GdsElementKind {
+    Boundary,
+    Path,
+    Sref,
+    Aref,
+    Text,
+    Node,
+    Box,
+}
+
+impl std::fmt::Display for GdsElementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Boundary => "Boundary",
+            Self::Path => "Path",
+            Self::Sref => "Sref",
+            Self::Aref => "Aref",
+            Self::Text => "Text",
+            Self::Node => "Node",
+            Self::Box => "Box",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GdsStructure {
     pub name: String,
     pub create_date: GdsDateTime,
     pub modify_date: GdsDateTime,
-    pub boundarys: Vec<GdsBoundary>,
-    pub paths: Vec<GdsPath>,
-    pub srefs: Vec<GdsSref>,
-    pub arefs: Vec<GdsAref>,
-    pub texts: Vec<GdsText>,
-    pub nodes: Vec<GdsNode>,
-    pub boxes: Vec<GdsBox>,
+    /// STRCLASS flags, only meaningful to Calma tools
+    pub str_class: Option<u16>,
+
+    /// Elements of every kind in the order they were read or pushed
+    elements: Vec<GdsElement>,
+
+    /// Obsolete records and elements found in the structure, positioned
+    /// by the number of header records and elements before them
+    pub raw_records: Vec<GdsRawRecord>,
+}
+
+/// An iterator over the elements of one kind, and one to change them in place.
+macro_rules! elements_of_kind {
+    ($($kind:ident($element:ty) => $name:ident, $name_mut:ident),*) => {
+        $(
+            pub fn $name(&self) -> impl Iterator<Item = &$element> {
+                self.elements.iter().filter_map(|element| match element {
+                    GdsElement::$kind(element) => Some(element),
+                    _ => None,
+                })
+            }
+
+            pub fn $name_mut(&mut self) -> impl Iterator<Item = &mut $element> {
+                self.elements.iter_mut().filter_map(|element| match element {
+                    GdsElement::$kind(element) => Some(element),
+                    _ => None,
+                })
+            }
+        )*
+    };
 }
 
 impl GdsStructure {
@@ -24,22 +76,71 @@ impl GdsStructure {
     }
 
     pub fn add_rectangle(&mut self, layer: i16, leftdown: impl Into<GdsCoord>, rightup: impl Into<GdsCoord>) {
-        self.boundarys.push(GdsBoundary::rect(layer, leftdown, rightup));
+        self.push(GdsBoundary::rect(layer, leftdown, rightup));
     }
 
     pub fn add_text(&mut self, layer: i16, offset: impl Into<GdsCoord>, text: impl Into<String>) {
-        self.texts.push(GdsText::new(layer, offset, text));
+        self.push(GdsText::new(layer, offset, text));
     }
 
     pub fn add_path(&mut self, layer: i16, coords: impl Into<Vec<GdsCoord>>, width: i32) {
-        self.paths.push(GdsPath::new(layer, coords, width));
+        self.push(GdsPath::new(layer, coords, width));
     }
 
     pub fn add_sref(&mut self, ref_name: impl Into<String>, coord: impl Into<GdsCoord>, transform: Option<GdsTransform>) {
-        self.srefs.push(GdsSref::new(ref_name, coord, transform));
+        self.push(GdsSref::new(ref_name, coord, transform));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_aref(
+        &mut self,
+        ref_name: impl Into<String>,
+        row: i16,
+        col: i16,
+        coord: impl Into<GdsCoord>,
+        row_pitch: impl Into<GdsCoord>,
+        col_pitch: impl Into<GdsCoord>,
+        transform: Option<GdsTransform>
+    ) {
+        self.push(GdsAref::new(ref_name, row, col, coord, row_pitch, col_pitch, transform));
     }
 
-    pub fn add_aref(&mut self, ref_name: impl Into<String>, row: i16, col: i16, coord: impl Into<GdsCoord>, transform: Option<GdsTransform>) {
-        self.arefs.push(GdsAref::new(ref_name, row, col, coord, transform));
+    /// Add an element after all the others.
+    pub fn push(&mut self, element: impl Into<GdsElement>) {
+        self.elements.push(element.into());
     }
+
+    pub fn element_count(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// The element at `index` in element order.
+    pub fn element(&self, index: usize) -> Option<GdsElementRef<'_>> {
+        self.elements.get(index).map(GdsElementRef::from)
+    }
+
+    /// The element at `index` in element order, to be changed in place.
+    pub fn element_mut(&mut self, index: usize) -> Option<GdsElementMut<'_>> {
+        self.elements.get_mut(index).map(GdsElementMut::from)
+    }
+
+    /// Every element, in element order.
+    pub fn elements(&self) -> impl Iterator<Item = GdsElementRef<'_>> {
+        self.elements.iter().map(GdsElementRef::from)
+    }
+
+    /// Every element, in element order, to be changed in place.
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = GdsElementMut<'_>> {
+        self.elements.iter_mut().map(GdsElementMut::from)
+    }
+
+    elements_of_kind!(
+        Boundary(GdsBoundary) => boundarys, boundarys_mut,
+        Path(GdsPath) => paths, paths_mut,
+        Sref(GdsSref) => srefs, srefs_mut,
+        Aref(GdsAref) => arefs, arefs_mut,
+        Text(GdsText) => texts, texts_mut,
+        Node(GdsNode) => nodes, nodes_mut,
+        Box(GdsBox) => boxes, boxes_mut
+    );
 }
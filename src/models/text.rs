@@ -1,8 +1,9 @@
-use crate::{GdsCoord, GdsTransform};
+use crate::{GdsCoord, GdsProperty, GdsRawRecord, GdsTransform};
 use derive_builder::Builder;
 use super::{GdsPathType, GdsPresentation};
 
 #[derive(Debug, Clone, Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[builder(setter(strip_option, into))]
 pub struct GdsText {
     #[builder(default)]
@@ -12,18 +13,26 @@ pub struct GdsText {
 
     pub layer: i16,
     pub text_type: i16,
+    #[cfg_attr(feature = "serde", serde(with = "crate::models::serialize::coord"))]
     pub position: GdsCoord,
     pub string: String,
 
     #[builder(default)]
     pub presentation: Option<GdsPresentation>,
     #[builder(default)]
-    pub path_type: GdsPathType,
+    pub path_type: Option<GdsPathType>,
     #[builder(default)]
     pub width: Option<i32>,
 
     #[builder(default)]
     pub transform: Option<GdsTransform>,
+
+    #[builder(default)]
+    pub properties: Vec<GdsProperty>,
+
+    /// Obsolete records found in the element, see `GdsRawRecord`
+    #[builder(default)]
+    pub raw_records: Vec<GdsRawRecord>,
 }
 
 impl GdsText {
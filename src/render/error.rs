@@ -0,0 +1,19 @@
+#[derive(Debug, thiserror::Error)]
+pub enum GdsRenderError {
+    #[error("Io error '{0}'")]
+    Io(#[from] std::io::Error),
+
+    #[error("Encode png failed '{0}'")]
+    Png(#[from] png::EncodingError),
+
+    #[error("No structure named '{0}'")]
+    UnknownStructure(String),
+
+    #[error("Structure '{0}' references itself")]
+    RecursiveStructure(String),
+
+    #[error("Nothing to draw in the view")]
+    EmptyView,
+}
+
+pub type GdsRenderResult<T> = Result<T, GdsRenderError>;
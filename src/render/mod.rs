@@ -0,0 +1,8 @@
+mod error;
+mod view;
+mod svg;
+mod raster;
+
+pub use error::*;
+pub use svg::*;
+pub use raster::*;
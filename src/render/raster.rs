@@ -0,0 +1,335 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use reda_geometry::shape::Rect;
+use crate::GdsLibrary;
+use crate::models::{Bounds, Flattener, Point, Shape};
+use super::view::view;
+use super::svg::PALETTE;
+use super::GdsRenderResult;
+
+/// Scanlines sampled per pixel row for anti-aliasing
+const SUBSAMPLES: usize = 4;
+
+/// Opacity of layers without a color
+const DEFAULT_ALPHA: u8 = 0x80;
+
+/// Color of the outlines of instances that are not expanded
+const INSTANCE_COLOR: [u8; 4] = [0x80, 0x80, 0x80, 0xff];
+
+/// What `GdsLibrary::render_image` draws and how.
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    /// Levels of instances expanded, `0` draws only the cell itself
+    pub depth: usize,
+    /// Size of the image in pixels, the view is scaled to fit and centered
+    pub width: u32,
+    pub height: u32,
+    /// Part of the cell drawn, in database units, the whole cell if `None`
+    pub viewport: Option<Rect<i32>>,
+    /// RGBA colors by (layer, datatype), alpha is the opacity the layer is blended with
+    pub colors: HashMap<(i16, i16), [u8; 4]>,
+    /// Outline the bounding box of instances that are not expanded
+    pub instance_outlines: bool,
+    /// RGBA color behind the layout
+    pub background: [u8; 4],
+}
+
+impl RasterOptions {
+    /// Expand the whole hierarchy into a `width` by `height` image on white.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            depth: usize::MAX,
+            width,
+            height,
+            viewport: None,
+            colors: HashMap::new(),
+            instance_outlines: true,
+            background: [0xff, 0xff, 0xff, 0xff],
+        }
+    }
+
+    /// A square image of `size` pixels.
+    pub fn thumbnail(size: u32) -> Self {
+        Self::new(size, size)
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: impl Into<Rect<i32>>) -> Self {
+        self.viewport = Some(viewport.into());
+        self
+    }
+
+    pub fn with_color(mut self, layer: i16, data_type: i16, rgba: [u8; 4]) -> Self {
+        self.colors.insert((layer, data_type), rgba);
+        self
+    }
+
+    pub fn with_background(mut self, rgba: [u8; 4]) -> Self {
+        self.background = rgba;
+        self
+    }
+
+    /// Color of (layer, datatype), picked from the default palette when not set.
+    pub fn color(&self, layer: i16, data_type: i16) -> [u8; 4] {
+        match self.colors.get(&(layer, data_type)) {
+            Some(&rgba) => rgba,
+            None => {
+                let [r, g, b] = PALETTE[layer.unsigned_abs() as usize % PALETTE.len()];
+                [r, g, b, DEFAULT_ALPHA]
+            }
+        }
+    }
+}
+
+/// An RGBA image, 8 bits per channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GdsImage {
+    pub width: u32,
+    pub height: u32,
+    /// Four bytes per pixel, row by row from the top
+    pub pixels: Vec<u8>,
+}
+
+impl GdsImage {
+    pub fn new(width: u32, height: u32, rgba: [u8; 4]) -> Self {
+        let pixels = rgba.repeat(width as usize * height as usize);
+        Self { width, height, pixels }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> GdsRenderResult<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        self.write_png(writer)
+    }
+
+    pub fn write_png<W: Write>(&self, writer: W) -> GdsRenderResult<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Blend `rgba` over the pixels, weighted by the coverage of each one.
+    fn blend(&mut self, coverage: &[f32], rgba: [u8; 4]) {
+        let alpha = rgba[3] as f32 / 255.0;
+        for (pixel, &cover) in self.pixels.chunks_exact_mut(4).zip(coverage) {
+            if cover <= 0.0 {
+                continue;
+            }
+            let a = alpha * cover.min(1.0);
+            for c in 0..3 {
+                pixel[c] = (rgba[c] as f32 * a + pixel[c] as f32 * (1.0 - a)).round() as u8;
+            }
+            pixel[3] = ((a + pixel[3] as f32 / 255.0 * (1.0 - a)) * 255.0).round() as u8;
+        }
+    }
+}
+
+/// An edge of a polygon in pixel coordinates, `y0 < y1`.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    /// +1 for an edge going down, -1 going up
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        self.x0 + (y - self.y0) * (self.x1 - self.x0) / (self.y1 - self.y0)
+    }
+}
+
+/// Fills polygons with anti-aliasing, merging everything given between two `finish`es.
+struct Rasterizer {
+    width: usize,
+    height: usize,
+    edges: Vec<Edge>,
+}
+
+impl Rasterizer {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, edges: vec![] }
+    }
+
+    /// Add a polygon, which is turned counterclockwise so that overlapping
+    /// polygons merge instead of cancelling out under the nonzero rule.
+    fn add_polygon(&mut self, points: &[Point]) {
+        if points.len() < 3 {
+            return;
+        }
+        let area: f64 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum();
+        let flip = if area < 0.0 { -1 } else { 1 };
+        for (&a, &b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            if a.1 == b.1 {
+                continue;
+            }
+            let edge = if a.1 < b.1 {
+                Edge { x0: a.0, y0: a.1, x1: b.0, y1: b.1, winding: flip }
+            } else {
+                Edge { x0: b.0, y0: b.1, x1: a.0, y1: a.1, winding: -flip }
+            };
+            self.edges.push(edge);
+        }
+    }
+
+    /// Add the outline of a polygon as lines one pixel wide.
+    fn add_outline(&mut self, points: &[Point]) {
+        for (&a, &b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+            let len = dx.hypot(dy);
+            if len == 0.0 {
+                continue;
+            }
+            let (nx, ny) = (-dy / len * 0.5, dx / len * 0.5);
+            self.add_polygon(&[(a.0 + nx, a.1 + ny), (b.0 + nx, b.1 + ny), (b.0 - nx, b.1 - ny), (a.0 - nx, a.1 - ny)]);
+        }
+    }
+
+    /// Coverage of every pixel by the polygons added so far, which are then cleared.
+    fn finish(&mut self) -> Vec<f32> {
+        let mut coverage = vec![0.0f32; self.width * self.height];
+        let scanlines = self.height * SUBSAMPLES;
+        let center = |scanline: usize| (scanline as f64 + 0.5) / SUBSAMPLES as f64;
+
+        // Edges by the first scanline crossing them
+        let mut starts: Vec<Vec<usize>> = vec![vec![]; scanlines];
+        for (i, edge) in self.edges.iter().enumerate() {
+            let first = (edge.y0 * SUBSAMPLES as f64 - 0.5).ceil().max(0.0);
+            if first < scanlines as f64 && center(first as usize) < edge.y1 {
+                starts[first as usize].push(i);
+            }
+        }
+
+        let weight = 1.0 / SUBSAMPLES as f32;
+        let mut active: Vec<usize> = vec![];
+        let mut crossings: Vec<(f64, i32)> = vec![];
+        for (scanline, start) in starts.iter().enumerate() {
+            let y = center(scanline);
+            active.extend(start);
+            active.retain(|&i| self.edges[i].y1 > y);
+            if active.is_empty() {
+                continue;
+            }
+
+            crossings.clear();
+            crossings.extend(active.iter().map(|&i| (self.edges[i].x_at(y), self.edges[i].winding)));
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let row = &mut coverage[scanline / SUBSAMPLES * self.width..][..self.width];
+            let mut winding = 0;
+            let mut span_start = 0.0;
+            for &(x, w) in &crossings {
+                if winding == 0 {
+                    span_start = x;
+                }
+                winding += w;
+                if winding == 0 {
+                    Self::add_span(row, span_start, x, weight);
+                }
+            }
+        }
+
+        self.edges.clear();
+        coverage
+    }
+
+    /// Cover `[start, end)` of a row, partly covering the pixels at both ends.
+    fn add_span(row: &mut [f32], start: f64, end: f64, weight: f32) {
+        let width = row.len() as f64;
+        let (start, end) = (start.clamp(0.0, width), end.clamp(0.0, width));
+        if start >= end {
+            return;
+        }
+        let (first, last) = (start as usize, end as usize);
+        if first == last {
+            row[first] += (end - start) as f32 * weight;
+            return;
+        }
+        row[first] += (first as f64 + 1.0 - start) as f32 * weight;
+        for cover in &mut row[first + 1..last] {
+            *cover += weight;
+        }
+        if last < row.len() {
+            row[last] += (end - last as f64) as f32 * weight;
+        }
+    }
+}
+
+/// Draws one cell of a library into an image.
+pub(crate) struct ImageRenderer<'a> {
+    library: &'a GdsLibrary,
+    options: &'a RasterOptions,
+}
+
+impl<'a> ImageRenderer<'a> {
+    pub(crate) fn new(library: &'a GdsLibrary, options: &'a RasterOptions) -> Self {
+        Self { library, options }
+    }
+
+    pub(crate) fn render(&self, cell: &str) -> GdsRenderResult<GdsImage> {
+        let (width, height) = (self.options.width.max(1), self.options.height.max(1));
+        let shapes = Flattener::new(self.library).flatten(cell, self.options.depth)?;
+        let view = view(&shapes, self.options.viewport.as_ref())?;
+        let to_pixels = Self::to_pixels(&view, width, height);
+
+        let mut polygons: BTreeMap<(i16, i16), Vec<&[Point]>> = BTreeMap::new();
+        let mut instances = vec![];
+        for shape in &shapes {
+            match shape {
+                Shape::Polygon { layer, data_type, points } => {
+                    polygons.entry((*layer, *data_type)).or_default().push(points);
+                }
+                Shape::Instance { outline, .. } if self.options.instance_outlines => instances.push(outline),
+                _ => {}
+            }
+        }
+
+        let mut image = GdsImage::new(width, height, self.options.background);
+        let mut rasterizer = Rasterizer::new(width as usize, height as usize);
+        for (&(layer, data_type), polygons) in &polygons {
+            for points in polygons {
+                let points: Vec<Point> = points.iter().map(|&p| to_pixels(p)).collect();
+                rasterizer.add_polygon(&points);
+            }
+            image.blend(&rasterizer.finish(), self.options.color(layer, data_type));
+        }
+        if !instances.is_empty() {
+            for outline in instances {
+                let points: Vec<Point> = outline.iter().map(|&p| to_pixels(p)).collect();
+                rasterizer.add_outline(&points);
+            }
+            image.blend(&rasterizer.finish(), INSTANCE_COLOR);
+        }
+        Ok(image)
+    }
+
+    /// Maps database units to pixels, with Y pointing down and the view centered.
+    fn to_pixels(view: &Bounds, width: u32, height: u32) -> impl Fn(Point) -> Point {
+        let scale = (width as f64 / view.width()).min(height as f64 / view.height());
+        let offset_x = (width as f64 - view.width() * scale) / 2.0;
+        let offset_y = (height as f64 - view.height() * scale) / 2.0;
+        let (min_x, max_y) = (view.min_x, view.max_y);
+        move |(x, y)| ((x - min_x) * scale + offset_x, (max_y - y) * scale + offset_y)
+    }
+}
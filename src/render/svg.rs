@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use reda_geometry::shape::Rect;
+use crate::GdsLibrary;
+use crate::models::{Flattener, Point, Shape};
+use super::view::view;
+use super::GdsRenderResult;
+
+/// Colors given to layers without a style, by layer number
+pub(super) const PALETTE: [[u8; 3]; 12] = [
+    [0x1f, 0x77, 0xb4], [0xff, 0x7f, 0x0e], [0x2c, 0xa0, 0x2c], [0xd6, 0x27, 0x28],
+    [0x94, 0x67, 0xbd], [0x8c, 0x56, 0x4b], [0xe3, 0x77, 0xc2], [0x7f, 0x7f, 0x7f],
+    [0xbc, 0xbd, 0x22], [0x17, 0xbe, 0xcf], [0x00, 0x00, 0xff], [0x00, 0xa0, 0x00],
+];
+
+/// Fills given to layers without a style, by layer plus datatype
+const FILLS: [SvgFill; 5] = [
+    SvgFill::Hatch, SvgFill::BackHatch, SvgFill::CrossHatch, SvgFill::Dots, SvgFill::Solid,
+];
+
+/// Spacing of hatch lines and dots in pixels
+const PATTERN_SIZE: f64 = 8.0;
+
+/// Height of labels in pixels
+const FONT_SIZE: f64 = 12.0;
+
+/// How the inside of the shapes of a layer is painted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SvgFill {
+    #[default]
+    Solid,
+    /// Only the outline is drawn
+    Outline,
+    /// Lines rising to the right
+    Hatch,
+    /// Lines falling to the right
+    BackHatch,
+    CrossHatch,
+    Dots,
+}
+
+/// Look of the shapes on one layer / datatype.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgLayerStyle {
+    /// Any SVG color, e.g. `#ff0000` or `red`
+    pub color: String,
+    pub fill: SvgFill,
+    /// Opacity of the fill, outlines are always opaque
+    pub opacity: f64,
+}
+
+impl SvgLayerStyle {
+    pub fn new(color: impl Into<String>, fill: SvgFill) -> Self {
+        Self { color: color.into(), fill, opacity: 0.5 }
+    }
+
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+/// What `GdsLibrary::render_svg` draws and how.
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// Levels of instances expanded, `0` draws only the cell itself
+    pub depth: usize,
+    /// Width of the image in pixels, the height follows from the view
+    pub width: u32,
+    /// Part of the cell drawn, in database units, the whole cell if `None`
+    pub viewport: Option<Rect<i32>>,
+    /// Styles by (layer, datatype), texts use their (layer, texttype)
+    pub styles: HashMap<(i16, i16), SvgLayerStyle>,
+    /// Draw TEXT elements as labels
+    pub texts: bool,
+    /// Outline the bounding box of instances that are not expanded
+    pub instance_outlines: bool,
+    /// Fill behind the layout, transparent if `None`
+    pub background: Option<String>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            depth: usize::MAX,
+            width: 800,
+            viewport: None,
+            styles: HashMap::new(),
+            texts: true,
+            instance_outlines: true,
+            background: Some("#ffffff".to_string()),
+        }
+    }
+}
+
+impl SvgOptions {
+    /// Expand the whole hierarchy, 800 pixels wide, texts and outlines on.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: impl Into<Rect<i32>>) -> Self {
+        self.viewport = Some(viewport.into());
+        self
+    }
+
+    pub fn with_style(mut self, layer: i16, data_type: i16, style: SvgLayerStyle) -> Self {
+        self.styles.insert((layer, data_type), style);
+        self
+    }
+
+    /// Style of (layer, datatype), picked from a default palette when not set.
+    pub fn style(&self, layer: i16, data_type: i16) -> SvgLayerStyle {
+        if let Some(style) = self.styles.get(&(layer, data_type)) {
+            return style.clone();
+        }
+        let [r, g, b] = PALETTE[layer.unsigned_abs() as usize % PALETTE.len()];
+        let fill = FILLS[(layer as i32 + data_type as i32).unsigned_abs() as usize % FILLS.len()];
+        SvgLayerStyle::new(format!("#{:02x}{:02x}{:02x}", r, g, b), fill)
+    }
+}
+
+/// Draws one cell of a library as an SVG document.
+pub(crate) struct SvgRenderer<'a> {
+    library: &'a GdsLibrary,
+    options: &'a SvgOptions,
+}
+
+impl<'a> SvgRenderer<'a> {
+    pub(crate) fn new(library: &'a GdsLibrary, options: &'a SvgOptions) -> Self {
+        Self { library, options }
+    }
+
+    pub(crate) fn render(&self, cell: &str) -> GdsRenderResult<String> {
+        let shapes = Flattener::new(self.library).flatten(cell, self.options.depth)?;
+        let view = view(&shapes, self.options.viewport.as_ref())?;
+        let shapes: Vec<_> = shapes
+            .into_iter()
+            .filter(|shape| shape.bounds().is_some_and(|b| b.intersects(&view)))
+            .collect();
+
+        let width = self.options.width.max(1);
+        let height = ((view.height() / view.width() * width as f64).round() as u32).max(1);
+        // Size of one pixel in database units
+        let pixel = view.width() / width as f64;
+
+        let mut polygons: BTreeMap<(i16, i16), Vec<&[Point]>> = BTreeMap::new();
+        let mut labels: BTreeMap<(i16, i16), Vec<(Point, &str)>> = BTreeMap::new();
+        let mut instances = vec![];
+        for shape in &shapes {
+            match shape {
+                Shape::Polygon { layer, data_type, points } => {
+                    polygons.entry((*layer, *data_type)).or_default().push(points);
+                }
+                Shape::Label { layer, text_type, position, string } => {
+                    if self.options.texts {
+                        labels.entry((*layer, *text_type)).or_default().push((*position, string));
+                    }
+                }
+                Shape::Instance { name, outline } => {
+                    if self.options.instance_outlines {
+                        instances.push((name, outline));
+                    }
+                }
+            }
+        }
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            width, height, num(view.min_x), num(-view.max_y), num(view.width()), num(view.height()),
+        );
+        let _ = writeln!(svg, "<title>{}</title>", escape(cell));
+
+        let _ = writeln!(svg, "<defs>");
+        for &(layer, data_type) in polygons.keys() {
+            let style = self.options.style(layer, data_type);
+            self.write_pattern(&mut svg, layer, data_type, &style, pixel);
+        }
+        let _ = writeln!(svg, "</defs>");
+
+        if let Some(background) = &self.options.background {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                num(view.min_x), num(-view.max_y), num(view.width()), num(view.height()), escape(background),
+            );
+        }
+
+        for (&(layer, data_type), polygons) in &polygons {
+            let style = self.options.style(layer, data_type);
+            let fill = match style.fill {
+                SvgFill::Solid => escape(&style.color),
+                SvgFill::Outline => "none".to_string(),
+                _ => format!("url(#{})", pattern_id(layer, data_type)),
+            };
+            let _ = writeln!(
+                svg,
+                r#"<g id="L{}D{}" fill="{}" fill-opacity="{}" stroke="{}" stroke-width="{}">"#,
+                layer, data_type, fill, num(style.opacity), escape(&style.color), num(pixel),
+            );
+            for points in polygons {
+                let _ = writeln!(svg, r#"<polygon points="{}"/>"#, points_attr(points));
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+
+        if !instances.is_empty() {
+            let _ = writeln!(
+                svg,
+                r##"<g id="instances" fill="none" stroke="#808080" stroke-width="{}" stroke-dasharray="{} {}">"##,
+                num(pixel), num(pixel * 4.0), num(pixel * 2.0),
+            );
+            for (name, outline) in instances {
+                let _ = writeln!(
+                    svg,
+                    r#"<polygon points="{}"><title>{}</title></polygon>"#,
+                    points_attr(outline), escape(name),
+                );
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+
+        for (&(layer, text_type), labels) in &labels {
+            let style = self.options.style(layer, text_type);
+            let _ = writeln!(
+                svg,
+                r#"<g id="L{}T{}" fill="{}" font-family="monospace" font-size="{}">"#,
+                layer, text_type, escape(&style.color), num(pixel * FONT_SIZE),
+            );
+            for &((x, y), string) in labels {
+                let _ = writeln!(svg, r#"<text x="{}" y="{}">{}</text>"#, num(x), num(-y), escape(string));
+            }
+            let _ = writeln!(svg, "</g>");
+        }
+
+        let _ = writeln!(svg, "</svg>");
+        Ok(svg)
+    }
+
+    fn write_pattern(&self, svg: &mut String, layer: i16, data_type: i16, style: &SvgLayerStyle, pixel: f64) {
+        let size = pixel * PATTERN_SIZE;
+        let s = num(size);
+        let content = match style.fill {
+            SvgFill::Solid | SvgFill::Outline => return,
+            SvgFill::Hatch => format!("M0,{s} L{s},0"),
+            SvgFill::BackHatch => format!("M0,0 L{s},{s}"),
+            SvgFill::CrossHatch => format!("M0,{s} L{s},0 M0,0 L{s},{s}"),
+            SvgFill::Dots => {
+                let _ = writeln!(
+                    svg,
+                    r#"<pattern id="{}" patternUnits="userSpaceOnUse" width="{s}" height="{s}"><circle cx="{}" cy="{}" r="{}" fill="{}"/></pattern>"#,
+                    pattern_id(layer, data_type), num(size / 2.0), num(size / 2.0), num(pixel), escape(&style.color),
+                );
+                return;
+            }
+        };
+        let _ = writeln!(
+            svg,
+            r#"<pattern id="{}" patternUnits="userSpaceOnUse" width="{s}" height="{s}"><path d="{}" stroke="{}" stroke-width="{}"/></pattern>"#,
+            pattern_id(layer, data_type), content, escape(&style.color), num(pixel),
+        );
+    }
+}
+
+fn pattern_id(layer: i16, data_type: i16) -> String {
+    format!("fill-L{}D{}", layer, data_type)
+}
+
+/// Points of a polygon with Y flipped, as SVG draws downwards.
+fn points_attr(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|&(x, y)| format!("{},{}", num(x), num(-y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Shortest form of a number, up to three decimals.
+fn num(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" | "" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
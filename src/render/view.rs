@@ -0,0 +1,43 @@
+use reda_geometry::shape::Rect;
+use crate::models::{Bounds, Shape};
+use super::{GdsRenderError, GdsRenderResult};
+
+/// The viewport, or everything drawn with a small margin.
+pub(crate) fn view(shapes: &[Shape], viewport: Option<&Rect<i32>>) -> GdsRenderResult<Bounds> {
+    let mut view = match viewport {
+        Some(viewport) => {
+            let (a, b) = (viewport.lower_left(), viewport.upper_right());
+            Bounds::of(&[(a.x as f64, a.y as f64), (b.x as f64, b.y as f64)])
+        }
+        None => {
+            let mut bounds = shapes.iter().filter_map(Shape::bounds);
+            bounds.next().map(|mut view| {
+                bounds.for_each(|b| view.union(&b));
+                let margin = view.width().max(view.height()) * 0.02;
+                Bounds::of(&[
+                    (view.min_x - margin, view.min_y - margin),
+                    (view.max_x + margin, view.max_y + margin),
+                ])
+                .unwrap()
+            })
+        }
+    }
+    .ok_or(GdsRenderError::EmptyView)?;
+
+    // A single point or line still gets some area
+    if view.width() <= 0.0 && view.height() <= 0.0 {
+        view.min_x -= 1.0;
+        view.max_x += 1.0;
+    }
+    if view.width() <= 0.0 {
+        let half = view.height() / 2.0;
+        view.min_x -= half;
+        view.max_x += half;
+    }
+    if view.height() <= 0.0 {
+        let half = view.width() / 2.0;
+        view.min_y -= half;
+        view.max_y += half;
+    }
+    Ok(view)
+}
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{GdsCoord, GdsElementKind, GdsElementRef, GdsLibrary, GdsProperty, GdsStructure};
+
+/// Most coords one XY record can hold
+const MAX_XY_POINTS: usize = 8191;
+
+/// Limits a library is checked against by `GdsValidator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GdsProfile {
+    /// Highest layer number
+    pub max_layer: i16,
+    /// Highest datatype, texttype, nodetype and boxtype
+    pub max_datatype: i16,
+    /// Most points of a BOUNDARY or PATH, never more than one XY record holds
+    pub max_points: usize,
+    /// Most points of a NODE
+    pub max_node_points: usize,
+    /// Longest structure name
+    pub max_name_len: usize,
+    /// Only allow A-Z, a-z, 0-9, '_', '?' and '$' in structure names
+    pub restrict_name_chars: bool,
+    /// Longest TEXT string
+    pub max_text_len: usize,
+    /// Let SREF and AREF name structures missing from the library,
+    /// e.g. when they come from a reference library
+    pub allow_undefined_references: bool,
+}
+
+impl GdsProfile {
+    /// The limits of the GDSII stream format specification.
+    pub fn strict() -> Self {
+        Self {
+            max_layer: 63,
+            max_datatype: 63,
+            max_points: 200,
+            max_node_points: 50,
+            max_name_len: 32,
+            restrict_name_chars: true,
+            max_text_len: 512,
+            allow_undefined_references: false,
+        }
+    }
+
+    /// The limits most current layout tools read without complaint.
+    pub fn common() -> Self {
+        Self {
+            max_layer: 255,
+            max_datatype: 255,
+            max_points: MAX_XY_POINTS,
+            max_node_points: 50,
+            max_name_len: 255,
+            restrict_name_chars: false,
+            max_text_len: 512,
+            allow_undefined_references: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum GdsViolationKind {
+    #[error("XY is empty")]
+    EmptyXy,
+
+    #[error("Expect at least {0} points, but got {1}")]
+    TooFewPoints(usize, usize),
+
+    #[error("Expect at most {0} points, but got {1}")]
+    TooManyPoints(usize, usize),
+
+    #[error("Expect 5 points in BOX XY, but got {0}")]
+    InvalidBoxPoints(usize),
+
+    #[error("XY is not closed, first and last points differ")]
+    Unclosed,
+
+    #[error("Layer {0} is out of range 0..={1}")]
+    LayerOutOfRange(i16, i16),
+
+    #[error("{0} {1} is out of range 0..={2}")]
+    TypeOutOfRange(&'static str, i16, i16),
+
+    #[error("Structure name is empty")]
+    EmptyName,
+
+    #[error("Name '{0}' is longer than {1} characters")]
+    NameTooLong(String, usize),
+
+    #[error("Name '{0}' has characters other than A-Z, a-z, 0-9, '_', '?' and '$'")]
+    InvalidNameChars(String),
+
+    #[error("Text is {0} characters long, more than {1}")]
+    TextTooLong(usize, usize),
+
+    #[error("Structure '{0}' is not defined")]
+    UndefinedStructure(String),
+
+    #[error("Array of {0} columns and {1} rows, both must be positive")]
+    InvalidArraySize(i16, i16),
+
+    #[error("Property attribute {0} is out of range 1..=127")]
+    PropertyAttrOutOfRange(i16),
+
+    #[error("Property value is {0} bytes long, more than 126")]
+    PropertyValueTooLong(usize),
+}
+
+/// A rule broken by a structure or one of its elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GdsViolation {
+    pub structure: String,
+    /// Element kind and index in element order, as given by
+    /// `GdsStructure::elements`, `None` if the structure itself breaks the rule
+    pub element: Option<(GdsElementKind, usize)>,
+    pub kind: GdsViolationKind,
+}
+
+impl fmt::Display for GdsViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "structure '{}'", self.structure)?;
+        if let Some((kind, index)) = self.element {
+            write!(f, " {kind} #{index}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// Checks libraries against the GDSII specification under a `GdsProfile`.
+#[derive(Debug, Clone)]
+pub struct GdsValidator {
+    profile: GdsProfile,
+}
+
+impl GdsValidator {
+    pub fn new(profile: GdsProfile) -> Self {
+        Self { profile }
+    }
+
+    pub fn profile(&self) -> &GdsProfile {
+        &self.profile
+    }
+
+    /// Every violation in `library`, structure by structure in file order.
+    pub fn validate(&self, library: &GdsLibrary) -> Vec<GdsViolation> {
+        let defined: HashSet<&str> = library.structures.keys().map(|name| name.as_str()).collect();
+        let mut violations = vec![];
+        for structure in library.ordered_structures() {
+            let structure = structure.read().unwrap();
+            self.check_structure(&structure, Some(&defined), &mut violations);
+        }
+        violations
+    }
+
+    /// Every violation in `structure`, references are not checked.
+    pub fn validate_structure(&self, structure: &GdsStructure) -> Vec<GdsViolation> {
+        let mut violations = vec![];
+        self.check_structure(structure, None, &mut violations);
+        violations
+    }
+
+    /// Violations of the structure name.
+    pub fn validate_name(&self, name: &str) -> Vec<GdsViolationKind> {
+        let mut found = vec![];
+        self.check_name(name, &mut found);
+        found
+    }
+
+    /// Violations of a single element, references are not checked.
+    pub fn validate_element(&self, element: GdsElementRef<'_>) -> Vec<GdsViolationKind> {
+        let p = &self.profile;
+        let mut found = vec![];
+        match element {
+            GdsElementRef::Boundary(boundary) => {
+                self.check_layer(boundary.layer, "Datatype", boundary.data_type, &mut found);
+                self.check_points(&boundary.xy, 4, p.max_points, &mut found);
+                if boundary.xy.len() > 1 && !Self::is_closed(&boundary.xy) {
+                    found.push(GdsViolationKind::Unclosed);
+                }
+            }
+            GdsElementRef::Path(path) => {
+                self.check_layer(path.layer, "Datatype", path.data_type, &mut found);
+                self.check_points(&path.xy, 2, p.max_points, &mut found);
+            }
+            GdsElementRef::Sref(_) => {}
+            GdsElementRef::Aref(aref) => {
+                if aref.col <= 0 || aref.row <= 0 {
+                    found.push(GdsViolationKind::InvalidArraySize(aref.col, aref.row));
+                }
+            }
+            GdsElementRef::Text(text) => {
+                self.check_layer(text.layer, "Texttype", text.text_type, &mut found);
+                let len = text.string.chars().count();
+                if len > p.max_text_len {
+                    found.push(GdsViolationKind::TextTooLong(len, p.max_text_len));
+                }
+            }
+            GdsElementRef::Node(node) => {
+                self.check_layer(node.layer, "Nodetype", node.node_type, &mut found);
+                self.check_points(&node.xy, 1, p.max_node_points, &mut found);
+            }
+            GdsElementRef::Box(bx) => {
+                self.check_layer(bx.layer, "Boxtype", bx.box_type, &mut found);
+                if bx.xy.len() != 5 {
+                    found.push(GdsViolationKind::InvalidBoxPoints(bx.xy.len()));
+                } else if !Self::is_closed(&bx.xy) {
+                    found.push(GdsViolationKind::Unclosed);
+                }
+            }
+        }
+        Self::check_properties(element.properties(), &mut found);
+        found
+    }
+
+    fn check_structure(&self, s: &GdsStructure, defined: Option<&HashSet<&str>>, violations: &mut Vec<GdsViolation>) {
+        let mut report = |element: Option<(GdsElementKind, usize)>, kinds: Vec<GdsViolationKind>| {
+            violations.extend(kinds.into_iter().map(|kind| GdsViolation {
+                structure: s.name.clone(),
+                element,
+                kind,
+            }));
+        };
+
+        report(None, self.validate_name(&s.name));
+
+        for (index, element) in s.elements().enumerate() {
+            let mut found = self.validate_element(element);
+            let reference = match element {
+                GdsElementRef::Sref(sref) => Some(&sref.s_name),
+                GdsElementRef::Aref(aref) => Some(&aref.s_name),
+                _ => None,
+            };
+            if let (Some(name), Some(defined)) = (reference, defined) {
+                if !self.profile.allow_undefined_references && !defined.contains(name.as_str()) {
+                    found.push(GdsViolationKind::UndefinedStructure(name.clone()));
+                }
+            }
+            report(Some((element.kind(), index)), found);
+        }
+    }
+
+    fn check_name(&self, name: &str, found: &mut Vec<GdsViolationKind>) {
+        if name.is_empty() {
+            found.push(GdsViolationKind::EmptyName);
+            return;
+        }
+        if name.len() > self.profile.max_name_len {
+            found.push(GdsViolationKind::NameTooLong(name.to_string(), self.profile.max_name_len));
+        }
+        let valid_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '?' | '$');
+        if self.profile.restrict_name_chars && !name.chars().all(valid_char) {
+            found.push(GdsViolationKind::InvalidNameChars(name.to_string()));
+        }
+    }
+
+    fn check_layer(&self, layer: i16, type_name: &'static str, data_type: i16, found: &mut Vec<GdsViolationKind>) {
+        if !(0..=self.profile.max_layer).contains(&layer) {
+            found.push(GdsViolationKind::LayerOutOfRange(layer, self.profile.max_layer));
+        }
+        if !(0..=self.profile.max_datatype).contains(&data_type) {
+            found.push(GdsViolationKind::TypeOutOfRange(type_name, data_type, self.profile.max_datatype));
+        }
+    }
+
+    fn check_points(&self, xy: &[GdsCoord], min: usize, max: usize, found: &mut Vec<GdsViolationKind>) {
+        let max = max.min(MAX_XY_POINTS);
+        if xy.is_empty() {
+            found.push(GdsViolationKind::EmptyXy);
+        } else if xy.len() < min {
+            found.push(GdsViolationKind::TooFewPoints(min, xy.len()));
+        } else if xy.len() > max {
+            found.push(GdsViolationKind::TooManyPoints(max, xy.len()));
+        }
+    }
+
+    fn check_properties(properties: &[GdsProperty], found: &mut Vec<GdsViolationKind>) {
+        for property in properties {
+            if !(1..=127).contains(&property.attr) {
+                found.push(GdsViolationKind::PropertyAttrOutOfRange(property.attr));
+            }
+            if property.value.len() > 126 {
+                found.push(GdsViolationKind::PropertyValueTooLong(property.value.len()));
+            }
+        }
+    }
+
+    fn is_closed(xy: &[GdsCoord]) -> bool {
+        match (xy.first(), xy.last()) {
+            (Some(first), Some(last)) => first.x == last.x && first.y == last.y,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use reda_gds::{GdsAref, GdsArefBuilderError, GdsCoord, GdsLibrary, GdsReadError, GdsStructure};
+use common::{find_record, gds_bytes, library};
+
+const COLROW: u16 = 0x1302;
+
+fn points(xy: &[GdsCoord]) -> Vec<(i32, i32)> {
+    xy.iter().map(|c| (c.x, c.y)).collect()
+}
+
+/// Two rows of three columns on a slanted lattice
+fn slanted() -> GdsAref {
+    let lattice = [GdsCoord::new(0, 0), GdsCoord::new(30, 6), GdsCoord::new(4, 40)];
+    GdsAref::from_lattice("leaf", 2, 3, lattice, None)
+}
+
+#[test]
+fn lattice_gives_pitches_and_positions() {
+    let aref = slanted();
+    assert_eq!(points(&aref.lattice()), [(0, 0), (30, 6), (4, 40)]);
+    assert_eq!(points(&[aref.col_pitch(), aref.row_pitch()]), [(10, 2), (2, 20)]);
+    assert_eq!(points(&[aref.instance_position(0, 0), aref.instance_position(1, 2)]), [(0, 0), (22, 24)]);
+    assert_eq!(points(&aref.positions()), [(0, 0), (10, 2), (20, 4), (2, 20), (12, 22), (22, 24)]);
+    assert_eq!(aref.instance_count(), 6);
+
+    // The same array from its pitches
+    let aref = GdsAref::new("leaf", 2, 3, (0, 0), (2, 20), (10, 2), None);
+    assert_eq!(points(&aref.lattice()), [(0, 0), (30, 6), (4, 40)]);
+}
+
+#[test]
+fn empty_arrays_have_no_pitch() {
+    let aref = GdsAref::new("leaf", 0, 0, (5, 5), (0, 10), (10, 0), None);
+    assert_eq!(points(&[aref.col_pitch(), aref.row_pitch(), aref.instance_position(0, 0)]), [(0, 0), (0, 0), (5, 5)]);
+    assert!(aref.positions().is_empty());
+}
+
+#[test]
+fn slanted_arrays_survive_gds() {
+    let mut top = GdsStructure::new("top");
+    top.push(slanted());
+    let output = GdsLibrary::read(gds_bytes(&library("lib", vec![GdsStructure::new("leaf"), top])).as_slice()).unwrap();
+
+    let top = output.structures["top"].read().unwrap();
+    let aref = top.arefs().next().unwrap();
+    assert_eq!((aref.row, aref.col), (2, 3));
+    assert_eq!(points(&aref.lattice()), [(0, 0), (30, 6), (4, 40)]);
+}
+
+#[test]
+fn arefs_need_colrow() {
+    let mut top = GdsStructure::new("top");
+    top.push(slanted());
+    let mut bytes = gds_bytes(&library("lib", vec![top]));
+    let offset = find_record(&bytes, COLROW, 0);
+    bytes.drain(offset..offset + 8);
+
+    let Err(error) = GdsLibrary::read(bytes.as_slice()) else {
+        panic!("read an AREF without COLROW");
+    };
+    assert!(matches!(error.root(), GdsReadError::BuildAref(GdsArefBuilderError::UninitializedField("col_row"))), "{error}");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use reda_gds::{CifWriteError, GdsBoundary, GdsCoord, GdsElementKind, GdsLibrary, GdsPath, GdsPathType, GdsSref, GdsStructure, GdsText};
+use common::{kinds, library};
+
+const CIF: &str = "\
+(two symbols; and a (nested) comment);
+DS 1 1 1;
+9 leaf;
+L L5D2;
+B 20 10 10 5;
+DF;
+DS 2;
+9 top;
+L metal;
+P 0 0 10 0 10 10;
+W 4 0 0 100 0;
+C 1 M Y R 0 1 T 50 60;
+94 clk 5 5;
+DF;
+C 2;
+E
+";
+
+fn points(xy: &[GdsCoord]) -> Vec<(i32, i32)> {
+    xy.iter().map(|c| (c.x, c.y)).collect()
+}
+
+#[test]
+fn symbols_become_structures() {
+    let library = GdsLibrary::read_cif(CIF.as_bytes()).unwrap();
+    // Centimicrons in nanometers
+    assert_eq!(library.meters_per_dbunit, 1e-9);
+    assert_eq!(library.structure_order, ["leaf", "top", "LIB"]);
+
+    let leaf = library.structures["leaf"].read().unwrap();
+    let rect = leaf.boundarys().next().unwrap();
+    assert_eq!((rect.layer, rect.data_type), (5, 2));
+    assert_eq!(points(&rect.xy), [(0, 0), (200, 0), (200, 100), (0, 100), (0, 0)]);
+
+    let top = library.structures["top"].read().unwrap();
+    assert_eq!(kinds(&top), [GdsElementKind::Boundary, GdsElementKind::Path, GdsElementKind::Sref, GdsElementKind::Text]);
+    // Named layers are numbered after the highest one seen
+    let polygon = top.boundarys().next().unwrap();
+    assert_eq!((polygon.layer, polygon.data_type), (6, 0));
+    assert_eq!(points(&polygon.xy), [(0, 0), (100, 0), (100, 100), (0, 0)]);
+
+    let wire = top.paths().next().unwrap();
+    assert_eq!((wire.path_type, wire.width, points(&wire.xy)), (Some(GdsPathType::RoundEnd), Some(40), vec![(0, 0), (1000, 0)]));
+
+    let call = top.srefs().next().unwrap();
+    let transform = call.transform.unwrap();
+    assert_eq!((call.s_name.as_str(), call.position.x, call.position.y), ("leaf", 500, 600));
+    assert!(transform.flag.reflect);
+    assert_eq!(transform.angle(), 90.0);
+
+    let label = top.texts().next().unwrap();
+    assert_eq!((label.string.as_str(), label.layer, label.position.x, label.position.y), ("clk", 6, 50, 50));
+
+    let outside = library.structures["LIB"].read().unwrap();
+    assert_eq!(outside.srefs().next().unwrap().s_name, "top");
+}
+
+#[test]
+fn written_cif_keeps_the_element_order() {
+    let mut leaf = GdsStructure::new("leaf");
+    leaf.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    let mut top = GdsStructure::new("top");
+    top.push(GdsText::new(2, (5, 5), "a b"));
+    top.push(GdsBoundary::rect(1, (0, 0), (20, 40)));
+    top.push(GdsSref::new("leaf", (100, 0), None));
+    let mut wire = GdsPath::new(3, vec![GdsCoord::new(0, 0), GdsCoord::new(0, 100)], 10);
+    wire.path_type = Some(GdsPathType::RoundEnd);
+    top.push(wire);
+    let mut triangle = GdsBoundary::new(1);
+    triangle.xy = vec![GdsCoord::new(0, 0), GdsCoord::new(30, 0), GdsCoord::new(0, 30), GdsCoord::new(0, 0)];
+    top.push(triangle);
+    let input = library("lib", vec![leaf, top]);
+
+    let mut cif = vec![];
+    input.write_cif(&mut cif).unwrap();
+    let output = GdsLibrary::read_cif(cif.as_slice()).unwrap();
+
+    let expect = input.structures["top"].read().unwrap();
+    let actual = output.structures["top"].read().unwrap();
+    assert_eq!(kinds(&actual), kinds(&expect));
+    assert_eq!(points(&actual.boundarys().nth(1).unwrap().xy), points(&expect.boundarys().nth(1).unwrap().xy));
+    assert_eq!(actual.srefs().next().unwrap().s_name, "leaf");
+    let label = actual.texts().next().unwrap();
+    assert_eq!((label.string.as_str(), label.layer), ("a_b", 2));
+    assert_eq!(points(&actual.paths().next().unwrap().xy), points(&expect.paths().next().unwrap().xy));
+}
+
+#[test]
+fn names_that_end_a_command_are_refused() {
+    for name in ["a;b", "a(b)"] {
+        let input = library("lib", vec![GdsStructure::new(name)]);
+        let mut cif = vec![];
+        let result = input.write_cif(&mut cif);
+        assert!(matches!(result, Err(CifWriteError::InvalidName(n)) if n == name));
+        assert!(cif.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use reda_gds::{GdsDateTime, GdsElementKind, GdsLibrary, GdsLibraryBuilder, GdsStructure};
+
+/// Deliberately malformed, for the error reporting examples
+pub const MALFORMED: &str = "cell_err.gds";
+
+pub fn data(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data").join(name)
+}
+
+/// The well-formed GDS files under the data directory, sorted
+pub fn gds_files() -> Vec<PathBuf> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, files);
+            } else if path.extension().is_some_and(|e| e == "gds") && !path.ends_with(MALFORMED) {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut files = vec![];
+    walk(&data(""), &mut files);
+    files.sort();
+    files
+}
+
+/// A library in nanometers holding `structures` in that order
+pub fn library(name: &str, structures: Vec<GdsStructure>) -> GdsLibrary {
+    let mut library = GdsLibraryBuilder::default()
+        .version(600)
+        .create_date(GdsDateTime::default())
+        .modify_date(GdsDateTime::default())
+        .name(name.to_string())
+        .usrunits_per_dbunit(0.001)
+        .meters_per_dbunit(1e-9)
+        .structures(Default::default())
+        .build()
+        .unwrap();
+    for structure in structures {
+        library.add_structure(structure);
+    }
+    library
+}
+
+/// The kinds of the elements of `structure`, in element order
+pub fn kinds(structure: &GdsStructure) -> Vec<GdsElementKind> {
+    structure.elements().map(|e| e.kind()).collect()
+}
+
+pub fn gds_bytes(library: &GdsLibrary) -> Vec<u8> {
+    let mut bytes = vec![];
+    library.write_gds(&mut bytes).unwrap();
+    bytes
+}
+
+/// Offset of the `nth` record with `code`, counted from zero
+pub fn find_record(bytes: &[u8], code: u16, nth: usize) -> usize {
+    let mut offset = 0;
+    let mut seen = 0;
+    while offset + 4 <= bytes.len() {
+        let size = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        if u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) == code {
+            if seen == nth {
+                return offset;
+            }
+            seen += 1;
+        }
+        offset += size.max(4);
+    }
+    panic!("no record {code:#06x} #{nth}");
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use reda_gds::{DefLayer, DefReadError, DefReader, GdsBoundary, GdsLibrary, GdsStructure};
+use common::library;
+
+/// Design `top` with the items `vias` and `nets` in its VIAS and NETS sections
+fn design(vias: &str, nets: &str) -> String {
+    format!("\
+VERSION 5.8 ;
+DESIGN top ;
+UNITS DISTANCE MICRONS 1000 ;
+VIAS 2 ;
+{vias}
+END VIAS
+NETS 1 ;
+{nets}
+END NETS
+END DESIGN
+")
+}
+
+fn reader(text: &str) -> DefReader<&[u8]> {
+    DefReader::new(text.as_bytes())
+        .with_layer("metal1", DefLayer::new(11, 0, 0.065))
+        .with_layer("metal2", DefLayer::new(13, 0, 0.07))
+}
+
+/// Layer, width and points of a path
+type Wire = (i16, i32, Vec<(i32, i32)>);
+
+/// Wires of the paths of the design
+fn wires(library: &GdsLibrary) -> Vec<Wire> {
+    let top = library.structures["top"].read().unwrap();
+    top.paths().map(|p| (p.layer, p.width(), p.xy.iter().map(|c| (c.x, c.y)).collect())).collect()
+}
+
+const CLK: &str = "- clk ( PIN clk ) + ROUTED metal2 ( 4000 35 ) ( * 3000 ) via1 ( 4800 * 50 ) ;";
+
+#[test]
+fn wires_go_on_on_the_other_layer_of_a_via() {
+    let text = design("", CLK);
+    let library = reader(&text).with_via_layers("via1", "metal1", "metal2").read().unwrap();
+    assert_eq!(wires(&library), [
+        (13, 70, vec![(4000, 35), (4000, 3000)]),
+        (11, 65, vec![(4000, 3000), (4800, 3000)]),
+    ]);
+    let top = library.structures["top"].read().unwrap();
+    let via = top.srefs().next().unwrap();
+    assert_eq!((via.s_name.as_str(), via.position.x, via.position.y), ("via1", 4000, 3000));
+}
+
+#[test]
+fn vias_section_gives_the_layers() {
+    let vias = "\
+- via1 + RECT metal1 ( -50 -50 ) ( 50 50 ) + RECT cut1 ( -30 -30 ) ( 30 30 ) + RECT metal2 ( -60 -60 ) ( 60 60 ) ;
+- via2 + VIARULE gen + CUTSIZE 60 60 + LAYERS metal2 cut2 metal3 + CUTSPACING 80 80 ;";
+    let nets = "- a ( PIN a ) + ROUTED metal1 ( 0 0 ) ( 100 0 ) via1 ( 100 100 ) via2 ( 200 * ) ;";
+    let text = design(vias, nets);
+    let library = reader(&text).read().unwrap();
+
+    // The wire on metal3 is not mapped
+    assert_eq!(wires(&library), [
+        (11, 65, vec![(0, 0), (100, 0)]),
+        (13, 70, vec![(100, 0), (100, 100)]),
+    ]);
+    // Only via1 is made of shapes, on the two mapped layers
+    assert_eq!(library.structure_order, ["via1", "top"]);
+    let via = library.structures["via1"].read().unwrap();
+    assert_eq!(via.boundarys().map(|b| b.layer).collect::<Vec<_>>(), [11, 13]);
+}
+
+#[test]
+fn unknown_vias_are_errors() {
+    let text = design("", CLK);
+    assert!(matches!(reader(&text).read(), Err(DefReadError::UnknownVia(via)) if via == "via1"));
+
+    let text = design("", CLK);
+    let result = reader(&text).with_via_layers("via1", "metal3", "metal4").read();
+    assert!(matches!(result, Err(DefReadError::ViaLayer(via, layer)) if via == "via1" && layer == "metal2"));
+
+    // A misspelt keyword is not taken for a via
+    let text = design("", "- a ( PIN a ) + ROUTED metal1 ( 0 0 ) ( 100 0 ) VIRTAUL ( 200 0 ) ;");
+    assert!(matches!(reader(&text).read(), Err(DefReadError::UnknownVia(via)) if via == "VIRTAUL"));
+}
+
+#[test]
+fn components_are_placed_by_their_bounding_box() {
+    let mut cell = GdsStructure::new("cell");
+    cell.push(GdsBoundary::rect(1, (10, 20), (110, 220)));
+    let mut library = library("lib", vec![cell]);
+
+    let text = "\
+DESIGN top ;
+UNITS DISTANCE MICRONS 1000 ;
+COMPONENTS 2 ;
+- u0 cell + PLACED ( 1000 1000 ) N ;
+- u1 cell + FIXED ( 2000 1000 ) S ;
+- u2 cell + UNPLACED ;
+END COMPONENTS
+END DESIGN
+";
+    let name = DefReader::new(text.as_bytes()).read_into(&mut library).unwrap();
+    let top = library.structures[&name].read().unwrap();
+    let placed: Vec<_> = top.srefs().map(|s| (s.property(1), s.position.x, s.position.y)).collect();
+    // A half turn moves the box to (-110, -220) .. (-10, -20)
+    assert_eq!(placed, [(Some("u0"), 990, 980), (Some("u1"), 2110, 1220)]);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use reda_gds::{DxfOptions, GdsBoundary, GdsSref, GdsStructure};
+use common::library;
+
+/// Values of the group `code` in a DXF document
+fn values<'a>(dxf: &'a str, code: &str) -> Vec<&'a str> {
+    let lines: Vec<_> = dxf.lines().collect();
+    lines.chunks(2)
+        .filter(|pair| pair[0].trim() == code)
+        .map(|pair| pair[1])
+        .collect()
+}
+
+#[test]
+fn clashing_block_names_get_a_suffix() {
+    let names = ["a:b", "a;b", "A_B"];
+    let mut structures: Vec<_> = names.iter()
+        .map(|&name| {
+            let mut leaf = GdsStructure::new(name);
+            leaf.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+            leaf
+        })
+        .collect();
+    let mut top = GdsStructure::new("top");
+    for (x, name) in names.into_iter().enumerate() {
+        top.push(GdsSref::new(name, (x as i32 * 20, 0), None));
+    }
+    structures.push(top);
+    let library = library("lib", structures);
+
+    let mut dxf = vec![];
+    library.write_dxf(&mut dxf, "top", DxfOptions::new()).unwrap();
+    let dxf = String::from_utf8(dxf).unwrap();
+
+    // Block names come as the BLOCK_RECORD, the BLOCK and the INSERT
+    let names = values(&dxf, "2");
+    for block in ["a_b", "a_b_1", "A_B_2"] {
+        assert_eq!(names.iter().filter(|&&name| name == block).count(), 3, "{block}");
+    }
+}
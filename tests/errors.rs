@@ -0,0 +1,58 @@
+use std::path::Path;
+use reda_gds::{GdsLibrary, GdsReadError, GdsReadErrorContext};
+
+const LAYER: u16 = 0x0d02;
+const DATATYPE: u16 = 0x0e02;
+const XY: u16 = 0x1003;
+
+fn dff() -> Vec<u8> {
+    std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("data/cells/dff.gds")).unwrap()
+}
+
+/// Offset of the first record with `code` and at least `min_size` bytes
+fn find_record(bytes: &[u8], code: u16, min_size: usize) -> usize {
+    let mut offset = 0;
+    while offset + 4 <= bytes.len() {
+        let size = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        if u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) == code && size >= min_size {
+            return offset;
+        }
+        offset += size;
+    }
+    panic!("no record {code:#06x}");
+}
+
+fn read_error(bytes: &[u8]) -> Box<GdsReadErrorContext> {
+    match GdsLibrary::read(bytes) {
+        Err(GdsReadError::Context(context)) => context,
+        other => panic!("{:?}", other.map(|lib| lib.name)),
+    }
+}
+
+#[test]
+fn error_shows_the_whole_offending_record() {
+    let mut bytes = dff();
+    let offset = find_record(&bytes, LAYER, 0);
+    bytes[offset + 2..offset + 4].copy_from_slice(&DATATYPE.to_be_bytes());
+
+    let context = read_error(&bytes);
+    assert_eq!(context.record_offset, Some(offset as u64));
+    assert_eq!(context.excerpt, bytes[offset..offset + 6]);
+    assert_eq!(context.structure.as_deref(), Some("dff"));
+    assert!(context.report().contains(&format!("0000  {}", context.excerpt_hex())));
+}
+
+#[test]
+fn error_truncates_long_records() {
+    let mut bytes = dff();
+    let offset = find_record(&bytes, XY, 100);
+    let size = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+    // Not a whole number of coordinates
+    bytes[offset..offset + 2].copy_from_slice(&(size as u16 - 4).to_be_bytes());
+
+    let context = read_error(&bytes);
+    assert_eq!(context.record_offset, Some(offset as u64));
+    assert_eq!(context.excerpt.len(), 64);
+    assert_eq!(context.excerpt, bytes[offset..offset + 64]);
+    assert!(context.report().contains(&format!("... {} more bytes", size - 4 - 64)));
+}
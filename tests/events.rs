@@ -0,0 +1,89 @@
+mod common;
+
+use reda_gds::{GdsEvent, GdsLibrary, GdsLibraryBuilder, GdsReader, GdsStructure};
+use common::{data, gds_bytes, gds_files, MALFORMED};
+
+/// Build a library back from the events of `reader`
+fn rebuild<R: std::io::Read>(reader: &mut GdsReader<R>) -> GdsLibrary {
+    let mut builder = GdsLibraryBuilder::default();
+    builder.structures(Default::default());
+    let (mut masks, mut structures) = (vec![], vec![]);
+    let mut structure: Option<GdsStructure> = None;
+
+    for event in reader.events() {
+        match event.unwrap() {
+            GdsEvent::Header { version } => { builder.version(version); }
+            GdsEvent::BeginLibrary { create_date, modify_date } => {
+                builder.create_date(create_date).modify_date(modify_date);
+            }
+            GdsEvent::LibDirSize(size) => { builder.libdirsize(size); }
+            GdsEvent::SrfName(name) => { builder.srfname(name); }
+            GdsEvent::LibSecur(libsecur) => { builder.libsecur(libsecur); }
+            GdsEvent::LibraryName(name) => { builder.name(name); }
+            GdsEvent::RefLibs(reflibs) => { builder.reflibs(reflibs); }
+            GdsEvent::Fonts(fonts) => { builder.fonts(fonts); }
+            GdsEvent::AttrTable(attrtable) => { builder.attrtable(attrtable); }
+            GdsEvent::Generations(generations) => { builder.generations(generations); }
+            GdsEvent::Format(format) => { builder.format(format); }
+            GdsEvent::Mask(mask) => masks.push(mask),
+            GdsEvent::EndMasks => { builder.end_masks(true); }
+            GdsEvent::Units { usrunits_per_dbunit, meters_per_dbunit } => {
+                builder.usrunits_per_dbunit(usrunits_per_dbunit).meters_per_dbunit(meters_per_dbunit);
+            }
+            GdsEvent::BeginStructure { name, create_date, modify_date } => {
+                let mut begun = GdsStructure::new(name);
+                (begun.create_date, begun.modify_date) = (create_date, modify_date);
+                structure = Some(begun);
+            }
+            GdsEvent::StrClass(class) => structure.as_mut().unwrap().str_class = Some(class),
+            GdsEvent::Boundary(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Path(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Sref(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Aref(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Text(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Node(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::Box(e) => structure.as_mut().unwrap().push(e),
+            GdsEvent::EndStructure => structures.push(structure.take().unwrap()),
+            GdsEvent::EndLibrary => {}
+            GdsEvent::Raw(records) => panic!("no obsolete records expected, got {records:?}"),
+        }
+    }
+
+    let mut library = builder.masks(masks).build().unwrap();
+    for structure in structures {
+        library.add_structure(structure);
+    }
+    library
+}
+
+#[test]
+fn events_rebuild_the_library() {
+    for path in gds_files() {
+        let bytes = std::fs::read(&path).unwrap();
+        let library = GdsLibrary::read(bytes.as_slice()).unwrap();
+        let mut reader = GdsReader::new(bytes.as_slice()).unwrap();
+        let mut rebuilt = rebuild(&mut reader);
+        // Padding after ENDLIB is not an event
+        rebuilt.block_padding = library.block_padding;
+
+        assert!(gds_bytes(&rebuilt) == gds_bytes(&library), "{} rebuilt differently", path.display());
+        assert!(reader.next_event().unwrap().is_none());
+    }
+}
+
+#[test]
+fn errors_surface_mid_stream() {
+    let mut reader = GdsReader::open(data(MALFORMED)).unwrap();
+    let events: Vec<_> = reader.events().collect();
+
+    // Events up to the bad record, then its error and nothing more
+    let (last, before) = events.split_last().unwrap();
+    assert!(before.iter().all(Result::is_ok));
+    assert!(matches!(before[0], Ok(GdsEvent::Header { .. })));
+    assert!(before.iter().any(|e| matches!(e, Ok(GdsEvent::BeginStructure { .. }))));
+    let Err(error) = last else { panic!("read a malformed stream") };
+
+    // The same error as a whole read
+    let Err(whole) = GdsLibrary::load_file(data(MALFORMED)) else { panic!("read a malformed stream") };
+    assert_eq!(error.root().to_string(), whole.root().to_string());
+}
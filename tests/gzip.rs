@@ -0,0 +1,39 @@
+use std::io::Read;
+use std::path::Path;
+use reda_gds::GdsLibrary;
+
+/// Hands out one byte per read, like a slow pipe
+struct Trickle<R>(R);
+
+impl<R: Read> Read for Trickle<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+fn dff() -> Vec<u8> {
+    std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("data/cells/dff.gds")).unwrap()
+}
+
+#[test]
+fn read_auto_detects_gzip_on_short_reads() {
+    let input = dff();
+    let library = GdsLibrary::read(input.as_slice()).unwrap();
+    let mut compressed = vec![];
+    library.write_gz(&mut compressed).unwrap();
+
+    let library = GdsLibrary::read_auto(Trickle(compressed.as_slice())).unwrap();
+    let mut output = vec![];
+    library.write_gds(&mut output).unwrap();
+    assert_eq!(input, output);
+}
+
+#[test]
+fn read_auto_reads_plain_gds_on_short_reads() {
+    let input = dff();
+    let library = GdsLibrary::read_auto(Trickle(input.as_slice())).unwrap();
+    let mut output = vec![];
+    library.write_gds(&mut output).unwrap();
+    assert_eq!(input, output);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use reda_gds::{
+    GdsBoundary, GdsElementKind, GdsEvent, GdsHandled, GdsLibrary, GdsReadError, GdsReader, GdsRecordScope, GdsSeverity, GdsStructure, GdsText, GdsUnknownRecords, ReadOptions
+};
+use common::{find_record, gds_bytes, library};
+
+const LAYER: u16 = 0x0d02;
+const ENDEL: u16 = 0x1100;
+const ENDSTR: u16 = 0x0700;
+/// Private record of some vendor tool: a layer and a label
+const LABEL: u16 = 0x7706;
+const NOTE: u16 = 0x7806;
+
+fn record(code: u16, data: &[u8]) -> Vec<u8> {
+    let mut bytes = ((data.len() + 4) as u16).to_be_bytes().to_vec();
+    bytes.extend(code.to_be_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+/// Structure `top` with two boundaries, a LABEL record between them and a NOTE after them
+fn vendor_gds() -> (Vec<u8>, usize) {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    top.push(GdsBoundary::rect(2, (0, 0), (20, 20)));
+    let mut bytes = gds_bytes(&library("lib", vec![top]));
+
+    let end = find_record(&bytes, ENDSTR, 0);
+    bytes.splice(end..end, record(NOTE, b"ok"));
+    let label = find_record(&bytes, ENDEL, 0) + 4;
+    bytes.splice(label..label, record(LABEL, b"\x00\x05hi"));
+    (bytes, label)
+}
+
+fn decode_label(record: &reda_gds::GdsRawRecord, scope: &GdsRecordScope) -> Result<GdsHandled, GdsReadError> {
+    assert_eq!(scope, &GdsRecordScope::Structure("top".to_string()));
+    let layer = i16::from_be_bytes([record.data[0], record.data[1]]);
+    let string = String::from_utf8(record.data[2..].to_vec()).unwrap();
+    Ok(GdsHandled::Element(GdsText::new(layer, (0, 0), string).into()))
+}
+
+#[test]
+fn unknown_records_are_kept_in_place() {
+    let (bytes, _) = vendor_gds();
+    let library = GdsLibrary::read(bytes.as_slice()).unwrap();
+    let top = library.structures["top"].read().unwrap();
+    assert_eq!(top.raw_records.iter().map(|r| r.code).collect::<Vec<_>>(), [LABEL, NOTE]);
+    drop(top);
+
+    let mut output = vec![];
+    library.write_gds(&mut output).unwrap();
+    assert_eq!(bytes, output);
+}
+
+#[test]
+fn rejected_unknown_records_fail_or_are_skipped() {
+    let (bytes, label) = vendor_gds();
+    let options = ReadOptions::default().with_unknown_records(GdsUnknownRecords::Reject);
+    let error = GdsLibrary::read_with_options(bytes.as_slice(), options).map(|_| ()).unwrap_err();
+    assert!(matches!(error.root(), GdsReadError::UnsupportRecordType(LABEL)));
+
+    let options = ReadOptions::recovery().with_unknown_records(GdsUnknownRecords::Reject);
+    let (library, diagnostics) = GdsLibrary::read_with_options(bytes.as_slice(), options).unwrap();
+    assert!(library.structures["top"].read().unwrap().raw_records.is_empty());
+    // Both the LABEL and the NOTE
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, GdsSeverity::Warning);
+    assert_eq!(diagnostics[0].offset, label as u64);
+    assert!(diagnostics[0].message.contains("0x7706"));
+}
+
+#[test]
+fn handlers_decode_records_into_elements() {
+    let (bytes, _) = vendor_gds();
+    let library = GdsReader::new(bytes.as_slice()).unwrap()
+        .with_handler(LABEL, decode_label)
+        .read()
+        .unwrap();
+
+    let top = library.structures["top"].read().unwrap();
+    let kinds: Vec<_> = top.elements().map(|e| e.kind()).collect();
+    assert_eq!(kinds, [GdsElementKind::Boundary, GdsElementKind::Text, GdsElementKind::Boundary]);
+    let text = top.texts().next().unwrap();
+    assert_eq!((text.layer, text.string.as_str()), (5, "hi"));
+    assert_eq!(top.raw_records.len(), 1);
+    drop(top);
+
+    // The NOTE left raw is still written after the last element
+    let output = gds_bytes(&library);
+    assert_eq!(find_record(&output, NOTE, 0) + 6, find_record(&output, ENDSTR, 0));
+}
+
+#[test]
+fn handlers_can_leave_records_alone() {
+    let (bytes, _) = vendor_gds();
+    let library = GdsReader::new(bytes.as_slice()).unwrap()
+        .with_handler(LABEL, |_, _| Ok(GdsHandled::NotHandled))
+        .with_handler(NOTE, |_, _| Ok(GdsHandled::Drop))
+        .read()
+        .unwrap();
+    let top = library.structures["top"].read().unwrap();
+    assert_eq!(top.raw_records.iter().map(|r| r.code).collect::<Vec<_>>(), [LABEL]);
+}
+
+#[test]
+fn records_inside_elements_can_not_become_elements() {
+    let (mut bytes, _) = vendor_gds();
+    let layer = find_record(&bytes, LAYER, 0);
+    bytes.splice(layer..layer, record(LABEL, b"\x00\x05hi"));
+
+    let result = GdsReader::new(bytes.as_slice()).unwrap()
+        .with_handler(LABEL, |record, _| Ok(GdsHandled::Element(GdsText::new(1, (0, 0), format!("{}", record.code)).into())))
+        .read();
+    assert!(matches!(result.map(|_| ()).unwrap_err().root(), GdsReadError::Handler(_)));
+}
+
+#[test]
+fn decoded_elements_are_events() {
+    let (bytes, _) = vendor_gds();
+    let mut reader = GdsReader::new(bytes.as_slice()).unwrap().with_handler(LABEL, decode_label);
+    let events: Vec<_> = reader.events().collect::<Result<_, _>>().unwrap();
+    let texts: Vec<_> = events.iter()
+        .filter_map(|event| match event {
+            GdsEvent::Text(text) => Some(text.string.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, ["hi"]);
+    assert!(events.iter().any(|event| matches!(event, GdsEvent::Raw(records) if records[0].code == NOTE)));
+}
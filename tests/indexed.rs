@@ -0,0 +1,65 @@
+mod common;
+
+use std::collections::BTreeSet;
+use reda_gds::{GdsIndexedReader, GdsLibrary, GdsReadError};
+use common::data;
+
+const SRAM: &str = "sram/sram_1rw0r0w_8_256_freepdk45.gds";
+const BANK: &str = "sram_1rw0r0w_8_256_freepdk45_bank";
+
+/// Names of `name` and every structure it references, directly or not
+fn closure(library: &GdsLibrary, name: &str) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut stack = vec![name.to_string()];
+    while let Some(name) = stack.pop() {
+        let Some(structure) = library.structures.get(&name) else { continue };
+        if names.insert(name) {
+            let structure = structure.read().unwrap();
+            stack.extend(structure.srefs().map(|s| s.s_name.clone()));
+            stack.extend(structure.arefs().map(|a| a.s_name.clone()));
+        }
+    }
+    names
+}
+
+#[test]
+fn loaded_structures_match_a_full_read() {
+    let library = GdsLibrary::load_file(data(SRAM)).unwrap();
+    let mut reader = GdsIndexedReader::open(data(SRAM)).unwrap();
+    assert_eq!(reader.structures().len(), library.structures.len());
+    assert!(reader.header().structures.is_empty());
+
+    for index in reader.structures().to_vec() {
+        let loaded = reader.load_structure(&index.name).unwrap();
+        let full = library.structures[&index.name].read().unwrap();
+        assert_eq!(format!("{loaded:?}"), format!("{full:?}"), "{}", index.name);
+    }
+}
+
+#[test]
+fn dependencies_are_transitive() {
+    let library = GdsLibrary::load_file(data(SRAM)).unwrap();
+    let mut reader = GdsIndexedReader::open(data(SRAM)).unwrap();
+
+    let expected = closure(&library, BANK);
+    // Some of them are only reached through other structures
+    assert!(expected.len() > reader.structure(BANK).unwrap().references.len() + 1);
+
+    let loaded = reader.load_with_dependencies(BANK).unwrap();
+    let names: BTreeSet<String> = loaded.structures.keys().cloned().collect();
+    assert_eq!(names, expected);
+    assert_eq!(closure(&loaded, BANK), expected);
+    for name in &names {
+        let loaded = loaded.structures[name].read().unwrap();
+        let full = library.structures[name].read().unwrap();
+        assert_eq!(format!("{loaded:?}"), format!("{full:?}"), "{name}");
+    }
+}
+
+#[test]
+fn unknown_structures_are_reported() {
+    let mut reader = GdsIndexedReader::open(data(SRAM)).unwrap();
+    assert!(!reader.contains("missing"));
+    assert!(matches!(reader.load_structure("missing"), Err(GdsReadError::UnknownStructure(name)) if name == "missing"));
+    assert!(matches!(reader.load_with_dependencies("missing"), Err(GdsReadError::UnknownStructure(_))));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use reda_gds::{GdsAref, GdsBoundary, GdsBoxBuilder, GdsCoord, GdsLibrary, GdsNodeBuilder, GdsPath, GdsPathType, GdsRawRecord, GdsSref, GdsStructure, GdsText, GdsTransform};
+use common::{gds_bytes, gds_files, library};
+
+/// A structure with an element of every kind
+fn every_kind() -> GdsLibrary {
+    let mut top = GdsStructure::new("top");
+    top.str_class = Some(2);
+    let mut boundary = GdsBoundary::rect(1, (0, 0), (10, 10));
+    boundary.set_property(1, "net");
+    boundary.raw_records.push(GdsRawRecord::new(2, 0x7706, vec![0, 5, b'h', b'i']));
+    top.push(boundary);
+    let mut path = GdsPath::new(2, vec![GdsCoord::new(0, 0), GdsCoord::new(0, 100)], 8);
+    path.path_type = Some(GdsPathType::CustomExtend { begin_extension: Some(3), end_extension: None });
+    top.push(path);
+    top.push(GdsSref::new("leaf", (5, 5), Some(GdsTransform::mirror_x().with_magnification(1.5))));
+    top.push(GdsAref::new("leaf", 2, 3, (0, 0), (0, 20), (30, 0), None));
+    top.push(GdsText::new(3, (1, 1), "label \"quoted\""));
+    top.push(GdsNodeBuilder::default().layer(4).node_type(1).xy(vec![GdsCoord::new(0, 0)]).build().unwrap());
+    top.push(GdsBoxBuilder::default().layer(5).box_type(2).xy(GdsBoundary::rect(5, (0, 0), (1, 1)).xy).build().unwrap());
+    library("lib", vec![GdsStructure::new("leaf"), top])
+}
+
+#[test]
+fn libraries_survive_json() {
+    let mut libraries = vec![every_kind()];
+    libraries.extend(gds_files().iter().map(|path| GdsLibrary::load_file(path).unwrap()));
+
+    for input in libraries {
+        let output = GdsLibrary::from_json(&input.to_json().unwrap()).unwrap();
+        assert!(gds_bytes(&output) == gds_bytes(&input), "{} changed through JSON", input.name);
+    }
+}
+
+#[test]
+fn structures_are_stored_under_their_name() {
+    let json = every_kind().to_json().unwrap();
+    assert!(json.contains(r#""top":{"name":"top","#));
+
+    let json = json.replace(r#""top":{"name":"top","#, r#""other":{"name":"top","#);
+    let error = GdsLibrary::from_json(&json).map(|_| ()).unwrap_err();
+    assert!(error.to_string().contains("structure 'top' is stored under 'other'"), "{error}");
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use reda_gds::{GdsBoundary, GdsCoord, GdsLibrary, GdsStructure, LefOptions, LefPinUse};
+use common::library;
+
+/// An inverter-like cell in nanometers: a ground rail with a tap touching it,
+/// an input labeled on a text layer, an unlabeled square, an L shape on metal2,
+/// a leaf instance and a shape on a layer that is not routing
+fn cell() -> GdsLibrary {
+    let mut leaf = GdsStructure::new("leaf");
+    leaf.push(GdsBoundary::rect(11, (0, 0), (100, 100)));
+
+    let mut inv = GdsStructure::new("inv");
+    inv.push(GdsBoundary::rect(100, (-100, 0), (1900, 2000)));
+    inv.push(GdsBoundary::rect(11, (0, 0), (1800, 200)));
+    inv.push(GdsBoundary::rect(11, (500, 200), (700, 600)));
+    inv.push(GdsBoundary::rect(11, (1000, 1000), (1200, 1200)));
+    inv.push(GdsBoundary::rect(11, (1500, 1500), (1700, 1700)));
+    let mut l_shape = GdsBoundary::new(13);
+    l_shape.xy = [(0, 1000), (400, 1000), (400, 1200), (200, 1200), (200, 1800), (0, 1800), (0, 1000)]
+        .into_iter()
+        .map(|(x, y)| GdsCoord::new(x, y))
+        .collect();
+    inv.push(l_shape);
+    inv.push(GdsBoundary::rect(12, (0, 0), (50, 50)));
+    inv.add_sref("leaf", (1500, 800), None);
+    inv.add_text(11, (100, 100), "gnd");
+    inv.add_text(50, (1100, 1100), "A");
+    // Nothing under it, so no pin
+    inv.add_text(11, (1000, 1900), "B");
+    library("lib", vec![leaf, inv])
+}
+
+fn options() -> LefOptions {
+    LefOptions::new()
+        .with_layer(11, 0, "metal1")
+        .with_layer(13, 0, "metal2")
+        .with_label_layer(50, 0, 11, 0)
+        .with_boundary_layer(100, 0)
+        .with_symmetry("X Y")
+        .with_site("core")
+}
+
+fn lef(options: LefOptions) -> String {
+    let mut lef = vec![];
+    cell().write_lef(&mut lef, &["inv"], options).unwrap();
+    String::from_utf8(lef).unwrap()
+}
+
+#[test]
+fn macro_has_size_pins_and_obstructions() {
+    let expected = "\
+VERSION 5.8 ;
+BUSBITCHARS \"[]\" ;
+DIVIDERCHAR \"/\" ;
+
+MACRO inv
+  CLASS CORE ;
+  ORIGIN 0.1 0 ;
+  FOREIGN inv 0 0 ;
+  SIZE 2 BY 2 ;
+  SYMMETRY X Y ;
+  SITE core ;
+  PIN gnd
+    DIRECTION INOUT ;
+    USE GROUND ;
+    PORT
+      LAYER metal1 ;
+        RECT 0 0 1.8 0.2 ;
+        RECT 0.5 0.2 0.7 0.6 ;
+    END
+  END gnd
+  PIN A
+    DIRECTION INOUT ;
+    USE SIGNAL ;
+    PORT
+      LAYER metal1 ;
+        RECT 1 1 1.2 1.2 ;
+    END
+  END A
+  OBS
+    LAYER metal1 ;
+      RECT 1.5 1.5 1.7 1.7 ;
+      RECT 1.5 0.8 1.6 0.9 ;
+    LAYER metal2 ;
+      POLYGON 0 1 0.4 1 0.4 1.2 0.2 1.2 0.2 1.8 0 1.8 ;
+  END
+END inv
+
+END LIBRARY
+";
+    assert_eq!(lef(options()), expected);
+}
+
+#[test]
+fn size_follows_the_boundary_layer() {
+    // The L shape on metal2
+    let text = lef(options().with_boundary_layer(13, 0));
+    assert!(text.contains("  ORIGIN 0 -1 ;\n  FOREIGN inv 0 0 ;\n  SIZE 0.4 BY 0.8 ;\n"), "{text}");
+
+    // All shapes, here the boundary is the largest
+    let mut options = options();
+    options.boundary_layer = None;
+    let text = lef(options);
+    assert!(text.contains("  ORIGIN 0.1 0 ;\n  FOREIGN inv 0 0 ;\n  SIZE 2 BY 2 ;\n"), "{text}");
+}
+
+#[test]
+fn pin_use_follows_supply_names() {
+    assert_eq!(LefPinUse::of("VDD!"), LefPinUse::Power);
+    assert_eq!(LefPinUse::of("vss"), LefPinUse::Ground);
+    assert_eq!(LefPinUse::of("A"), LefPinUse::Signal);
+}
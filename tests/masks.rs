@@ -0,0 +1,67 @@
+mod common;
+
+use reda_gds::{GdsFormat, GdsLibrary, GdsMask, GdsStructure};
+use common::{find_record, gds_bytes, library};
+
+const FORMAT: u16 = 0x3602;
+const MASK: u16 = 0x3706;
+const ENDMASKS: u16 = 0x3800;
+
+fn filtered(masks: Vec<GdsMask>, end_masks: bool) -> GdsLibrary {
+    let mut library = library("lib", vec![GdsStructure::new("top")]);
+    library.format = Some(GdsFormat::GdsiiFiltered);
+    library.masks = masks;
+    library.end_masks = end_masks;
+    library
+}
+
+/// Check `library` comes back the same from GDS and from text
+fn assert_round_trips(library: &GdsLibrary) -> GdsLibrary {
+    let input = gds_bytes(library);
+    let output = GdsLibrary::read(input.as_slice()).unwrap();
+    assert!(gds_bytes(&output) == input, "changed through GDS");
+
+    let mut text = vec![];
+    output.write_text(&mut text).unwrap();
+    assert!(gds_bytes(&GdsLibrary::read_text(text.as_slice()).unwrap()) == input, "changed through text");
+    output
+}
+
+#[test]
+fn masks_end_with_endmasks() {
+    let masks = vec![GdsMask::new("1 5-7 ; 0 2"), GdsMask::from_ranges(&[10..=12], &[])];
+    let library = filtered(masks.clone(), false);
+    let bytes = gds_bytes(&library);
+    // The second mask comes right before ENDMASKS
+    let mask = find_record(&bytes, MASK, 1);
+    let size = u16::from_be_bytes([bytes[mask], bytes[mask + 1]]) as usize;
+    assert_eq!(find_record(&bytes, ENDMASKS, 0), mask + size);
+
+    let output = assert_round_trips(&library);
+    assert_eq!(output.masks, masks);
+    assert!(output.end_masks);
+    assert!(output.masks[0].contains(6, 2) && !output.masks[0].contains(6, 1));
+}
+
+#[test]
+fn bare_endmasks_is_kept() {
+    let library = filtered(vec![], true);
+    let bytes = gds_bytes(&library);
+    assert_eq!(find_record(&bytes, ENDMASKS, 0), find_record(&bytes, FORMAT, 0) + 6);
+
+    let output = assert_round_trips(&library);
+    assert!(output.masks.is_empty());
+    assert!(output.end_masks);
+
+    // Without it nothing follows FORMAT
+    let output = assert_round_trips(&filtered(vec![], false));
+    assert!(!output.end_masks);
+}
+
+#[test]
+fn masks_need_endmasks() {
+    let mut bytes = gds_bytes(&filtered(vec![GdsMask::new("1 ; 0")], false));
+    let offset = find_record(&bytes, ENDMASKS, 0);
+    bytes.drain(offset..offset + 4);
+    assert!(GdsLibrary::read(bytes.as_slice()).is_err());
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::io::Write;
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use reda_gds::{GdsAref, GdsBoundary, GdsCoord, GdsLibrary, GdsPath, GdsSref, GdsStructure, GdsText, OasisReadError, OasisWriter};
+use common::{kinds, library};
+
+const MAGIC: &[u8] = b"%SEMI-OASIS\r\n";
+const CELL: u8 = 14;
+const XYRELATIVE: u8 = 16;
+const RECTANGLE: u8 = 20;
+const TRAPEZOID: u8 = 23;
+const TRAPEZOID_A: u8 = 24;
+const CTRAPEZOID: u8 = 26;
+const PROPERTY: u8 = 28;
+const CBLOCK: u8 = 34;
+
+/// OASIS file of one cell `top` holding `records`, all numbers below 128
+fn oasis(records: &[u8]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    // START: version, 1000 units per micron, table offsets in START
+    bytes.extend([1, 3, b'1', b'.', b'0', 0, 0xe8, 0x07, 0]);
+    bytes.extend([0; 12]);
+    bytes.extend([CELL, 3, b't', b'o', b'p']);
+    bytes.extend(records);
+    // END: no padding and no validation
+    bytes.extend([2, 0, 0]);
+    bytes
+}
+
+/// S_GDS_PROPERTY record with `attr` and a one letter `value`
+fn gds_property(attr: u8, value: u8) -> Vec<u8> {
+    let mut bytes = vec![PROPERTY, 0x24, 14];
+    bytes.extend(b"S_GDS_PROPERTY");
+    bytes.extend([8, attr, 10, 1, value]);
+    bytes
+}
+
+/// Layer 1 datatype 0 rectangle of 10 by 20 at (x, 0)
+fn rectangle(x: u8) -> Vec<u8> {
+    vec![RECTANGLE, 0x7b, 1, 0, 10, 20, x * 2, 0]
+}
+
+fn read_top(bytes: &[u8]) -> GdsStructure {
+    let library = GdsLibrary::read_oasis(bytes).unwrap();
+    let top = library.structures["top"].read().unwrap();
+    top.clone()
+}
+
+fn outline(boundary: &GdsBoundary) -> Vec<(i32, i32)> {
+    boundary.xy.iter().map(|c| (c.x, c.y)).collect()
+}
+
+fn layout() -> GdsLibrary {
+    let mut leaf = GdsStructure::new("leaf");
+    leaf.push(GdsBoundary::rect(1, (0, 0), (10, 20)));
+    let mut top = GdsStructure::new("top");
+    let mut text = GdsText::new(2, (5, 5), "net");
+    text.set_property(1, "label");
+    top.push(text);
+    top.push(GdsPath::new(3, vec![GdsCoord::new(0, 0), GdsCoord::new(100, 0)], 8));
+    top.push(GdsSref::new("leaf", (50, 50), None));
+    top.push(GdsAref::new("leaf", 2, 3, (0, 100), (0, 30), (20, 0), None));
+    library("lib", vec![leaf, top])
+}
+
+#[test]
+fn layouts_survive_oasis() {
+    for compress in [false, true] {
+        let input = layout();
+        let mut bytes = vec![];
+        OasisWriter::new(&mut bytes).with_compression(compress).write(&input).unwrap();
+        let output = GdsLibrary::read_oasis(bytes.as_slice()).unwrap();
+
+        assert_eq!(output.structure_order, input.structure_order);
+        for name in &input.structure_order {
+            let expect = input.structures[name].read().unwrap();
+            let actual = output.structures[name].read().unwrap();
+            assert_eq!(kinds(&actual), kinds(&expect), "{name}");
+            // The elements have no PartialEq, their debug output shows every field
+            assert_eq!(format!("{:?}", actual.boundarys().collect::<Vec<_>>()), format!("{:?}", expect.boundarys().collect::<Vec<_>>()), "{name}");
+            assert_eq!(format!("{:?}", actual.paths().collect::<Vec<_>>()), format!("{:?}", expect.paths().collect::<Vec<_>>()), "{name}");
+            assert_eq!(format!("{:?}", actual.srefs().collect::<Vec<_>>()), format!("{:?}", expect.srefs().collect::<Vec<_>>()), "{name}");
+            assert_eq!(format!("{:?}", actual.arefs().collect::<Vec<_>>()), format!("{:?}", expect.arefs().collect::<Vec<_>>()), "{name}");
+            let texts: Vec<_> = actual.texts().map(|t| (t.layer, t.position.x, t.position.y, t.string.as_str(), t.property(1))).collect();
+            let expect: Vec<_> = expect.texts().map(|t| (t.layer, t.position.x, t.position.y, t.string.as_str(), t.property(1))).collect();
+            assert_eq!(texts, expect, "{name}");
+        }
+    }
+}
+
+#[test]
+fn trapezoids_become_boundaries() {
+    let mut records = vec![];
+    // Horizontal, 100 by 50, delta-a 20 and delta-b -10
+    records.extend([TRAPEZOID, 0x7b, 1, 0, 100, 50, 40, 21, 0, 0]);
+    // Vertical with the same modal size, delta-a -30 only
+    records.extend([TRAPEZOID_A, 0x80, 61]);
+    // Type 0, and type 16 which only needs the width
+    records.extend([CTRAPEZOID, 0xe0, 0, 100, 50]);
+    records.extend([CTRAPEZOID, 0x80, 16]);
+    let top = read_top(&oasis(&records));
+
+    let outlines: Vec<_> = top.boundarys().map(outline).collect();
+    assert_eq!(outlines, [
+        vec![(20, 50), (90, 50), (100, 0), (0, 0), (20, 50)],
+        vec![(0, 0), (0, 50), (100, 50), (100, 30), (0, 0)],
+        vec![(0, 0), (0, 50), (50, 50), (100, 0), (0, 0)],
+        vec![(0, 0), (0, 100), (100, 0), (0, 0)],
+    ]);
+    assert!(top.boundarys().all(|b| (b.layer, b.data_type) == (1, 0)));
+}
+
+#[test]
+fn ctrapezoid_types_are_checked() {
+    let bytes = oasis(&[CTRAPEZOID, 0xe3, 1, 0, 26, 100, 50]);
+    assert!(matches!(GdsLibrary::read_oasis(bytes.as_slice()), Err(OasisReadError::InvalidType("ctrapezoid", 26))));
+
+    // Type 0 draws with the height, which was never set
+    let bytes = oasis(&[CTRAPEZOID, 0xc3, 1, 0, 0, 100]);
+    assert!(matches!(GdsLibrary::read_oasis(bytes.as_slice()), Err(OasisReadError::UndefinedModal("geometry-h"))));
+}
+
+#[test]
+fn properties_skip_transparent_records() {
+    let mut records = rectangle(0);
+    records.extend([0, XYRELATIVE]);
+    records.extend(gds_property(1, b'a'));
+    records.extend(rectangle(20));
+
+    // The property of the second rectangle is read from a CBLOCK
+    let property = gds_property(2, b'b');
+    let mut block = DeflateEncoder::new(vec![], Compression::default());
+    block.write_all(&property).unwrap();
+    let block = block.finish().unwrap();
+    records.extend([CBLOCK, 0, property.len() as u8, block.len() as u8]);
+    records.extend(block);
+    let top = read_top(&oasis(&records));
+
+    let properties: Vec<_> = top.boundarys().map(|b| (b.property(1), b.property(2))).collect();
+    assert_eq!(properties, [(Some("a"), None), (None, Some("b"))]);
+}
+
+#[test]
+fn properties_after_a_cell_stay_off_its_elements() {
+    let mut records = rectangle(0);
+    records.extend([CELL, 4, b'n', b'e', b'x', b't']);
+    records.extend(gds_property(1, b'a'));
+    let library = GdsLibrary::read_oasis(oasis(&records).as_slice()).unwrap();
+
+    assert!(library.structures["top"].read().unwrap().boundarys().next().unwrap().properties.is_empty());
+    assert!(library.structures.contains_key("next"));
+}
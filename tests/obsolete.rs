@@ -0,0 +1,99 @@
+mod common;
+
+use reda_gds::{GdsAccessControl, GdsBoundary, GdsCoord, GdsLibrary, GdsStructure};
+use common::{find_record, gds_bytes, library};
+
+const BGNLIB: u16 = 0x0102;
+const LIBSECUR: u16 = 0x3B02;
+const STRCLASS: u16 = 0x3401;
+const BOUNDARY: u16 = 0x0800;
+const PATH: u16 = 0x0900;
+const LAYER: u16 = 0x0D02;
+const XY: u16 = 0x1003;
+const ENDEL: u16 = 0x1100;
+const TAPENUM: u16 = 0x3202;
+const TAPECODE: u16 = 0x3302;
+const STRTYPE: u16 = 0x2502;
+const ELKEY: u16 = 0x2703;
+const BORDER: u16 = 0x3C00;
+/// Not a GDSII record type
+const UNKNOWN: u16 = 0x7702;
+
+fn record(code: u16, data: &[u8]) -> Vec<u8> {
+    let mut bytes = ((data.len() + 4) as u16).to_be_bytes().to_vec();
+    bytes.extend(code.to_be_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+/// A library with LIBSECUR and STRCLASS, and obsolete or unknown records
+/// spliced in the library, between elements and inside elements
+fn obsolete_gds() -> Vec<u8> {
+    let mut top = GdsStructure::new("top");
+    top.str_class = Some(0x0003);
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    top.add_path(2, vec![GdsCoord::new(0, 0), GdsCoord::new(10, 0)], 2);
+    let mut library = library("lib", vec![top]);
+    library.libsecur = vec![GdsAccessControl::new(1, 2, 3), GdsAccessControl::new(4, 5, 6)];
+    let bytes = gds_bytes(&library);
+
+    let border = [
+        record(BORDER, b""),
+        record(LAYER, &7i16.to_be_bytes()),
+        record(XY, &[0; 8]),
+        record(ENDEL, b""),
+    ].concat();
+    let mut splices = vec![
+        (find_record(&bytes, BGNLIB, 0), [record(TAPENUM, &[0, 1]), record(TAPECODE, &[0; 12])].concat()),
+        (find_record(&bytes, BOUNDARY, 0), record(STRTYPE, &[0, 2])),
+        (find_record(&bytes, ENDEL, 0), record(ELKEY, &[0, 0, 0, 9])),
+        (find_record(&bytes, PATH, 0), border),
+        (find_record(&bytes, ENDEL, 1), record(UNKNOWN, &[0, 4])),
+    ];
+    // From the end so earlier offsets stay valid
+    splices.sort_by_key(|(offset, _)| std::cmp::Reverse(*offset));
+
+    let mut bytes = bytes;
+    for (offset, inserted) in splices {
+        bytes.splice(offset..offset, inserted);
+    }
+    bytes
+}
+
+fn codes(records: &[reda_gds::GdsRawRecord]) -> Vec<u16> {
+    records.iter().map(|r| r.code).collect()
+}
+
+#[test]
+fn obsolete_records_are_read_where_they_are() {
+    let bytes = obsolete_gds();
+    let library = GdsLibrary::read(bytes.as_slice()).unwrap();
+    assert_eq!(library.libsecur, [GdsAccessControl::new(1, 2, 3), GdsAccessControl::new(4, 5, 6)]);
+    assert_eq!(codes(&library.raw_records), [TAPENUM, TAPECODE]);
+
+    let top = library.structures["top"].read().unwrap();
+    assert_eq!(top.str_class, Some(0x0003));
+    // The obsolete element is kept whole, at one position
+    assert_eq!(codes(&top.raw_records), [STRTYPE, BORDER, LAYER, XY, ENDEL]);
+    let positions: Vec<_> = top.raw_records.iter().map(|r| r.position).collect();
+    assert_eq!(positions[1..], [positions[1]; 4]);
+    assert!(positions[0] < positions[1]);
+
+    assert_eq!(codes(&top.boundarys().next().unwrap().raw_records), [ELKEY]);
+    assert_eq!(codes(&top.paths().next().unwrap().raw_records), [UNKNOWN]);
+    assert_eq!(top.element_count(), 2);
+}
+
+#[test]
+fn obsolete_records_are_written_back_in_place() {
+    let bytes = obsolete_gds();
+    let library = GdsLibrary::read(bytes.as_slice()).unwrap();
+    assert!(gds_bytes(&library) == bytes, "changed through GDS");
+    assert_eq!(find_record(&bytes, LIBSECUR, 0), find_record(&gds_bytes(&library), LIBSECUR, 0));
+    assert_eq!(find_record(&bytes, STRCLASS, 0), find_record(&gds_bytes(&library), STRCLASS, 0));
+
+    let mut text = vec![];
+    library.write_text(&mut text).unwrap();
+    let library = GdsLibrary::read_text(text.as_slice()).unwrap();
+    assert!(gds_bytes(&library) == bytes, "changed through text");
+}
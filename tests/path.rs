@@ -0,0 +1,111 @@
+mod common;
+
+use reda_gds::{GdsCoord, GdsLibrary, GdsPath, GdsPathType, GdsStructure};
+use common::{gds_bytes, library};
+
+const BGNEXTN: u16 = 0x3003;
+const ENDEXTN: u16 = 0x3103;
+
+fn points(xy: &[GdsCoord]) -> Vec<(i32, i32)> {
+    xy.iter().map(|c| (c.x, c.y)).collect()
+}
+
+fn path(xy: &[(i32, i32)], width: i32, path_type: GdsPathType) -> GdsPath {
+    let mut path = GdsPath::new(1, xy.iter().map(|&(x, y)| GdsCoord::new(x, y)).collect::<Vec<_>>(), width);
+    path.path_type = Some(path_type);
+    path
+}
+
+/// Number of records with `code` in a GDS stream
+fn count_records(bytes: &[u8], code: u16) -> usize {
+    let mut offset = 0;
+    let mut count = 0;
+    while offset + 4 <= bytes.len() {
+        let size = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+        if u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) == code {
+            count += 1;
+        }
+        offset += size.max(4);
+    }
+    count
+}
+
+#[test]
+fn custom_extensions_round_trip_as_given() {
+    let cases = [
+        (Some(5), None, 1, 0),
+        (None, Some(-3), 0, 1),
+        (Some(5), Some(-3), 1, 1),
+        (None, None, 0, 0),
+    ];
+    for (begin_extension, end_extension, begins, ends) in cases {
+        let path_type = GdsPathType::CustomExtend { begin_extension, end_extension };
+        let mut top = GdsStructure::new("top");
+        top.push(path(&[(0, 0), (100, 0)], 10, path_type));
+        let input = gds_bytes(&library("lib", vec![top]));
+        assert_eq!((count_records(&input, BGNEXTN), count_records(&input, ENDEXTN)), (begins, ends), "{path_type}");
+
+        let library = GdsLibrary::read(input.as_slice()).unwrap();
+        let top = library.structures["top"].read().unwrap();
+        assert_eq!(top.paths().next().unwrap().path_type, Some(path_type));
+        drop(top);
+        assert!(gds_bytes(&library) == input, "{path_type} changed through GDS");
+
+        let mut text = vec![];
+        library.write_text(&mut text).unwrap();
+        let library = GdsLibrary::read_text(text.as_slice()).unwrap();
+        assert!(gds_bytes(&library) == input, "{path_type} changed through text");
+    }
+}
+
+#[test]
+fn extensions_follow_the_path_type() {
+    let xy = [(0, 0), (100, 0)];
+    let lengths: Vec<_> = [
+        GdsPathType::SquareEnd,
+        GdsPathType::RoundEnd,
+        GdsPathType::SquareEndExtend,
+        GdsPathType::custom_extend(5, -3),
+        GdsPathType::CustomExtend { begin_extension: Some(5), end_extension: None },
+    ]
+    .into_iter()
+    .map(|path_type| path(&xy, 20, path_type).length())
+    .collect();
+    assert_eq!(lengths, [100.0, 120.0, 120.0, 102.0, 105.0]);
+
+    // Two segments of 100
+    assert_eq!(path(&[(0, 0), (100, 0), (100, 100)], 20, GdsPathType::SquareEnd).length(), 200.0);
+}
+
+#[test]
+fn outline_miters_the_joints() {
+    let bend = path(&[(0, 0), (100, 0), (100, 100)], 20, GdsPathType::SquareEnd);
+    assert_eq!(points(&bend.outline()), [(0, 10), (90, 10), (90, 100), (110, 100), (110, -10), (0, -10)]);
+
+    let extended = path(&[(0, 0), (100, 0)], 20, GdsPathType::SquareEndExtend);
+    assert_eq!(points(&extended.outline()), [(-10, 10), (110, 10), (110, -10), (-10, -10)]);
+
+    // Round ends add arcs but reach no further than square extended ends
+    let round = path(&[(0, 0), (100, 0)], 20, GdsPathType::RoundEnd);
+    assert_eq!(round.outline().len(), 4 + 2 * 7);
+
+    // Fewer than two distinct points have no outline
+    assert!(path(&[(5, 5), (5, 5)], 20, GdsPathType::SquareEnd).outline().is_empty());
+}
+
+#[test]
+fn bbox_covers_the_outline() {
+    let bbox = |path: GdsPath| {
+        let bbox = path.bbox().unwrap();
+        let (min, max) = (bbox.lower_left(), bbox.upper_right());
+        (min.x, min.y, max.x, max.y)
+    };
+    let xy = [(0, 0), (100, 0)];
+    assert_eq!(bbox(path(&xy, 20, GdsPathType::SquareEnd)), (0, -10, 100, 10));
+    assert_eq!(bbox(path(&xy, 20, GdsPathType::RoundEnd)), (-10, -10, 110, 10));
+    assert_eq!(bbox(path(&xy, 20, GdsPathType::custom_extend(5, -3))), (-5, -10, 97, 10));
+
+    // Without an outline the points are the box
+    assert_eq!(bbox(path(&[(5, 5)], 20, GdsPathType::SquareEnd)), (5, 5, 5, 5));
+    assert!(path(&[], 20, GdsPathType::SquareEnd).bbox().is_none());
+}
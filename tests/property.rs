@@ -0,0 +1,89 @@
+mod common;
+
+use reda_gds::{
+    GdsAref, GdsBoundary, GdsBoxBuilder, GdsCoord, GdsLibrary, GdsNodeBuilder, GdsPath, GdsProperty,
+    GdsReadError, GdsSref, GdsStructure, GdsText,
+};
+use common::{find_record, gds_bytes, library};
+
+const PROPATTR: u16 = 0x2B02;
+const PROPVALUE: u16 = 0x2C06;
+const ENDEL: u16 = 0x1100;
+
+/// An element of every kind, each with its kind number as property 1 and a name as property 126
+fn with_properties() -> GdsLibrary {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    top.push(GdsPath::new(2, vec![GdsCoord::new(0, 0), GdsCoord::new(0, 100)], 8));
+    top.push(GdsSref::new("leaf", (5, 5), None));
+    top.push(GdsAref::new("leaf", 2, 3, (0, 0), (0, 20), (30, 0), None));
+    top.push(GdsText::new(3, (1, 1), "a"));
+    top.push(GdsNodeBuilder::default().layer(4).node_type(1).xy(vec![GdsCoord::new(0, 0)]).build().unwrap());
+    top.push(GdsBoxBuilder::default().layer(5).box_type(2).xy(GdsBoundary::rect(5, (0, 0), (1, 1)).xy).build().unwrap());
+    for (i, mut element) in top.elements_mut().enumerate() {
+        let properties = element.properties_mut();
+        properties.push(GdsProperty::new(1, i.to_string()));
+        // Odd lengths are padded with a null
+        properties.push(GdsProperty::new(126, format!("element {i}")));
+    }
+    library("lib", vec![GdsStructure::new("leaf"), top])
+}
+
+fn properties_of(library: &GdsLibrary) -> Vec<Vec<GdsProperty>> {
+    let top = library.structures["top"].read().unwrap();
+    top.elements().map(|e| e.properties().to_vec()).collect()
+}
+
+#[test]
+fn properties_round_trip() {
+    let input = with_properties();
+    let bytes = gds_bytes(&input);
+    // The pairs end the element
+    let value = find_record(&bytes, PROPVALUE, 1);
+    let size = u16::from_be_bytes([bytes[value], bytes[value + 1]]) as usize;
+    assert_eq!(value + size, find_record(&bytes, ENDEL, 0));
+    assert_eq!(find_record(&bytes, PROPATTR, 1), find_record(&bytes, PROPVALUE, 0) + 6);
+
+    let output = GdsLibrary::read(bytes.as_slice()).unwrap();
+    let properties = properties_of(&output);
+    assert_eq!(properties, properties_of(&input));
+    assert_eq!(properties[6], [GdsProperty::new(1, "6"), GdsProperty::new(126, "element 6")]);
+    assert!(gds_bytes(&output) == bytes, "changed through GDS");
+
+    let mut text = vec![];
+    output.write_text(&mut text).unwrap();
+    let output = GdsLibrary::read_text(text.as_slice()).unwrap();
+    assert_eq!(properties_of(&output), properties_of(&input));
+}
+
+#[test]
+fn propattr_needs_propvalue() {
+    let mut bytes = gds_bytes(&with_properties());
+    let offset = find_record(&bytes, PROPVALUE, 0);
+    let size = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+    bytes.drain(offset..offset + size);
+
+    let Err(error) = GdsLibrary::read(bytes.as_slice()) else {
+        panic!("read a PROPATTR without PROPVALUE");
+    };
+    assert!(matches!(error.root(), GdsReadError::UnexpectRecordType(..)), "{error}");
+    assert!(error.root().to_string().starts_with("Expect record type 'PropValue'"), "{error}");
+}
+
+#[test]
+fn accessors_find_set_and_remove() {
+    let mut text = GdsText::new(1, (0, 0), "a");
+    assert_eq!(text.property(1), None);
+
+    text.set_property(1, "net");
+    text.set_property(2, "pin");
+    assert_eq!((text.property(1), text.property(2)), (Some("net"), Some("pin")));
+
+    // Setting again replaces in place
+    text.set_property(1, "vdd");
+    assert_eq!(text.properties, [GdsProperty::new(1, "vdd"), GdsProperty::new(2, "pin")]);
+
+    assert_eq!(text.remove_property(1), Some("vdd".to_string()));
+    assert_eq!(text.remove_property(1), None);
+    assert_eq!(text.properties, [GdsProperty::new(2, "pin")]);
+}
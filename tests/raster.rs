@@ -0,0 +1,85 @@
+mod common;
+
+use reda_gds::{GdsBoundary, GdsCoord, GdsLibrary, GdsStructure, RasterOptions};
+use reda_geometry::shape::Rect;
+use common::library;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// A square from 20 to 60 on layer 1 and a leaf with a square on layer 2 placed at 70
+fn squares() -> GdsLibrary {
+    let mut leaf = GdsStructure::new("leaf");
+    leaf.push(GdsBoundary::rect(2, (0, 0), (20, 20)));
+
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(1, (20, 20), (60, 60)));
+    top.add_sref("leaf", (70, 70), None);
+    library("lib", vec![leaf, top])
+}
+
+/// One database unit per pixel when the image is 100 pixels high
+fn options(width: u32) -> RasterOptions {
+    RasterOptions::new(width, 100)
+        .with_viewport(Rect::new(GdsCoord::new(0, 0), GdsCoord::new(100, 100)))
+        .with_color(1, 0, RED)
+        .with_color(2, 0, BLUE)
+        .with_background(WHITE)
+}
+
+#[test]
+fn pixels_inside_the_rectangle_are_filled() {
+    let image = squares().render_image("top", &options(100)).unwrap();
+    assert_eq!((image.width, image.height, image.pixels.len()), (100, 100, 100 * 100 * 4));
+
+    // Y points down, the square covers rows 40 to 80
+    assert_eq!(image.pixel(21, 41), RED);
+    assert_eq!(image.pixel(40, 60), RED);
+    assert_eq!(image.pixel(58, 78), RED);
+    assert_eq!(image.pixel(10, 60), WHITE);
+    assert_eq!(image.pixel(40, 30), WHITE);
+    assert_eq!(image.pixel(40, 85), WHITE);
+    assert_eq!(image.pixel(80, 20), BLUE);
+}
+
+#[test]
+fn depth_zero_leaves_instances_empty() {
+    let image = squares().render_image("top", &options(100).with_depth(0)).unwrap();
+    assert_eq!(image.pixel(40, 60), RED);
+    assert_eq!(image.pixel(80, 20), WHITE);
+    // Only the outline of the instance is drawn
+    assert_ne!(image.pixel(70, 20), WHITE);
+
+    let mut options = options(100).with_depth(0);
+    options.instance_outlines = false;
+    let image = squares().render_image("top", &options).unwrap();
+    assert_eq!(image.pixel(70, 20), WHITE);
+}
+
+#[test]
+fn the_view_is_centered() {
+    // Twice as wide as the view, so 50 pixels are left on each side
+    let image = squares().render_image("top", &options(200)).unwrap();
+    assert_eq!(image.pixel(90, 60), RED);
+    assert_eq!(image.pixel(40, 60), WHITE);
+    assert_eq!(image.pixel(130, 20), BLUE);
+}
+
+#[test]
+fn png_holds_the_image() {
+    let image = squares().render_image("top", &options(200)).unwrap();
+    let mut png = vec![];
+    image.write_png(&mut png).unwrap();
+
+    assert_eq!(png[..8], [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+    // IHDR comes first: 13 bytes, then width and height, 8 bits RGBA
+    assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+    assert_eq!(png[16..24], [0, 0, 0, 200, 0, 0, 0, 100]);
+    assert_eq!(png[24..26], [8, 6]);
+
+    let mut reader = png::Decoder::new(std::io::Cursor::new(png)).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+    assert!(pixels == image.pixels);
+}
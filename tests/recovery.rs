@@ -0,0 +1,24 @@
+use std::path::{Path, PathBuf};
+use reda_gds::{GdsLibrary, GdsSeverity, ReadOptions};
+
+fn data(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data").join(name)
+}
+
+#[test]
+fn truncated_file_keeps_what_was_read() {
+    let (library, diagnostics) = GdsLibrary::load_file_with_options(data("cell_err.gds"), ReadOptions::recovery()).unwrap();
+    assert!(library.structures.contains_key("dff"));
+
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("{diagnostics:?}");
+    };
+    assert_eq!(diagnostic.severity, GdsSeverity::Error);
+    assert_eq!(diagnostic.structure.as_deref(), Some("dff"));
+    assert_eq!(diagnostic.element_index, Some(164));
+
+    // The offset is where the truncated XY record starts
+    let bytes = std::fs::read(data("cell_err.gds")).unwrap();
+    let offset = diagnostic.offset as usize;
+    assert_eq!(bytes[offset + 2..offset + 4], [0x10, 0x03]);
+}
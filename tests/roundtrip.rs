@@ -0,0 +1,30 @@
+mod common;
+
+use reda_gds::GdsLibrary;
+use common::{data, gds_files, MALFORMED};
+
+#[test]
+fn data_files_round_trip_byte_for_byte() {
+    let files = gds_files();
+    assert!(!files.is_empty());
+
+    for path in files {
+        let input = std::fs::read(&path).unwrap();
+        let library = GdsLibrary::read(std::io::Cursor::new(&input))
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+        let mut output = vec![];
+        library.write_gds(&mut output).unwrap();
+
+        if input != output {
+            let offset = input.iter().zip(&output).position(|(a, b)| a != b)
+                .unwrap_or(input.len().min(output.len()));
+            panic!("{}: differs at byte {offset} ({} vs {} bytes)", path.display(), input.len(), output.len());
+        }
+    }
+}
+
+#[test]
+fn malformed_file_is_rejected() {
+    assert!(GdsLibrary::load_file(data(MALFORMED)).is_err());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use reda_gds::{
+    GdsAref, GdsBoundary, GdsCoord, GdsElementKind, GdsElementMut, GdsElementTrait, GdsPath, GdsProperty, GdsSref,
+    GdsStructure, GdsText, GdsTransform,
+};
+use common::kinds;
+
+fn points(xy: &[GdsCoord]) -> Vec<(i32, i32)> {
+    xy.iter().map(|c| (c.x, c.y)).collect()
+}
+
+#[test]
+fn elements_come_in_push_order() {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    top.push(GdsBoundary::rect(2, (0, 0), (10, 10)));
+    top.add_text(5, (0, 0), "a");
+    top.push(GdsBoundary::rect(3, (0, 0), (10, 10)));
+    top.add_sref("leaf", (0, 0), None);
+    top.add_path(4, vec![GdsCoord::new(0, 0), GdsCoord::new(10, 0)], 2);
+
+    assert_eq!(kinds(&top), [
+        GdsElementKind::Boundary, GdsElementKind::Boundary, GdsElementKind::Text,
+        GdsElementKind::Boundary, GdsElementKind::Sref, GdsElementKind::Path,
+    ]);
+    let layers: Vec<_> = top.elements().map(|e| e.layer()).collect();
+    assert_eq!(layers, [Some(1), Some(2), Some(5), Some(3), None, Some(4)]);
+
+    // The per kind views keep the same order
+    assert_eq!(top.boundarys().map(|b| b.layer).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(top.element_count(), 6);
+    assert!(matches!(top.element(2), Some(e) if e.kind() == GdsElementKind::Text));
+    assert!(top.element(6).is_none());
+}
+
+#[test]
+fn elements_change_in_place() {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsText::new(1, (0, 0), "a"));
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    top.push(GdsText::new(1, (5, 5), "b"));
+
+    for mut element in top.elements_mut() {
+        if let GdsElementMut::Text(text) = &mut element {
+            text.string.push('!');
+        }
+        element.properties_mut().push(GdsProperty::new(1, "seen"));
+    }
+    for boundary in top.boundarys_mut() {
+        boundary.layer = 7;
+    }
+
+    let strings: Vec<_> = top.texts().map(|t| t.string.as_str()).collect();
+    assert_eq!(strings, ["a!", "b!"]);
+    assert!(top.elements().all(|e| e.properties().len() == 1));
+    assert_eq!(top.boundarys().next().unwrap().layer, 7);
+    assert_eq!(kinds(&top), [GdsElementKind::Text, GdsElementKind::Boundary, GdsElementKind::Text]);
+}
+
+#[test]
+fn transform_by_places_elements() {
+    let turn = Some(GdsTransform::identity().with_rotation(90.0));
+
+    let mut boundary = GdsBoundary::rect(1, (0, 0), (10, 20));
+    boundary.transform_by(GdsCoord::new(100, 0), turn);
+    assert_eq!(points(&boundary.xy), [(100, 0), (80, 0), (80, 10), (100, 10), (100, 0)]);
+    let bbox = boundary.bbox().unwrap();
+    let (min, max) = (bbox.lower_left(), bbox.upper_right());
+    assert_eq!((min.x, min.y, max.x, max.y), (80, 0, 100, 10));
+
+    // Widths grow with the magnification
+    let mut path = GdsPath::new(2, vec![GdsCoord::new(0, 0), GdsCoord::new(10, 0)], 4);
+    path.transform_by(GdsCoord::new(0, 0), Some(GdsTransform::identity().with_magnification(2.0)));
+    assert_eq!((points(&path.xy), path.width), (vec![(0, 0), (20, 0)], Some(8)));
+
+    // A reflected instance turned a quarter keeps its reflection
+    let mut sref = GdsSref::new("leaf", (10, 0), Some(GdsTransform::mirror_x()));
+    sref.transform_by(GdsCoord::new(0, 0), turn);
+    let transform = sref.transform.unwrap();
+    assert_eq!((sref.position.x, sref.position.y), (0, 10));
+    assert!(transform.flag.reflect);
+    assert_eq!(transform.angle(), 90.0);
+
+    // The array turns around its origin, its instances stay on the lattice
+    let mut aref = GdsAref::new("leaf", 2, 3, (10, 0), (0, 30), (20, 0), None);
+    aref.transform_by(GdsCoord::new(0, 0), turn);
+    assert_eq!(points(&aref.lattice()), [(0, 10), (0, 70), (-60, 10)]);
+    assert_eq!(aref.transform.unwrap().angle(), 90.0);
+
+    let mut text = GdsText::new(3, (5, 0), "a");
+    text.transform_by(GdsCoord::new(1, 1), turn);
+    assert_eq!((text.position.x, text.position.y), (1, 6));
+    assert_eq!(text.transform.unwrap().angle(), 90.0);
+}
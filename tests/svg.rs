@@ -0,0 +1,106 @@
+mod common;
+
+use reda_gds::{GdsBoundary, GdsCoord, GdsLibrary, GdsStructure, SvgFill, SvgLayerStyle, SvgOptions};
+use reda_geometry::shape::Rect;
+use common::library;
+
+const TOP: &str = "top \"1\"";
+const LEAF: &str = "leaf<&>";
+
+/// A leaf with a square and a label, placed twice next to a square of the top cell
+fn hierarchy() -> GdsLibrary {
+    let mut leaf = GdsStructure::new(LEAF);
+    leaf.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    leaf.add_text(2, (5, 5), "a<b&c");
+
+    let mut top = GdsStructure::new(TOP);
+    top.push(GdsBoundary::rect(3, (100, 0), (110, 10)));
+    top.add_sref(LEAF, (0, 0), None);
+    top.add_sref(LEAF, (50, 0), None);
+    library("lib", vec![leaf, top])
+}
+
+/// The `<polygon>` lines of the group with `id`, empty if there is no such group
+fn group<'a>(svg: &'a str, id: &str) -> Vec<&'a str> {
+    let start = format!(r#"<g id="{id}""#);
+    svg.lines()
+        .skip_while(|line| !line.starts_with(&start))
+        .skip(1)
+        .take_while(|line| *line != "</g>")
+        .collect()
+}
+
+#[test]
+fn depth_expands_instances() {
+    let library = hierarchy();
+
+    let svg = library.render_svg(TOP, &SvgOptions::new().with_depth(0)).unwrap();
+    assert_eq!(group(&svg, "L3D0").len(), 1);
+    assert!(group(&svg, "L1D0").is_empty());
+    assert!(group(&svg, "L2T0").is_empty());
+    assert_eq!(group(&svg, "instances").len(), 2);
+
+    let svg = library.render_svg(TOP, &SvgOptions::new()).unwrap();
+    assert_eq!(group(&svg, "L3D0").len(), 1);
+    assert_eq!(group(&svg, "L1D0"), [r#"<polygon points="0,0 0,-10 10,-10 10,0"/>"#, r#"<polygon points="50,0 50,-10 60,-10 60,0"/>"#]);
+    assert_eq!(group(&svg, "L2T0").len(), 2);
+    // Nothing is left unexpanded
+    assert!(!svg.contains(r#"id="instances""#));
+}
+
+#[test]
+fn instance_outlines_can_be_left_out() {
+    let library = hierarchy();
+    let mut options = SvgOptions::new().with_depth(0);
+    let svg = library.render_svg(TOP, &options).unwrap();
+    assert_eq!(group(&svg, "instances")[1], r#"<polygon points="50,0 50,-10 60,-10 60,0"><title>leaf&lt;&amp;&gt;</title></polygon>"#);
+
+    options.instance_outlines = false;
+    let svg = library.render_svg(TOP, &options).unwrap();
+    assert!(!svg.contains(r#"id="instances""#));
+    assert_eq!(group(&svg, "L3D0").len(), 1);
+}
+
+#[test]
+fn viewport_clips_the_drawing() {
+    let viewport = Rect::new(GdsCoord::new(90, -5), GdsCoord::new(120, 15));
+    let svg = hierarchy().render_svg(TOP, &SvgOptions::new().with_width(300).with_viewport(viewport)).unwrap();
+
+    assert!(svg.contains(r#"width="300" height="200" viewBox="90 -15 30 20""#), "{svg}");
+    assert_eq!(group(&svg, "L3D0").len(), 1);
+    // The instances lie outside
+    assert!(group(&svg, "L1D0").is_empty());
+    assert!(group(&svg, "L2T0").is_empty());
+}
+
+#[test]
+fn styles_replace_the_palette() {
+    let options = SvgOptions::new()
+        .with_style(3, 0, SvgLayerStyle::new("red", SvgFill::Solid).with_opacity(0.25))
+        .with_style(2, 0, SvgLayerStyle::new("blue", SvgFill::Outline));
+    let svg = hierarchy().render_svg(TOP, &options).unwrap();
+
+    assert!(svg.contains(r#"<g id="L3D0" fill="red" fill-opacity="0.25" stroke="red""#), "{svg}");
+    assert!(svg.contains(r#"<g id="L2T0" fill="blue""#), "{svg}");
+    // Layer 1 keeps its default hatching
+    assert_eq!(options.style(1, 0).fill, SvgFill::BackHatch);
+    assert!(svg.contains(r##"<g id="L1D0" fill="url(#fill-L1D0)""##), "{svg}");
+    assert!(svg.contains(r#"<pattern id="fill-L1D0""#));
+    assert!(!svg.contains(r#"<pattern id="fill-L3D0""#));
+
+    let options = SvgOptions::new().with_style(3, 0, SvgLayerStyle::new("red", SvgFill::Outline));
+    let svg = hierarchy().render_svg(TOP, &options).unwrap();
+    assert!(svg.contains(r#"<g id="L3D0" fill="none""#), "{svg}");
+}
+
+#[test]
+fn names_and_texts_are_escaped() {
+    let svg = hierarchy().render_svg(TOP, &SvgOptions::new()).unwrap();
+    assert!(svg.contains("<title>top &quot;1&quot;</title>"), "{svg}");
+    assert!(svg.contains(r#"<text x="5" y="-5">a&lt;b&amp;c</text>"#), "{svg}");
+    assert!(!svg.contains("a<b"));
+
+    let mut options = SvgOptions::new();
+    options.texts = false;
+    assert!(!hierarchy().render_svg(TOP, &options).unwrap().contains("<text"));
+}
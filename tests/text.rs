@@ -0,0 +1,82 @@
+mod common;
+
+use std::fs;
+use reda_gds::{GdsBoundary, GdsLibrary, GdsRawRecord, GdsReadError, GdsStructure, GdsText};
+use common::{gds_bytes, gds_files, library};
+
+fn text_of(library: &GdsLibrary) -> String {
+    let mut text = vec![];
+    library.write_text(&mut text).unwrap();
+    String::from_utf8(text).unwrap()
+}
+
+fn text_error(text: &str) -> (usize, String) {
+    match GdsLibrary::read_text(text.as_bytes()) {
+        Err(GdsReadError::Text(line, message)) => (line, message),
+        other => panic!("{:?}", other.map(|lib| lib.name)),
+    }
+}
+
+#[test]
+fn text_roundtrip_is_lossless() {
+    let files = gds_files();
+    assert!(!files.is_empty());
+
+    for file in files {
+        let input = fs::read(&file).unwrap();
+        let text = text_of(&GdsLibrary::read(input.as_slice()).unwrap());
+        let library = GdsLibrary::read_text(text.as_bytes()).unwrap();
+        assert!(gds_bytes(&library) == input, "{} changed through text", file.display());
+    }
+}
+
+#[test]
+fn strings_properties_and_raw_records_survive() {
+    let mut top = GdsStructure::new("top\\cell");
+    let mut text = GdsText::new(1, (0, 0), "two\nlines\t\\");
+    text.set_property(1, "net = a\r");
+    top.push(text);
+    let mut boundary = GdsBoundary::rect(2, (0, 0), (10, 10));
+    boundary.raw_records.push(GdsRawRecord::new(2, 0x7706, vec![0, 5, b'h', b'i']));
+    top.push(boundary);
+    let input = library("lib", vec![top]);
+
+    let output = GdsLibrary::read_text(text_of(&input).as_bytes()).unwrap();
+    let top = output.structures["top\\cell"].read().unwrap();
+    let text = top.texts().next().unwrap();
+    assert_eq!(text.string, "two\nlines\t\\");
+    assert_eq!(text.property(1), Some("net = a\r"));
+    assert_eq!(top.boundarys().next().unwrap().raw_records, [GdsRawRecord::new(2, 0x7706, vec![0, 5, b'h', b'i'])]);
+    drop(top);
+    assert_eq!(gds_bytes(&input), gds_bytes(&output));
+}
+
+#[test]
+fn edited_text_is_read() {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(2, (0, 0), (10, 10)));
+    let text = text_of(&library("lib", vec![top])).replace("layer: 2", "layer: 7");
+
+    let library = GdsLibrary::read_text(text.as_bytes()).unwrap();
+    assert_eq!(library.structures["top"].read().unwrap().boundarys().next().unwrap().layer, 7);
+}
+
+#[test]
+fn errors_name_the_line() {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsBoundary::rect(2, (0, 0), (10, 10)));
+    let text = text_of(&library("lib", vec![top]));
+    let line = text.lines().position(|l| l.trim() == "layer: 2").unwrap() + 1;
+
+    let (number, message) = text_error(&text.replace("layer: 2", "layer: two"));
+    assert_eq!(number, line);
+    assert!(message.contains("two"), "{message}");
+
+    let (number, _) = text_error(&text.replace("layer: 2", "colour: 2"));
+    assert_eq!(number, line);
+
+    let (number, _) = text_error("GDS\n");
+    assert_eq!(number, 1);
+    let (number, _) = text_error("");
+    assert_eq!(number, 0);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use std::io::Read;
+use reda_gds::GdsLibrary;
+use common::{gds_bytes, gds_files};
+
+/// A pipe-like source: no `Seek`, and reads that return a few bytes at a time
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    reads: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // 1 to 7 bytes, so record headers are split across reads
+        self.reads += 1;
+        let len = buf.len().min(self.bytes.len()).min(self.reads % 7 + 1);
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes = &self.bytes[len..];
+        Ok(len)
+    }
+}
+
+fn text_of(library: &GdsLibrary) -> String {
+    let mut text = vec![];
+    library.write_text(&mut text).unwrap();
+    String::from_utf8(text).unwrap()
+}
+
+#[test]
+fn short_reads_give_the_same_library() {
+    let files = gds_files();
+    assert!(!files.is_empty());
+
+    for path in files {
+        let bytes = std::fs::read(&path).unwrap();
+        let seekable = GdsLibrary::read(std::io::Cursor::new(&bytes)).unwrap();
+        let trickled = GdsLibrary::read(Trickle { bytes: &bytes, reads: 0 })
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+        assert_eq!(text_of(&trickled), text_of(&seekable), "{}", path.display());
+        assert!(gds_bytes(&trickled) == bytes, "{} changed", path.display());
+    }
+}
+
+#[test]
+fn short_reads_of_a_truncated_stream_fail() {
+    let bytes = std::fs::read(&gds_files()[0]).unwrap();
+    let bytes = &bytes[..bytes.len() / 2];
+    assert!(GdsLibrary::read(Trickle { bytes, reads: 0 }).is_err());
+}
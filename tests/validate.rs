@@ -0,0 +1,110 @@
+mod common;
+
+use reda_gds::{
+    GdsBoundary, GdsElementKind, GdsLibrary, GdsProfile, GdsReadError, GdsSeverity, GdsSref, GdsStreamWriter, GdsStructure, GdsText, GdsValidator, GdsViolationKind, GdsWriteError, GdsWriter, ReadOptions
+};
+use common::{find_record, gds_bytes, library};
+
+const BOUNDARY: u16 = 0x0800;
+
+/// A text, a boundary on layer 300 and a valid boundary
+fn bad_layer() -> GdsLibrary {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsText::new(1, (0, 0), "a"));
+    top.push(GdsBoundary::rect(300, (0, 0), (10, 10)));
+    top.push(GdsBoundary::rect(1, (0, 0), (10, 10)));
+    library("lib", vec![top])
+}
+
+#[test]
+fn violations_are_indexed_in_element_order() {
+    let violations = GdsValidator::new(GdsProfile::common()).validate(&bad_layer());
+    let [violation] = &violations[..] else {
+        panic!("{violations:?}");
+    };
+    assert_eq!(violation.structure, "top");
+    assert_eq!(violation.element, Some((GdsElementKind::Boundary, 1)));
+    assert_eq!(violation.kind, GdsViolationKind::LayerOutOfRange(300, 255));
+}
+
+#[test]
+fn undefined_references_depend_on_the_profile() {
+    let mut top = GdsStructure::new("top");
+    top.push(GdsSref::new("missing", (0, 0), None));
+    let library = library("lib", vec![top]);
+
+    let violations = GdsValidator::new(GdsProfile::common()).validate(&library);
+    assert_eq!(violations[0].kind, GdsViolationKind::UndefinedStructure("missing".to_string()));
+
+    let profile = GdsProfile { allow_undefined_references: true, ..GdsProfile::common() };
+    assert!(GdsValidator::new(profile).validate(&library).is_empty());
+}
+
+#[test]
+fn writer_refuses_invalid_libraries() {
+    let mut bytes = vec![];
+    let result = GdsWriter::new(&mut bytes).with_validation(GdsProfile::common()).write(&bad_layer());
+    assert!(matches!(result, Err(GdsWriteError::Invalid(v)) if v.len() == 1));
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn reader_refuses_invalid_libraries() {
+    let bytes = gds_bytes(&bad_layer());
+    let options = ReadOptions::default().with_validation(GdsProfile::common());
+    let result = GdsLibrary::read_with_options(bytes.as_slice(), options);
+    assert!(matches!(result, Err(GdsReadError::Invalid(v)) if v[0].element == Some((GdsElementKind::Boundary, 1))));
+
+    // Without a profile anything that parses is read
+    assert!(GdsLibrary::read(bytes.as_slice()).is_ok());
+}
+
+#[test]
+fn recovery_reports_violations_where_the_element_starts() {
+    let bytes = gds_bytes(&bad_layer());
+    let options = ReadOptions::recovery().with_validation(GdsProfile::common());
+    let (library, diagnostics) = GdsLibrary::read_with_options(bytes.as_slice(), options).unwrap();
+    assert_eq!(library.structures["top"].read().unwrap().boundarys().count(), 2);
+
+    let [diagnostic] = &diagnostics[..] else {
+        panic!("{diagnostics:?}");
+    };
+    assert_eq!(diagnostic.severity, GdsSeverity::Warning);
+    assert_eq!(diagnostic.structure.as_deref(), Some("top"));
+    assert_eq!(diagnostic.element_index, Some(1));
+    assert_eq!(diagnostic.offset, find_record(&bytes, BOUNDARY, 0) as u64);
+}
+
+#[test]
+fn stream_writer_refuses_invalid_elements() {
+    let header = library("lib", vec![]);
+    let mut writer = GdsStreamWriter::new(vec![], &header).unwrap().with_validation(GdsProfile::strict());
+
+    assert!(matches!(writer.begin_structure("bad name"), Err(GdsWriteError::Invalid(_))));
+    writer.begin_structure("top").unwrap();
+    writer.write_text(&GdsText::new(1, (0, 0), "a")).unwrap();
+    let result = writer.write_boundary(&GdsBoundary::rect(64, (0, 0), (10, 10)));
+    match result {
+        Err(GdsWriteError::Invalid(violations)) => {
+            assert_eq!(violations[0].structure, "top");
+            assert_eq!(violations[0].element, Some((GdsElementKind::Boundary, 1)));
+        }
+        other => panic!("{other:?}"),
+    }
+
+    // Nothing was written for the refused element
+    writer.write_boundary(&GdsBoundary::rect(1, (0, 0), (10, 10))).unwrap();
+    writer.end_structure().unwrap();
+    let bytes = writer.finish().unwrap();
+    let library = GdsLibrary::read(bytes.as_slice()).unwrap();
+    assert_eq!(library.structures["top"].read().unwrap().element_count(), 2);
+}
+
+#[test]
+fn stream_writer_checks_whole_structures() {
+    let header = library("lib", vec![]);
+    let mut writer = GdsStreamWriter::new(vec![], &header).unwrap().with_validation(GdsProfile::common());
+    let bad = bad_layer();
+    let top = bad.structures["top"].read().unwrap();
+    assert!(matches!(writer.write_structure(&top), Err(GdsWriteError::Invalid(_))));
+}